@@ -1,13 +1,14 @@
-use std::io::{BufReader, ErrorKind, Seek};
+use std::io::{BufReader, Cursor, ErrorKind, Seek};
+use std::sync::OnceLock;
 
 use bencher::{benchmark_group, benchmark_main, Bencher};
 use molly::{
     reader,
-    selection::{AtomSelection, FrameSelection},
-    Frame, Magic, XTCReader,
+    selection::{AtomSelection, FrameSelection, SelectionStrategy},
+    Frame, Magic, Precision, XTCReader,
 };
 
-benchmark_main!(reading, decoding);
+benchmark_main!(reading, decoding, scanning);
 benchmark_group!(
     reading,
     read_frame,
@@ -16,6 +17,13 @@ benchmark_group!(
     read_frames_buffered,
     read_frames_few_atoms,
     read_frames_few_atoms_buffered,
+    read_all_frames,
+    read_all_frames_with_capacity,
+    read_all_frames_bufreader,
+    read_frame_sparse_during_decode,
+    read_frame_sparse_after_decode,
+    read_frame_dense_during_decode,
+    read_frame_dense_after_decode,
 );
 benchmark_group!(
     decoding,
@@ -24,8 +32,35 @@ benchmark_group!(
     read_compressed_positions_from_file_buffered,
 );
 
+benchmark_group!(scanning, determine_offsets_large, scan_large);
+
 const PATH: &str = "tests/trajectories/adk_oplsaa.xtc";
 
+/// A large synthetic trajectory of many small frames, generated once and kept in memory.
+///
+/// With little data per frame, the time it takes to scan such a trajectory is dominated by the
+/// overhead per frame.
+fn synthetic_trajectory() -> Cursor<&'static [u8]> {
+    const NFRAMES: usize = 100_000;
+    const NATOMS: usize = 32;
+    static BYTES: OnceLock<Vec<u8>> = OnceLock::new();
+    let bytes = BYTES.get_or_init(|| {
+        let mut bytes = Vec::new();
+        for idx in 0..NFRAMES {
+            let frame = Frame {
+                step: idx as u64,
+                time: idx as f32,
+                precision: Precision::Compressed(1000.0),
+                positions: (0..NATOMS * 3).map(|i| (i + idx) as f32 * 0.01).collect(),
+                ..Default::default()
+            };
+            molly::writer::write_frame(&mut bytes, &frame, Magic::Xtc1995).unwrap();
+        }
+        bytes
+    });
+    Cursor::new(bytes)
+}
+
 fn read_frame(b: &mut Bencher) {
     let mut reader = XTCReader::open(PATH).unwrap();
     let mut frame = Frame::default();
@@ -91,6 +126,64 @@ fn read_frames_few_atoms_buffered(b: &mut Bencher) {
     });
 }
 
+fn read_all_frames(b: &mut Bencher) {
+    let mut reader = XTCReader::open(PATH).unwrap();
+    b.iter(|| {
+        reader.home().unwrap();
+        reader.read_all_frames().unwrap()
+    });
+}
+
+fn read_all_frames_with_capacity(b: &mut Bencher) {
+    let mut reader = XTCReader::open(PATH).unwrap();
+    b.iter(|| {
+        reader.home().unwrap();
+        let n = reader.frame_count().unwrap();
+        reader.read_all_frames_with_capacity(n).unwrap()
+    });
+}
+
+fn read_all_frames_bufreader(b: &mut Bencher) {
+    let mut reader = XTCReader::open_buffered(PATH).unwrap();
+    b.iter(|| {
+        reader.home().unwrap();
+        reader.read_all_frames().unwrap()
+    });
+}
+
+/// Read frames with a selection of every `step`th atom, spread over the whole frame.
+fn read_frame_strided(b: &mut Bencher, step: usize, strategy: SelectionStrategy) {
+    let mut reader = XTCReader::open(PATH).unwrap();
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame).unwrap();
+    let natoms = frame.natoms() as u32;
+    let indices: Vec<u32> = (0..natoms).step_by(step).collect();
+    let selection = AtomSelection::from_index_list(&indices);
+    b.iter(
+        || match reader.read_frame_with_strategy(&mut frame, &selection, strategy) {
+            Ok(_) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => reader.home().unwrap(),
+            Err(err) => panic!("{err}"),
+        },
+    );
+}
+
+fn read_frame_sparse_during_decode(b: &mut Bencher) {
+    read_frame_strided(b, 300, SelectionStrategy::DuringDecode)
+}
+
+fn read_frame_sparse_after_decode(b: &mut Bencher) {
+    read_frame_strided(b, 300, SelectionStrategy::AfterDecode)
+}
+
+fn read_frame_dense_during_decode(b: &mut Bencher) {
+    read_frame_strided(b, 2, SelectionStrategy::DuringDecode)
+}
+
+fn read_frame_dense_after_decode(b: &mut Bencher) {
+    read_frame_strided(b, 2, SelectionStrategy::AfterDecode)
+}
+
 fn read_compressed_positions(b: &mut Bencher) {
     let magic = Magic::Xtc1995;
     let natoms = 125;
@@ -168,3 +261,17 @@ fn read_compressed_positions_from_file_buffered(b: &mut Bencher) {
         .unwrap()
     });
 }
+
+fn determine_offsets_large(b: &mut Bencher) {
+    let mut reader = XTCReader::new(synthetic_trajectory());
+    b.iter(|| {
+        // Make sure the offsets are determined anew, rather than taken from the cache.
+        reader.invalidate_offsets();
+        reader.determine_offsets(None).unwrap()
+    });
+}
+
+fn scan_large(b: &mut Bencher) {
+    let mut reader = XTCReader::new(synthetic_trajectory());
+    b.iter(|| reader.scan(None).unwrap());
+}
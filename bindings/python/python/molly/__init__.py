@@ -1,3 +1,3 @@
-from ._molly import XTCReader
+from ._molly import InvalidFileError, TruncatedFileError, XTCReader
 
-__all__ = ["XTCReader"]
+__all__ = ["XTCReader", "InvalidFileError", "TruncatedFileError"]
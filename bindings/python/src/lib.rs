@@ -1,26 +1,88 @@
 #![allow(non_local_definitions, dead_code)]
 
 use std::io;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU32, NonZeroU64};
 use std::path::PathBuf;
 
 use molly::selection;
-use numpy::ndarray::{Array, Axis};
-use numpy::{IntoPyArray, Ix2, PyArray, PyReadwriteArrayDyn, PyUntypedArrayMethods};
-use pyo3::exceptions::{PyTypeError, PyValueError};
+use numpy::ndarray::{Array3, ArrayView2, Axis};
+use numpy::{
+    IntoPyArray, Ix1, Ix2, Ix3, PyArray, PyArray1, PyArrayMethods, PyReadwriteArrayDyn,
+    PyUntypedArrayMethods,
+};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyIndexError, PyOSError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyIterator, PyList, PySlice};
 
 type BoxVec = [[f32; 3]; 3];
 
-#[derive(Debug, Default)]
-struct FrameSelection(selection::FrameSelection);
+create_exception!(
+    molly,
+    InvalidFileError,
+    PyOSError,
+    "Raised when a file does not contain valid xtc data, such as an unknown magic number."
+);
+create_exception!(
+    molly,
+    TruncatedFileError,
+    PyOSError,
+    "Raised when a file ends before the data that was being read is complete."
+);
+
+/// A molly reader error that is raised as a specific Python exception.
+///
+/// Errors that indicate invalid data become an `InvalidFileError`, and unexpected ends of files
+/// become a `TruncatedFileError`. Both are subclasses of `OSError`. Any other error is raised as
+/// the `OSError` that pyo3 would otherwise produce.
+#[derive(Debug)]
+struct Error(io::Error);
+
+type Result<T> = std::result::Result<T, Error>;
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self(err)
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(Error(err): Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::InvalidData => InvalidFileError::new_err(err.to_string()),
+            io::ErrorKind::UnexpectedEof => TruncatedFileError::new_err(err.to_string()),
+            _ => err.into(),
+        }
+    }
+}
+
+/// A selection of frames, as passed from Python.
+///
+/// Like a Python sequence, the frames may be indexed from the end with negative indices, and
+/// indices beyond the frames are rejected. Such a selection can only be resolved once the number
+/// of frames is known, which takes a scan of the headers. See `XTCReader::resolve_frame_selection`.
+#[derive(Debug)]
+enum FrameSelection {
+    /// A selection that does not depend on the number of frames.
+    Resolved(selection::FrameSelection),
+    /// A slice with a negative start or stop.
+    Slice {
+        start: Option<i64>,
+        stop: Option<i64>,
+        step: Option<NonZeroU64>,
+    },
+    /// A list of indices, which count from the end if they are negative.
+    Indices(Vec<i64>),
+    /// A boolean mask with a value for each frame.
+    Mask(Vec<bool>),
+}
+
 #[derive(Debug, Default)]
 struct AtomSelection(selection::AtomSelection);
 
-impl From<FrameSelection> for selection::FrameSelection {
-    fn from(val: FrameSelection) -> Self {
-        val.0
+impl Default for FrameSelection {
+    fn default() -> Self {
+        Self::Resolved(selection::FrameSelection::default())
     }
 }
 
@@ -30,32 +92,158 @@ impl From<AtomSelection> for selection::AtomSelection {
     }
 }
 
+impl FrameSelection {
+    /// Select the frames at the indices where the `mask` is true.
+    ///
+    /// Like a NumPy boolean index, the mask must have a value for each frame.
+    fn from_mask(mask: impl IntoIterator<Item = bool>) -> Self {
+        Self::Mask(mask.into_iter().collect())
+    }
+
+    /// Select the frames in a slice, of which the `start` and `stop` count from the end if they
+    /// are negative.
+    fn from_slice(start: Option<i64>, stop: Option<i64>, step: Option<i64>) -> PyResult<Self> {
+        let step = step
+            .map(|step| {
+                u64::try_from(step)
+                    .ok()
+                    .and_then(NonZeroU64::new)
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "the step of a frame slice must be positive, found {step}"
+                        ))
+                    })
+            })
+            .transpose()?;
+        if start.is_some_and(i64::is_negative) || stop.is_some_and(i64::is_negative) {
+            return Ok(Self::Slice { start, stop, step });
+        }
+        let (start, stop) = (start.map(|v| v as u64), stop.map(|v| v as u64));
+        Ok(Self::Resolved(frame_range(start, stop, step)))
+    }
+
+    /// Select the frames at the `indices`, which count from the end if they are negative.
+    fn from_indices(indices: Vec<i64>) -> Self {
+        Self::Indices(indices)
+    }
+
+    /// Resolve this selection for a trajectory of `nframes` frames.
+    ///
+    /// Like the bounds of a Python slice, negative bounds beyond the first frame are clamped to
+    /// it. Like a Python sequence, an index beyond the first or last frame raises an
+    /// `IndexError`. Like NumPy, so does a mask that does not have a value for each frame.
+    fn resolve(self, nframes: usize) -> PyResult<selection::FrameSelection> {
+        let nframes = nframes as u64;
+        match self {
+            Self::Resolved(selection) => Ok(selection),
+            Self::Slice { start, stop, step } => {
+                let bound = |bound: i64| match u64::try_from(bound) {
+                    Ok(bound) => bound,
+                    Err(_) => nframes.saturating_sub(bound.unsigned_abs()),
+                };
+                Ok(frame_range(start.map(bound), stop.map(bound), step))
+            }
+            Self::Indices(indices) => {
+                let indices = indices
+                    .into_iter()
+                    .map(|idx| resolve_index(idx, nframes as usize))
+                    .collect::<PyResult<Vec<usize>>>()?;
+                Ok(selection::FrameSelection::framelist_from_iter(indices))
+            }
+            Self::Mask(mask) => {
+                if mask.len() as u64 != nframes {
+                    return Err(PyIndexError::new_err(format!(
+                        "the frame mask has {} values, but there are {nframes} frames",
+                        mask.len()
+                    )));
+                }
+                let indices = mask
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(idx, selected)| selected.then_some(idx));
+                Ok(selection::FrameSelection::framelist_from_iter(indices))
+            }
+        }
+    }
+}
+
+/// Returns the frame index for an `idx` that counts from the end if it is negative, in a
+/// trajectory of `nframes` frames.
+///
+/// An index beyond the first or last frame raises an `IndexError`.
+fn resolve_index(idx: i64, nframes: usize) -> PyResult<usize> {
+    let resolved = if idx < 0 { idx + nframes as i64 } else { idx };
+    usize::try_from(resolved)
+        .ok()
+        .filter(|&resolved| resolved < nframes)
+        .ok_or_else(|| {
+            PyIndexError::new_err(format!(
+                "frame index {idx} is out of range for {nframes} frames"
+            ))
+        })
+}
+
+/// Flush an `array` to disk if it is a `np.memmap`, and do nothing otherwise.
+fn flush_memmap(py: Python<'_>, array: &Bound<'_, PyAny>) -> PyResult<()> {
+    let memmap = py.import("numpy")?.getattr("memmap")?;
+    if array.is_instance(&memmap)? {
+        array.call_method0("flush")?;
+    }
+    Ok(())
+}
+
+/// Returns the range of frames from `start` up to `stop`, which is empty if `start` exceeds
+/// `stop`, like a Python slice.
+fn frame_range(
+    start: Option<u64>,
+    stop: Option<u64>,
+    step: Option<NonZeroU64>,
+) -> selection::FrameSelection {
+    let stop = stop.map(|stop| stop.max(start.unwrap_or_default()));
+    selection::FrameSelection::Range(selection::Range::new(start, stop, step))
+}
+
 impl FromPyObject<'_> for FrameSelection {
     fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
         if let Ok(selection) = ob.downcast::<PySlice>() {
             // TODO: This getattr business seems silly, but maybe it's necessary?
-            let start = selection.getattr("start")?.extract().ok();
-            let end = selection.getattr("stop")?.extract().ok();
-            let step = selection.getattr("step")?.extract::<NonZeroU64>().ok();
-            let range = selection::Range::new(start, end, step);
-            return Ok(FrameSelection(selection::FrameSelection::Range(range)));
+            let start = selection.getattr("start")?.extract()?;
+            let stop = selection.getattr("stop")?.extract()?;
+            let step = selection.getattr("step")?.extract()?;
+            return FrameSelection::from_slice(start, stop, step);
+        }
+
+        // A boolean mask, such as the result of a per-frame analysis. This must be checked before
+        // the indices, since a bool will happily be extracted as an integer.
+        if let Ok(mask) = ob.downcast::<PyArray1<bool>>() {
+            return Ok(FrameSelection::from_mask(
+                mask.readonly().as_array().iter().copied(),
+            ));
+        }
+        // An empty list could be either, but selects no frames like an empty list of indices,
+        // rather than being a mask of the wrong length.
+        if ob.downcast::<PyList>().is_ok_and(|list| list.is_empty()) {
+            return Ok(FrameSelection::from_indices(Vec::new()));
+        }
+        if let Ok(mask) = ob
+            .downcast::<PyList>()
+            .map_err(PyErr::from)
+            .and_then(|list| list.extract::<Vec<bool>>())
+        {
+            return Ok(FrameSelection::from_mask(mask));
         }
 
         if let Ok(indices) = ob.downcast::<PyList>().map_err(PyErr::from).and_then(|it| {
             it.iter()
-                .map(|i| i.extract::<usize>())
-                .collect::<PyResult<Vec<usize>>>()
+                .map(|i| i.extract::<i64>())
+                .collect::<PyResult<Vec<i64>>>()
         }) {
-            return Ok(FrameSelection(
-                selection::FrameSelection::framelist_from_iter(indices),
-            ));
+            return Ok(FrameSelection::from_indices(indices));
         }
 
         if let Ok(it) = ob.downcast::<PyIterator>() {
-            if let Ok(indices) = it.extract::<Vec<usize>>() {
-                return Ok(FrameSelection(
-                    selection::FrameSelection::framelist_from_iter(indices),
-                ));
+            if let Ok(indices) = it.extract::<Vec<i64>>() {
+                return Ok(FrameSelection::from_indices(indices));
             }
         }
 
@@ -72,6 +260,24 @@ impl FromPyObject<'_> for AtomSelection {
             return Ok(AtomSelection(selection::AtomSelection::Until(until)));
         }
 
+        if let Ok(selection) = ob.downcast::<PySlice>() {
+            let start = selection.getattr("start")?.extract::<Option<u32>>()?;
+            let stop = selection.getattr("stop")?.extract::<Option<u32>>()?;
+            let step = selection.getattr("step")?.extract::<Option<NonZeroU32>>()?;
+            let start = start.unwrap_or(0);
+            let step = step.unwrap_or(NonZeroU32::MIN);
+            let selection = match stop {
+                // Without a stop, the stride runs up to the end of each frame.
+                None => selection::AtomSelection::Stride { start, step },
+                Some(stop) => selection::AtomSelection::from_index_list(
+                    &(start..stop)
+                        .step_by(step.get() as usize)
+                        .collect::<Vec<_>>(),
+                ),
+            };
+            return Ok(AtomSelection(selection));
+        }
+
         if let Ok(list) = ob.downcast::<PyList>() {
             if let Ok(bools) = list.extract::<Vec<bool>>() {
                 return Ok(AtomSelection(selection::AtomSelection::Mask(bools)));
@@ -98,10 +304,13 @@ struct XTCReader {
 #[pymethods]
 impl XTCReader {
     /// Open a file as an `XTCReader`.
+    ///
+    /// If an `offset` is given, the xtc data is read starting at that byte offset in the file,
+    /// and `home` returns to it.
     #[new]
-    #[pyo3(signature = (path, buffered=true))]
-    fn open(path: PathBuf, buffered: bool) -> io::Result<Self> {
-        let inner = molly::XTCReader::open(path)?;
+    #[pyo3(signature = (path, buffered=true, offset=0))]
+    fn open(path: PathBuf, buffered: bool, offset: u64) -> Result<Self> {
+        let inner = molly::XTCReader::open_at(path, offset)?;
         Ok(Self {
             inner,
             frame: None,
@@ -133,35 +342,71 @@ impl XTCReader {
     /// frame offsets *from* its position are determined. If you wish to determine the offsets from
     /// the initial reader position, call `XTCReader.home` before calling this function.
     #[pyo3(signature = (until=None))]
-    fn determine_offsets(&mut self, until: Option<usize>) -> io::Result<Vec<u64>> {
-        self.inner.determine_offsets(until).map(|l| l.to_vec())
+    fn determine_offsets(&mut self, until: Option<usize>) -> Result<Vec<u64>> {
+        Ok(self.inner.determine_offsets(until)?.to_vec())
     }
 
     /// Returns the frame sizes in bytes of this `XTCReader`.
     #[pyo3(signature = (until=None))]
-    fn determine_frame_sizes(&mut self, until: Option<usize>) -> io::Result<Vec<u64>> {
-        self.inner.determine_frame_sizes(until).map(|l| l.to_vec())
+    fn determine_frame_sizes(&mut self, until: Option<usize>) -> Result<Vec<u64>> {
+        Ok(self.inner.determine_frame_sizes(until)?.to_vec())
     }
 
     /// Reset the reading head to the start of the file.
-    fn home(&mut self) -> PyResult<()> {
+    fn home(&mut self) -> Result<()> {
         Ok(self.inner.home()?)
     }
 
+    /// Open the file anew and reset the reading head to the start of it.
+    ///
+    /// Unlike `home`, this sees the current contents of the path, even if the file has been
+    /// replaced since it was opened.
+    fn reopen(&mut self) -> Result<()> {
+        Ok(self.inner.reopen()?)
+    }
+
     /// Read a single frame into the `frame` field of the `XTCReader`.
-    fn read_frame(&mut self) -> io::Result<()> {
+    fn read_frame(&mut self) -> Result<()> {
         if self.frame.is_none() {
             self.frame = Some(Frame::default());
         }
         let frame = &mut self.frame.as_mut().unwrap().inner;
-        self.inner.read_frame(frame)
+        Ok(self.inner.read_frame(frame)?)
     }
 
     /// Read a single frame and return a copy.
     ///
-    /// Calls `read_frame` internally and returns the frame immediately.
-    fn pop_frame(&mut self) -> io::Result<Frame> {
-        self.read_frame()?;
+    /// Without an `index`, this calls `read_frame` internally and returns the frame immediately.
+    ///
+    /// With an `index`, the frame at that index is read, where a negative index counts from the
+    /// end, such that `pop_frame(-1)` returns the last frame. Like the frame selections of
+    /// `read_frames`, the index counts from the current position of the reader. The reader is left
+    /// after the frame that was read. An index beyond the first or last frame raises an
+    /// `IndexError`.
+    #[pyo3(signature = (index=None))]
+    fn pop_frame(&mut self, index: Option<i64>) -> PyResult<Frame> {
+        let Some(index) = index else {
+            self.read_frame()?;
+            return Ok(self.frame.clone().unwrap());
+        };
+
+        let nframes = self.inner.frame_count().map_err(Error)?;
+        let idx = resolve_index(index, nframes)?;
+        let offset = self.inner.determine_offsets(Some(idx + 1)).map_err(Error)?[idx];
+        // The frame is read at the step of its index, which matters for a step remap.
+        self.inner.step += idx;
+        let frame = &mut self.frame.get_or_insert_with(Frame::default).inner;
+        let all = selection::AtomSelection::All;
+        match self.buffered {
+            true => self
+                .inner
+                .read_frame_at_offset::<true>(frame, offset, &all)
+                .map_err(Error)?,
+            false => self
+                .inner
+                .read_frame_at_offset::<false>(frame, offset, &all)
+                .map_err(Error)?,
+        };
         Ok(self.frame.clone().unwrap())
     }
 
@@ -176,29 +421,142 @@ impl XTCReader {
         &mut self,
         frame_selection: Option<FrameSelection>,
         atom_selection: Option<AtomSelection>,
-    ) -> io::Result<Vec<Frame>> {
+    ) -> PyResult<Vec<Frame>> {
         let mut frames = Vec::new();
-        let frame_selection = frame_selection.unwrap_or_default().into();
+        let frame_selection = self.resolve_frame_selection(frame_selection)?;
         let atom_selection = atom_selection.unwrap_or_default().into();
         match self.buffered {
-            true => {
-                self.inner
-                    .read_frames::<true>(&mut frames, &frame_selection, &atom_selection)?
-            }
-            false => {
-                self.inner
-                    .read_frames::<false>(&mut frames, &frame_selection, &atom_selection)?
-            }
+            true => self
+                .inner
+                .read_frames::<true>(&mut frames, &frame_selection, &atom_selection)
+                .map_err(Error)?,
+            false => self
+                .inner
+                .read_frames::<false>(&mut frames, &frame_selection, &atom_selection)
+                .map_err(Error)?,
         };
 
         Ok(frames.into_iter().map(|frame| frame.into()).collect())
     }
 
+    /// Read frames according to the selections and return their positions stacked into a single
+    /// `np.ndarray` of shape `(nframes, natoms, 3)` and `dtype=np.float32`.
+    ///
+    /// The number of atoms is inferred from the first selected frame. All selected frames must
+    /// contain that same number of atoms, otherwise a `ValueError` is raised.
+    ///
+    /// # Note
+    ///
+    /// This function can perform the reads in a buffered manner, depending on the value of the
+    /// `buffered` attribute.
+    #[pyo3(signature = (frame_selection=None, atom_selection=None))]
+    fn read_stack<'py>(
+        &mut self,
+        py: Python<'py>,
+        frame_selection: Option<FrameSelection>,
+        atom_selection: Option<AtomSelection>,
+    ) -> PyResult<Bound<'py, PyArray<f32, Ix3>>> {
+        let frame_selection = self.resolve_frame_selection(frame_selection)?;
+        let atom_selection: selection::AtomSelection = atom_selection.unwrap_or_default().into();
+        let offsets = self.selected_offsets(&frame_selection)?;
+
+        let mut frame = molly::Frame::default();
+        let mut stack: Option<Array3<f32>> = None;
+        for (i, &offset) in offsets.iter().enumerate() {
+            py.check_signals()?;
+            match self.buffered {
+                true => {
+                    self.inner
+                        .read_frame_at_offset::<true>(&mut frame, offset, &atom_selection)
+                        .map_err(Error)?;
+                }
+                false => {
+                    self.inner
+                        .read_frame_at_offset::<false>(&mut frame, offset, &atom_selection)
+                        .map_err(Error)?;
+                }
+            };
+            // Now that we know the number of atoms from the first frame, we can allocate the
+            // whole output array at once.
+            let stack =
+                stack.get_or_insert_with(|| Array3::zeros((offsets.len(), frame.natoms(), 3)));
+            let natoms = stack.shape()[1];
+            if frame.natoms() != natoms {
+                return Err(PyValueError::new_err(format!(
+                    "cannot stack frames with different numbers of atoms: frame {i} has {} atoms, \
+                    but {natoms} were expected",
+                    frame.natoms()
+                )));
+            }
+            // Unwrap should be fine here, since the freshly allocated stack is contiguous.
+            stack
+                .index_axis_mut(Axis(0), i)
+                .as_slice_mut()
+                .unwrap()
+                .copy_from_slice(&frame.positions);
+        }
+
+        let stack = stack.unwrap_or_else(|| Array3::zeros((0, 0, 3)));
+        Ok(stack.into_pyarray(py))
+    }
+
+    /// Returns the times (ps) of the first and last frames as a tuple `(first, last)`.
+    ///
+    /// Only those two headers are read, once the frame offsets are known.
+    fn time_bounds(&mut self) -> Result<(f32, f32)> {
+        Ok(self.inner.time_bounds()?)
+    }
+
+    /// Read the times (ps) of the selected frames into a 1-dimensional `np.ndarray`.
+    ///
+    /// Only the frame headers are read, so no positions are decoded.
+    #[pyo3(signature = (frame_selection=None))]
+    fn read_times<'py>(
+        &mut self,
+        py: Python<'py>,
+        frame_selection: Option<FrameSelection>,
+    ) -> PyResult<Bound<'py, PyArray<f32, Ix1>>> {
+        let frame_selection = self.resolve_frame_selection(frame_selection)?;
+        let metas = self.scan_selected(&frame_selection)?;
+        let times: Vec<f32> = metas.iter().map(|meta| meta.time).collect();
+        Ok(times.into_pyarray(py))
+    }
+
+    /// Read the steps of the selected frames into a 1-dimensional `np.ndarray`.
+    ///
+    /// Only the frame headers are read, so no positions are decoded.
+    #[pyo3(signature = (frame_selection=None))]
+    fn read_steps<'py>(
+        &mut self,
+        py: Python<'py>,
+        frame_selection: Option<FrameSelection>,
+    ) -> PyResult<Bound<'py, PyArray<u64, Ix1>>> {
+        let frame_selection = self.resolve_frame_selection(frame_selection)?;
+        let metas = self.scan_selected(&frame_selection)?;
+        let steps: Vec<u64> = metas.iter().map(|meta| meta.step).collect();
+        Ok(steps.into_pyarray(py))
+    }
+
+    /// Returns the number of frames that are included in the `frame_selection`.
+    ///
+    /// This is the number of frames that `read_frames` returns and that `read_into_array` fills
+    /// for the same selection, so it can be used to allocate the arrays for the latter.
+    #[pyo3(signature = (frame_selection=None))]
+    fn count_frames(&mut self, frame_selection: Option<FrameSelection>) -> PyResult<usize> {
+        let frame_selection = self.resolve_frame_selection(frame_selection)?;
+        let offsets = self
+            .inner
+            .determine_offsets(frame_selection.until())
+            .map_err(Error)?;
+        Ok(frame_selection.estimated_count(offsets.len()))
+    }
+
     /// Read all frames into the provided `np.ndarray`.
     ///
     /// The `coordinate_array` must have a shape of `(nframes, natoms, 3)` and have `dtype=np.float32`.
     ///
     /// The `boxvec_array` must have a shape of `(nframes, 3, 3)` and have `dtype=np.float32`.
+    /// Each box is written with one box vector per row, just like `Frame.box_rows`.
     ///
     /// Returns `True` if the reading operation was successful.
     ///
@@ -206,6 +564,37 @@ impl XTCReader {
     ///
     /// This function can perform the reads in a buffered manner, depending on the value of the
     /// `buffered` attribute.
+    ///
+    /// # Tensors
+    ///
+    /// The positions are decoded straight into the memory of the provided arrays. To fill a
+    /// PyTorch tensor without an extra copy, pass a NumPy view of it, which shares its memory:
+    ///
+    /// ```python
+    /// coordinates = torch.empty((nframes, natoms, 3), dtype=torch.float32)
+    /// boxvecs = torch.empty((nframes, 3, 3), dtype=torch.float32)
+    /// reader.read_into_array(coordinates.numpy(), boxvecs.numpy())
+    /// ```
+    ///
+    /// This works for any contiguous tensor in host memory. For training on a GPU, decode into a
+    /// pinned host tensor (`torch.empty(..., pin_memory=True)`) and move it to the device with
+    /// `tensor.to("cuda", non_blocking=True)`. Frameworks with immutable arrays, such as JAX, can
+    /// take the filled array without a copy through `jax.dlpack.from_dlpack`.
+    ///
+    /// # Memory-mapped arrays
+    ///
+    /// The frames are decoded one at a time, so reading uses the memory of a single frame beyond
+    /// the provided arrays. Selections that do not fit in memory can therefore be read into a
+    /// `np.memmap`, which is written to disk as it is filled:
+    ///
+    /// ```python
+    /// nframes = reader.count_frames(frame_selection)
+    /// coordinates = np.memmap(path, dtype=np.float32, mode="w+", shape=(nframes, natoms, 3))
+    /// boxvecs = np.zeros((nframes, 3, 3), dtype=np.float32)
+    /// reader.read_into_array(coordinates, boxvecs, frame_selection=frame_selection)
+    /// ```
+    ///
+    /// Any `np.memmap` among the arrays is flushed once all frames have been read.
     #[pyo3(signature = (coordinate_array, boxvec_array, time_array=None, frame_selection=None, atom_selection=None))]
     fn read_into_array<'py>(
         &mut self,
@@ -269,41 +658,27 @@ impl XTCReader {
         let mut times = time_array.as_mut().map(|ts| ts.as_array_mut());
 
         let atom_selection: selection::AtomSelection = atom_selection.unwrap_or_default().into();
+        let frame_selection = self.resolve_frame_selection(frame_selection)?;
         let mut frame = molly::Frame::default();
-        let until = frame_selection
-            .as_ref()
-            .and_then(|FrameSelection(selection)| selection.until());
-        let offsets = self.inner.determine_offsets(until)?;
-        let offsets = offsets.iter().enumerate().filter_map(|(idx, offset)| {
-            if let Some(FrameSelection(selection)) = &frame_selection {
-                match selection.is_included(idx) {
-                    Some(true) => Some(offset),
-                    Some(false) => None,
-                    None => None,
-                }
-            } else {
-                Some(offset)
-            }
-        });
+        let offsets = self.selected_offsets(&frame_selection)?;
         // TODO: Fix up this mess of zips.
         for (i, ((mut array_coordinates, mut array_boxvecs), &offset)) in coordinates
             .axis_iter_mut(Axis(0))
             .zip(boxvecs.axis_iter_mut(Axis(0)))
-            .zip(offsets)
+            .zip(offsets.iter())
             .enumerate()
         {
             py.check_signals()?;
             match self.buffered {
                 true => {
                     self.inner
-                        .read_frame_at_offset::<true>(&mut frame, offset, &atom_selection)?;
+                        .read_frame_at_offset::<true>(&mut frame, offset, &atom_selection)
+                        .map_err(Error)?;
                 }
                 false => {
-                    self.inner.read_frame_at_offset::<false>(
-                        &mut frame,
-                        offset,
-                        &atom_selection,
-                    )?;
+                    self.inner
+                        .read_frame_at_offset::<false>(&mut frame, offset, &atom_selection)
+                        .map_err(Error)?;
                 }
             };
             // TODO: Check whether the two unwraps here can just be elided somehow.
@@ -316,9 +691,9 @@ impl XTCReader {
                     frame_coord.write_to_slice(array_coord.as_slice_mut().unwrap())
                 });
             array_boxvecs
-                .columns_mut()
+                .rows_mut()
                 .into_iter()
-                .zip(frame.boxvec.to_cols_array_2d())
+                .zip(frame.box_rows())
                 .for_each(|(array_boxvec, frame_boxvec)| {
                     for (&frame_value, array_value) in frame_boxvec.iter().zip(array_boxvec) {
                         *array_value = frame_value
@@ -329,14 +704,77 @@ impl XTCReader {
             }
         }
 
+        flush_memmap(py, coordinate_array.as_any())?;
+        flush_memmap(py, boxvec_array.as_any())?;
+        if let Some(ref time_array) = time_array {
+            flush_memmap(py, time_array.as_any())?;
+        }
+
         Ok(true)
     }
 }
 
+impl XTCReader {
+    /// Resolve a `FrameSelection` from Python, which defaults to all frames.
+    ///
+    /// If the selection counts from the end, the frames from the current position are counted
+    /// by reading their headers.
+    fn resolve_frame_selection(
+        &mut self,
+        frame_selection: Option<FrameSelection>,
+    ) -> PyResult<selection::FrameSelection> {
+        match frame_selection.unwrap_or_default() {
+            FrameSelection::Resolved(selection) => Ok(selection),
+            frame_selection => {
+                let nframes = self.inner.frame_count().map_err(Error)?;
+                frame_selection.resolve(nframes)
+            }
+        }
+    }
+
+    /// Returns the offsets of the frames that are included in a `FrameSelection`.
+    fn selected_offsets(
+        &mut self,
+        frame_selection: &selection::FrameSelection,
+    ) -> Result<Vec<u64>> {
+        let offsets = self.inner.determine_offsets(frame_selection.until())?;
+        let mut selected = Vec::with_capacity(frame_selection.estimated_count(offsets.len()));
+        for (idx, &offset) in offsets.iter().enumerate() {
+            match frame_selection.is_included(idx) {
+                Some(true) => selected.push(offset),
+                Some(false) => continue,
+                None => break,
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Returns the metadata of the frames that are included in a `FrameSelection`.
+    fn scan_selected(
+        &mut self,
+        frame_selection: &selection::FrameSelection,
+    ) -> Result<Vec<molly::FrameMeta>> {
+        let metas = self.inner.scan(frame_selection.until())?;
+        let selected = metas
+            .into_iter()
+            .enumerate()
+            .map_while(|(idx, meta)| {
+                frame_selection
+                    .is_included(idx)
+                    .map(|included| (included, meta))
+            })
+            .filter_map(|(included, meta)| included.then_some(meta))
+            .collect();
+        Ok(selected)
+    }
+}
+
 /// A single trajectory frame.
 ///
 /// All distances are given in nanometers.
-#[pyclass]
+///
+/// A `Frame` is immutable, which allows its positions to be handed out without copying them.
+#[pyclass(frozen)]
 #[derive(Default, Clone)]
 struct Frame {
     inner: molly::Frame,
@@ -351,7 +789,7 @@ impl From<molly::Frame> for Frame {
 #[pymethods]
 impl Frame {
     #[getter]
-    fn get_step(&self) -> u32 {
+    fn get_step(&self) -> u64 {
         self.inner.step
     }
 
@@ -360,29 +798,85 @@ impl Frame {
         self.inner.time
     }
 
-    /// The box vectors of this frame as an array of columns of a 3×3 matrix.
+    /// The index of this frame in the trajectory, if it was read through a frame selection.
+    #[getter]
+    fn get_index(&self) -> Option<usize> {
+        self.inner.index
+    }
+
+    /// The byte offset of this frame in its file, if it was read at an offset.
+    #[getter]
+    fn get_offset(&self) -> Option<u64> {
+        self.inner.offset
+    }
+
+    /// The box vectors of this frame with one box vector per row.
+    ///
+    /// This is the same as `box_rows`, which follows the Gromacs and MDAnalysis convention.
     #[getter]
     fn get_box(&self) -> BoxVec {
-        self.inner.boxvec.to_cols_array_2d()
+        self.inner.box_rows()
     }
 
+    /// The box vectors of this frame with one box vector per row.
+    ///
+    /// The first row is box vector `a`, the second `b`, and the third `c`.
     #[getter]
-    fn get_precision(&self) -> f32 {
-        self.inner.precision
+    fn get_box_rows(&self) -> BoxVec {
+        self.inner.box_rows()
+    }
+
+    /// The box vectors of this frame with one box vector per column.
+    ///
+    /// This is the transpose of `box_rows`.
+    #[getter]
+    fn get_box_cols(&self) -> BoxVec {
+        self.inner.box_cols()
+    }
+
+    /// The lengths (nm) and angles (degrees) of the box as a tuple `([a, b, c], [alpha, beta,
+    /// gamma])`.
+    ///
+    /// The angle `alpha` is between the box vectors `b` and `c`, `beta` between `a` and `c`, and
+    /// `gamma` between `a` and `b`, like the unit cell of a PDB file. A boxless frame gives
+    /// lengths of zero and angles of 90 degrees.
+    #[getter]
+    fn get_box_lengths_angles(&self) -> ([f32; 3], [f32; 3]) {
+        self.inner.box_lengths_and_angles()
+    }
+
+    /// The precision of the positions, or `None` for a frame of 9 atoms or less, whose
+    /// positions are stored uncompressed.
+    #[getter]
+    fn get_precision(&self) -> Option<f32> {
+        self.inner.precision.value()
     }
 
     /// Get the positions as an `np.ndarray`.
     ///
     /// Distances in nanometers.
+    ///
+    /// The returned array is a read-only view into the positions of this frame. No copy is made,
+    /// so accessing this attribute repeatedly is cheap. Use `np.copy` to obtain a writable array.
+    ///
+    /// Note that this used to return a writable copy. In-place operations on the returned array,
+    /// such as `positions -= com`, now raise a `ValueError` and must be applied to a copy.
     #[getter]
-    fn get_positions<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray<f32, Ix2>> {
+    fn get_positions<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray<f32, Ix2>>> {
+        let frame = slf.get();
         // Fingers crossed we don't make any position lists of which the length isn't a multiple of
         // 3... This is a guarantee from the implementation, so It's Fine(tm).
-        let positions = &self.inner.positions;
+        let positions = &frame.inner.positions;
         let natoms = positions.len() / 3;
-        Array::from_shape_vec((natoms, 3), positions.clone())
-            .unwrap()
-            .into_pyarray(py)
+        let view = ArrayView2::from_shape((natoms, 3), positions).unwrap();
+        // Safety: The array holds a reference to this `Frame` as its base object, which keeps the
+        // positions buffer alive for as long as the array exists. The `Frame` is frozen, so it
+        // cannot be modified once it is handed to Python, and the buffer will not be reallocated
+        // underneath the array.
+        let array = unsafe { PyArray::borrow_from_array(&view, slf.clone().into_any()) };
+        // Writing through the view would silently modify the frame, so we forbid it.
+        array.getattr("flags")?.setattr("writeable", false)?;
+        Ok(array)
     }
 }
 
@@ -390,8 +884,10 @@ impl Frame {
 ///
 /// Marieke Westendorp, 2024.
 #[pymodule]
-fn _molly(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+fn _molly(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<XTCReader>()?;
+    m.add("InvalidFileError", py.get_type::<InvalidFileError>())?;
+    m.add("TruncatedFileError", py.get_type::<TruncatedFileError>())?;
 
     Ok(())
 }
@@ -99,6 +99,275 @@ def test_read_into_array(
     return duration
 
 
+def test_read_stack(path, full_mda_frames, frame_selection, atom_selection) -> float:
+    _, molly_reader = setup_readers(path)
+    mda_frames = full_mda_frames[frame_selection]
+    nframes = len(mda_frames)
+    natoms = len(mda_frames[0])
+    print(f"\t\t{nframes = }, {natoms = }")
+    start = time.time()
+    molly_frames = molly_reader.read_stack(
+        frame_selection=frame_selection, atom_selection=atom_selection
+    )
+    duration = time.time() - start
+
+    assert molly_frames.shape == (nframes, natoms, 3), f"{molly_frames.shape = }"
+    for i, (mda_positions, molly_positions) in enumerate(zip(mda_frames, molly_frames)):
+        print("\t\t", i, end="\r")
+
+        assert (
+            mda_positions.tolist() == molly_positions.tolist()
+        ), f"{mda_positions = }\n{molly_positions = }"
+
+    return duration
+
+
+def test_read_times_and_steps(path, frame_selection=None):
+    """The header-only times and steps must match those of the fully read frames."""
+
+    _, molly_reader = setup_readers(path)
+    frames = molly_reader.read_frames(frame_selection=frame_selection)
+    molly_reader.home()
+    times = molly_reader.read_times(frame_selection=frame_selection)
+    molly_reader.home()
+    steps = molly_reader.read_steps(frame_selection=frame_selection)
+
+    assert times.tolist() == [frame.time for frame in frames]
+    assert steps.tolist() == [frame.step for frame in frames]
+
+
+def test_count_frames(path, full_mda_frames, frame_selection=None):
+    """The frame count must match the number of frames that are read."""
+
+    _, molly_reader = setup_readers(path)
+    count = molly_reader.count_frames(frame_selection=frame_selection)
+    molly_reader.home()
+    frames = molly_reader.read_frames(frame_selection=frame_selection)
+
+    assert count == len(frames)
+    if frame_selection is not None:
+        assert count == len(full_mda_frames[frame_selection])
+
+
+def test_frame_mask(path, full_mda_frames):
+    """A boolean mask selects the frames where it is true, as a list or as a NumPy array."""
+
+    _, molly_reader = setup_readers(path)
+    nframes = len(full_mda_frames)
+    expected = molly_reader.read_frames(frame_selection=slice(None, None, 3))
+    mask = [i % 3 == 0 for i in range(nframes)]
+    for frame_selection in [mask, np.array(mask)]:
+        molly_reader.home()
+        frames = molly_reader.read_frames(frame_selection=frame_selection)
+        assert len(frames) == len(expected)
+        for frame, expected_frame in zip(frames, expected):
+            assert frame.step == expected_frame.step
+
+    # A list of bools is a mask, not a list of the indices zero and one.
+    molly_reader.home()
+    frames = molly_reader.read_frames(frame_selection=[False, True] + [False] * (nframes - 2))
+    molly_reader.home()
+    second = molly_reader.read_frames(frame_selection=[1])
+    assert len(frames) == 1
+    assert frames[0].step == second[0].step
+
+    # An empty list selects no frames, rather than being a mask of the wrong length.
+    molly_reader.home()
+    assert len(molly_reader.read_frames(frame_selection=[])) == 0
+
+    # Like NumPy boolean indexing, a mask must have a value for each frame.
+    for length in [nframes - 1, nframes + 1]:
+        molly_reader.home()
+        try:
+            molly_reader.read_frames(frame_selection=np.ones(length, dtype=bool))
+        except IndexError:
+            continue
+        raise AssertionError(f"a mask of {length} values should raise an IndexError")
+
+
+def test_negative_indices(path, full_mda_frames):
+    """Negative indices and slice bounds count from the end, like they do in Python."""
+
+    _, molly_reader = setup_readers(path)
+    nframes = len(full_mda_frames)
+    steps = molly_reader.read_steps().tolist()
+
+    for frame_selection in [
+        slice(-10, None),
+        slice(None, -10),
+        slice(-10, -2, 3),
+        slice(-nframes - 10, 5),
+        slice(-2, -5),
+    ]:
+        molly_reader.home()
+        selected = molly_reader.read_steps(frame_selection=frame_selection).tolist()
+        assert selected == steps[frame_selection], f"{frame_selection = }"
+        molly_reader.home()
+        frames = molly_reader.read_frames(frame_selection=frame_selection)
+        assert [frame.step for frame in frames] == steps[frame_selection]
+        molly_reader.home()
+        assert molly_reader.count_frames(frame_selection=frame_selection) == len(selected)
+
+    molly_reader.home()
+    frames = molly_reader.read_frames(frame_selection=[-1, 0])
+    assert [frame.step for frame in frames] == [steps[0], steps[-1]]
+
+    molly_reader.home()
+    try:
+        molly_reader.read_frames(frame_selection=[-nframes - 1])
+    except IndexError:
+        pass
+    else:
+        raise AssertionError("an index before the first frame should raise an IndexError")
+
+    # Like a Python sequence, an index beyond the last frame raises an IndexError, too.
+    molly_reader.home()
+    try:
+        molly_reader.read_frames(frame_selection=[0, nframes])
+    except IndexError:
+        pass
+    else:
+        raise AssertionError("an index beyond the last frame should raise an IndexError")
+
+    # A single frame can be popped by its index.
+    molly_reader.home()
+    assert molly_reader.pop_frame(-1).step == steps[-1]
+    molly_reader.home()
+    assert molly_reader.pop_frame(3).step == steps[3]
+    assert molly_reader.pop_frame().step == steps[4]
+    molly_reader.home()
+    try:
+        molly_reader.pop_frame(nframes)
+    except IndexError:
+        pass
+    else:
+        raise AssertionError("popping a frame beyond the last frame should raise an IndexError")
+
+    try:
+        molly_reader.read_frames(frame_selection=slice(None, None, -1))
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("a negative step should raise a ValueError")
+
+
+def test_box_lengths_angles(path):
+    """The box lengths and angles agree with the dimensions reported by MDAnalysis."""
+
+    mda_reader, molly_reader = setup_readers(path)
+    for i in range(min(mda_reader.n_frames, 10)):
+        dimensions = mda_reader.trajectory[i].dimensions
+        lengths, angles = molly_reader.pop_frame().box_lengths_angles
+        assert np.allclose(lengths, dimensions[:3], rtol=1e-5), f"{lengths = }, {dimensions = }"
+        assert np.allclose(angles, dimensions[3:], atol=1e-3), f"{angles = }, {dimensions = }"
+
+
+def test_read_into_tensor(path, full_mda_frames):
+    """Reading into the NumPy view of a tensor fills the tensor itself."""
+
+    try:
+        import torch
+    except ImportError:
+        print("\tSKIPPED: torch is not available")
+        return
+
+    _, molly_reader = setup_readers(path)
+    nframes = len(full_mda_frames)
+    natoms = len(full_mda_frames[0])
+    coordinates = torch.zeros((nframes, natoms, 3), dtype=torch.float32)
+    boxvecs = torch.zeros((nframes, 3, 3), dtype=torch.float32)
+    molly_reader.read_into_array(coordinates.numpy(), boxvecs.numpy())
+
+    for i, (mda_positions, tensor_positions) in enumerate(
+        zip(full_mda_frames, coordinates)
+    ):
+        print("\t\t", i, end="\r")
+
+        assert (
+            mda_positions.tolist() == tensor_positions.tolist()
+        ), f"{mda_positions = }\n{tensor_positions = }"
+
+
+def test_read_into_memmap(path, full_mda_frames):
+    """Reading into a memory-mapped array writes the positions to its file."""
+
+    import tempfile
+
+    _, molly_reader = setup_readers(path)
+    nframes = molly_reader.count_frames()
+    natoms = len(full_mda_frames[0])
+    with tempfile.TemporaryDirectory() as tmp:
+        filename = f"{tmp}/coordinates.f32"
+        coordinates = np.memmap(
+            filename, dtype=np.float32, mode="w+", shape=(nframes, natoms, 3)
+        )
+        boxvecs = np.zeros((nframes, 3, 3), dtype=np.float32)
+        molly_reader.read_into_array(coordinates, boxvecs)
+        del coordinates
+
+        written = np.fromfile(filename, dtype=np.float32).reshape((nframes, natoms, 3))
+        for i, (mda_positions, written_positions) in enumerate(
+            zip(full_mda_frames, written)
+        ):
+            print("\t\t", i, end="\r")
+
+            assert (
+                mda_positions.tolist() == written_positions.tolist()
+            ), f"{mda_positions = }\n{written_positions = }"
+
+
+def test_read_into_array_box_shape(path, full_mda_frames):
+    """A box array with a shape other than (nframes, 3, 3) must be rejected."""
+
+    _, molly_reader = setup_readers(path)
+    nframes = len(full_mda_frames)
+    natoms = len(full_mda_frames[0])
+    molly_frames = np.zeros((nframes, natoms, 3), dtype=np.float32)
+    for shape in [(nframes, 3, 4), (nframes, 4, 3), (nframes, 2, 3), (nframes, 9)]:
+        molly_boxvecs = np.zeros(shape, dtype=np.float32)
+        try:
+            molly_reader.read_into_array(molly_frames, molly_boxvecs)
+        except ValueError:
+            continue
+        raise AssertionError(f"a box array of shape {shape} should raise a ValueError")
+
+
+def test_error_types(path):
+    """Invalid and truncated files raise their own subclasses of OSError."""
+
+    import tempfile
+
+    with open(path, "rb") as file:
+        data = file.read()
+
+    with tempfile.TemporaryDirectory() as tmp:
+        invalid = f"{tmp}/invalid.xtc"
+        with open(invalid, "wb") as file:
+            file.write(b"This is not an xtc file, but it is long enough to hold a header.")
+        try:
+            molly.XTCReader(invalid).pop_frame()
+        except molly.InvalidFileError:
+            pass
+        else:
+            raise AssertionError("a file that is not xtc should raise an InvalidFileError")
+
+        # Cut off within the header and within the positions of the first frame.
+        for end in [20, 200]:
+            truncated = f"{tmp}/truncated_{end}.xtc"
+            with open(truncated, "wb") as file:
+                file.write(data[:end])
+            try:
+                molly.XTCReader(truncated).pop_frame()
+            except molly.TruncatedFileError:
+                pass
+            else:
+                raise AssertionError("a truncated file should raise a TruncatedFileError")
+
+    # Both are subclasses of OSError.
+    assert issubclass(molly.InvalidFileError, OSError)
+    assert issubclass(molly.TruncatedFileError, OSError)
+
+
 def read_test(path, full_mda_frames, frame_selection=None, atom_selection=None):
     print(f"TEST: {frame_selection = }, {atom_selection = }, {path = }")
     print(" -\tread_frames")
@@ -107,6 +376,9 @@ def read_test(path, full_mda_frames, frame_selection=None, atom_selection=None):
     print(" -\tread_into_array")
     dur = test_read_into_array(path, full_mda_frames, frame_selection, atom_selection)
     print(f"\tOK!\t\tReading took {dur:8.3} s.")
+    print(" -\tread_stack")
+    dur = test_read_stack(path, full_mda_frames, frame_selection, atom_selection)
+    print(f"\tOK!\t\tReading took {dur:8.3} s.")
 
 
 path = "../../tests/trajectories/trajectory_smol.xtc"
@@ -122,3 +394,51 @@ read_test(path, full_mda_frames, slice(25, 50, 2))
 read_test(path, full_mda_frames, slice(None, None, 3))
 read_test(path, full_mda_frames, slice(None, 20, 3))
 read_test(path, full_mda_frames, slice(25, 50, 3))
+
+# Array shapes.
+print("TEST: read_into_array with a wrongly shaped box array")
+test_read_into_array_box_shape(path, full_mda_frames)
+print("\tOK!")
+
+# Frame masks.
+print("TEST: read_frames with a boolean frame mask")
+test_frame_mask(path, full_mda_frames)
+print("\tOK!")
+
+# Negative indices.
+print("TEST: negative frame indices")
+test_negative_indices(path, full_mda_frames)
+print("\tOK!")
+
+# Errors.
+print("TEST: InvalidFileError and TruncatedFileError")
+test_error_types(path)
+print("\tOK!")
+
+# Box lengths and angles.
+print("TEST: box_lengths_angles")
+test_box_lengths_angles(path)
+print("\tOK!")
+
+# Memory-mapped arrays.
+print("TEST: read_into_array into a np.memmap")
+test_read_into_memmap(path, full_mda_frames)
+print("\tOK!")
+
+# Tensors.
+print("TEST: read_into_array into a torch tensor")
+test_read_into_tensor(path, full_mda_frames)
+print("\tOK!")
+
+# Header-only reads.
+print("TEST: read_times and read_steps")
+test_read_times_and_steps(path)
+test_read_times_and_steps(path, slice(25, 50, 3))
+print("\tOK!")
+
+# Frame counts.
+print("TEST: count_frames")
+test_count_frames(path, full_mda_frames)
+test_count_frames(path, full_mda_frames, slice(25, 50, 3))
+test_count_frames(path, full_mda_frames, slice(None, 10000))
+print("\tOK!")
@@ -16,7 +16,7 @@ fn main() -> std::io::Result<()> {
     });
 
     for frame in rx {
-        eprintln!("got another frame! -> {:?}", frame.boxvec);
+        eprintln!("got another frame! -> {frame}");
     }
 
     Ok(())
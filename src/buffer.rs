@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom};
 
-use crate::reader::read_nbytes;
+use crate::reader::{read_nbytes, read_opaque};
 use crate::{padding, Magic};
 
 pub trait Buffered<'s, 'r, R>: Sized {
@@ -30,6 +30,9 @@ pub trait Buffered<'s, 'r, R>: Sized {
     /// Returns the byte position of the reader.
     fn tell(&self) -> usize;
 
+    /// Returns how many bytes of the compressed data have been read and used so far.
+    fn stats(&self) -> BufferStats;
+
     /// Finish will eat your reader, leaving it at the start of the next frame, and then drops it.
     ///
     /// For an implementation that relies on [`std::io::Seek`] ([`Buffer`] in our case), this
@@ -37,33 +40,79 @@ pub trait Buffered<'s, 'r, R>: Sized {
     fn finish(self) -> io::Result<()>;
 }
 
+/// How much of the compressed data of a frame was read from the reader, and how much of that was
+/// needed to decode the selected positions.
+///
+/// These statistics help to choose between buffered and unbuffered reading. The unbuffered
+/// reader always reads all compressed bytes of a frame. The buffered reader reads them in blocks,
+/// as far as decoding requires. When a selection only needs the start of each frame, `read` is
+/// much less than `size`, and buffering pays off. When `read` comes close to `size` anyway,
+/// reading without a buffer is likely faster.
+///
+/// Statistics of multiple frames can be summed with `+=`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BufferStats {
+    /// The size of the compressed data in bytes, including padding.
+    pub size: usize,
+    /// The number of bytes that were read from the reader.
+    pub read: usize,
+    /// The number of bytes that were needed for decoding.
+    pub used: usize,
+}
+
+impl BufferStats {
+    /// Returns the fraction of the compressed data that was read from the reader.
+    ///
+    /// For empty compressed data, this is one.
+    pub fn read_fraction(&self) -> f64 {
+        if self.size == 0 {
+            return 1.0;
+        }
+        self.read as f64 / self.size as f64
+    }
+}
+
+impl std::ops::AddAssign for BufferStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.size += rhs.size;
+        self.read += rhs.read;
+        self.used += rhs.used;
+    }
+}
+
 /// A specialized buffered reader for the compressed datastream.
-pub struct Buffer<'s, 'r> {
+pub struct Buffer<'s, 'r, R = File> {
     /// Internal scratch buffer to read into.
     ///
     /// # Warning
     ///
     /// Accessing bytes from this buffer directly is valid iff the index of that byte < `self.idx`.
-    scratch: &'s mut [u8],
+    ///
+    /// The scratch buffer grows as bytes are read into it, up to `size`.
+    scratch: &'s mut Vec<u8>,
+    /// The number of compressed bytes, including the padding.
+    size: usize,
     /// Points to the next unfilled byte in `scratch`.
     ///
     /// The starting point for reading bytes from `reader` into `scratch`.
     front: usize,
     /// Points to the last-most byte that has been read.
     head: usize,
-    reader: &'r mut File,
+    reader: &'r mut R,
+    /// Set if the reader ended before `size` bytes could be read.
+    truncated: bool,
     // TODO(buffered): Add some notion of a 'rich' heuristic. For instance, if we know there are
     // 1000 atoms, and we only want to read up until the 500th atom, we can pretty safely assume
     // that we can just read (500/1000) * 1.1 * nbytes = 0.55 * nbytes and be fine.
 }
 
-impl Buffer<'_, '_> {
+impl<R: Read> Buffer<'_, '_, R> {
     const BLOCK_SIZE: usize = 0x20000;
     const MIN_BUFFERED_SIZE: usize = 2 * Self::BLOCK_SIZE;
 
     /// Returns the size of this [`Buffer`].
     const fn size(&self) -> usize {
-        self.scratch.len()
+        self.size
     }
 
     /// Returns the number of bytes that are yet to be read by this [`Buffer`].
@@ -77,39 +126,52 @@ impl Buffer<'_, '_> {
     ///
     /// - `index` < `self.front`, because of the inverse condition in the while loop.
     /// - Values before `self.front` are loaded with valid values from the reader.
+    ///
+    /// If the reader ends early, the missing bytes are set to zero and the [`Buffer`] is marked as
+    /// truncated, such that [`Buffered::finish`] reports the error once decoding is done.
     #[cold]
     fn read_to_include(&mut self, index: usize) -> io::Result<()> {
         while index >= self.front {
-            // TODO(buffered): Consider dealing with n_bytes == 0 indicating eof.
             // Read a bunch of bytes limited by the size of the scratch buffer and BLOCK_SIZE.
             // We would rather do a couple more smaller reads (BLOCK_SIZE) than one big one that
             // goes way beyond what we need according to some AtomSelection.
             let until = usize::min(self.size(), index + Self::BLOCK_SIZE);
-            self.front += self.reader.read(&mut self.scratch[self.front..until])?;
+            // Only grow the scratch buffer as far as we read, such that a corrupt count cannot
+            // lead to an enormous allocation.
+            if self.scratch.len() < until {
+                self.scratch.resize(until, 0xff); // FIXME: Is MaybeUninit a good idea here?
+            }
+            let n = self.reader.read(&mut self.scratch[self.front..until])?;
+            if n == 0 {
+                self.truncated = true;
+                self.scratch[self.front..until].fill(0);
+                self.front = until;
+            }
+            self.front += n;
         }
         assert!(index < self.front); // Already proven by the while loop, but let's double-check :)
         Ok(())
     }
 }
 
-impl<'s, 'r> Buffered<'s, 'r, File> for Buffer<'s, 'r> {
-    fn new(scratch: &'s mut Vec<u8>, reader: &'r mut File, magic: Magic) -> io::Result<Self> {
+impl<'s, 'r, R: Read + Seek> Buffered<'s, 'r, R> for Buffer<'s, 'r, R> {
+    fn new(scratch: &'s mut Vec<u8>, reader: &'r mut R, magic: Magic) -> io::Result<Self> {
         let count = read_nbytes(reader, magic)?;
 
-        // Fill the scratch buffer with a cautionary value.
-        scratch.resize(count + padding(count), 0xff); // FIXME: Is MaybeUninit a good idea here?
-
         let mut buffer = Self {
             scratch,
+            size: count.saturating_add(padding(count)),
             front: 0,
             head: 0,
             reader,
+            truncated: false,
         };
+        buffer.scratch.clear();
 
         // In case the buffer size is rather low, it is probably most efficient to just read it all
         // at once, right here.
-        if buffer.scratch.len() <= Self::MIN_BUFFERED_SIZE {
-            buffer.read_to_include(count.saturating_sub(1))?;
+        if (1..=Self::MIN_BUFFERED_SIZE).contains(&buffer.size()) {
+            buffer.read_to_include(buffer.size() - 1)?;
             assert_eq!(buffer.size(), buffer.front)
         }
 
@@ -139,7 +201,24 @@ impl<'s, 'r> Buffered<'s, 'r, File> for Buffer<'s, 'r> {
         self.head
     }
 
+    fn stats(&self) -> BufferStats {
+        BufferStats {
+            size: self.size(),
+            read: self.front,
+            used: self.head,
+        }
+    }
+
     fn finish(self) -> io::Result<()> {
+        if self.truncated {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "the compressed positions span {} bytes, but the reader ended before that",
+                    self.size()
+                ),
+            ));
+        }
         self.reader.seek(SeekFrom::Current(self.left() as i64))?;
         Ok(())
     }
@@ -153,9 +232,7 @@ pub struct UnBuffered<'s> {
 /// A fallback non-buffered implementation in case [`std::io::Seek`] is not available for `R`.
 impl<'s, 'r, R: Read> Buffered<'s, 'r, R> for UnBuffered<'s> {
     fn new(scratch: &'s mut Vec<u8>, reader: &'r mut R, magic: Magic) -> io::Result<Self> {
-        let count = read_nbytes(reader, magic)?;
-        scratch.resize(count + padding(count), 0);
-        reader.read_exact(scratch)?;
+        read_opaque(reader, magic, scratch)?;
         Ok(Self { head: 0, scratch })
     }
 
@@ -176,6 +253,15 @@ impl<'s, 'r, R: Read> Buffered<'s, 'r, R> for UnBuffered<'s> {
         self.head
     }
 
+    fn stats(&self) -> BufferStats {
+        // All bytes are read up front.
+        BufferStats {
+            size: self.scratch.len(),
+            read: self.scratch.len(),
+            used: self.head,
+        }
+    }
+
     fn finish(self) -> io::Result<()> {
         Ok(()) // Nothing to do, since we already read everything.
     }
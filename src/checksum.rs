@@ -0,0 +1,281 @@
+//! Per-frame checksums, stored out-of-band in a sidecar file.
+//!
+//! The xtc format has no checksums of its own, so silent corruption of an archived trajectory
+//! can go unnoticed as long as the damaged frames still decode. To detect this, a CRC-32 of the
+//! bytes of each frame can be recorded in a sidecar file next to the trajectory, with the path of
+//! the trajectory followed by `.crc` (see [`sidecar_path`]). The trajectory itself is left
+//! untouched, so readers that are not concerned with the checksums are not affected by them.
+//!
+//! The sidecar is a plain text file with one line per frame, holding its byte offset, its size in
+//! bytes, and its checksum as eight hexadecimal digits, separated by tabs.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::XTCReader;
+
+/// The reversed IEEE 802.3 polynomial, as used by zlib, gzip, and png.
+const POLYNOMIAL: u32 = 0xedb88320;
+
+/// Lookup table for the CRC-32 of each byte value.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut crc = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            crc = if crc & 1 == 1 {
+                POLYNOMIAL ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            k += 1;
+        }
+        table[n] = crc;
+        n += 1;
+    }
+    table
+};
+
+/// An incremental CRC-32 (IEEE) hasher.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self(!0)
+    }
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed `bytes` into the checksum.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = TABLE[((self.0 ^ byte as u32) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    /// Returns the checksum of all bytes fed so far.
+    pub fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Returns the CRC-32 (IEEE) of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
+
+/// The location and checksum of a single frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameChecksum {
+    /// The byte offset of the start of the frame.
+    pub offset: u64,
+    /// The size of the frame in bytes.
+    pub size: u64,
+    /// The CRC-32 of the bytes of the frame.
+    pub crc: u32,
+}
+
+/// Returns the path of the checksum sidecar for the trajectory at `path`.
+///
+/// This is the path of the trajectory with `.crc` appended, such that `traj.xtc` gets the sidecar
+/// `traj.xtc.crc`.
+pub fn sidecar_path(path: impl AsRef<Path>) -> PathBuf {
+    let mut sidecar = path.as_ref().as_os_str().to_owned();
+    sidecar.push(".crc");
+    PathBuf::from(sidecar)
+}
+
+/// The number of bytes of a frame that are fed into its checksum at once.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Determines the checksum of each frame in `reader`, from the start.
+///
+/// The bytes of each frame are read in chunks, so the memory used does not depend on the size of
+/// the frames. The reader is left at its start afterwards.
+///
+/// # Errors
+///
+/// This function will pass through any reader errors, including those of a structurally damaged
+/// trajectory, as reported by [`XTCReader::scan`]. If a frame extends beyond the end of the
+/// reader, an error of the kind [`io::ErrorKind::UnexpectedEof`] is returned.
+pub fn frame_checksums<R: Read + Seek>(
+    reader: &mut XTCReader<R>,
+) -> io::Result<Vec<FrameChecksum>> {
+    reader.home()?;
+    let metas = reader.scan(None)?;
+    let mut chunk = vec![0; CHUNK_SIZE];
+    let mut checksums = Vec::with_capacity(metas.len());
+    for meta in metas {
+        reader.file.seek(SeekFrom::Start(meta.offset))?;
+        let mut frame = (&mut reader.file).take(meta.size);
+        let mut crc = Crc32::new();
+        let mut read = 0;
+        loop {
+            let n = match frame.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            crc.update(&chunk[..n]);
+            read += n as u64;
+        }
+        if read < meta.size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "the frame at byte {} spans {} bytes, but only {read} bytes could be read",
+                    meta.offset, meta.size
+                ),
+            ));
+        }
+        checksums.push(FrameChecksum {
+            offset: meta.offset,
+            size: meta.size,
+            crc: crc.finish(),
+        });
+    }
+    reader.home()?;
+    Ok(checksums)
+}
+
+/// Writes `checksums` to a sidecar file at `path`.
+///
+/// # Errors
+///
+/// This function will pass through any writer errors.
+pub fn write_sidecar(path: impl AsRef<Path>, checksums: &[FrameChecksum]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for checksum in checksums {
+        let FrameChecksum { offset, size, crc } = checksum;
+        writeln!(writer, "{offset}\t{size}\t{crc:08x}")?;
+    }
+    writer.flush()
+}
+
+/// Reads the checksums from a sidecar file at `path`.
+///
+/// # Errors
+///
+/// If a line in the sidecar is malformed, an error of kind [`io::ErrorKind::InvalidData`] is
+/// returned. Any reader errors are passed through.
+pub fn read_sidecar(path: impl AsRef<Path>) -> io::Result<Vec<FrameChecksum>> {
+    let invalid = |n: usize| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line {} of the checksum sidecar is malformed", n + 1),
+        )
+    };
+
+    let mut checksums = Vec::new();
+    for (n, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        let mut fields = line.split('\t');
+        let (Some(offset), Some(size), Some(crc), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid(n));
+        };
+        checksums.push(FrameChecksum {
+            offset: offset.parse().map_err(|_| invalid(n))?,
+            size: size.parse().map_err(|_| invalid(n))?,
+            crc: u32::from_str_radix(crc, 16).map_err(|_| invalid(n))?,
+        });
+    }
+    Ok(checksums)
+}
+
+/// Determines the frame checksums of the trajectory at `path` and writes them to its sidecar.
+///
+/// See [`sidecar_path`].
+///
+/// # Errors
+///
+/// This function will pass through any reader and writer errors.
+pub fn stamp(path: impl AsRef<Path>) -> io::Result<()> {
+    let checksums = frame_checksums(&mut XTCReader::open(&path)?)?;
+    write_sidecar(sidecar_path(&path), &checksums)
+}
+
+/// Checks the frames of the trajectory at `path` against the checksums in its sidecar.
+///
+/// Returns the indices of the frames whose bytes no longer match their recorded checksum. An
+/// empty list means the trajectory is intact.
+///
+/// # Errors
+///
+/// If the frames in the trajectory no longer line up with those recorded in the sidecar, an error
+/// of kind [`io::ErrorKind::InvalidData`] is returned. Any reader errors are passed through,
+/// including those of a missing sidecar.
+pub fn verify(path: impl AsRef<Path>) -> io::Result<Vec<usize>> {
+    let recorded = read_sidecar(sidecar_path(&path))?;
+    let found = frame_checksums(&mut XTCReader::open(&path)?)?;
+    if recorded.len() != found.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the trajectory contains {} frames, but the checksum sidecar records {}",
+                found.len(),
+                recorded.len()
+            ),
+        ));
+    }
+
+    let mut mismatches = Vec::new();
+    for (idx, (recorded, found)) in recorded.iter().zip(&found).enumerate() {
+        if (recorded.offset, recorded.size) != (found.offset, found.size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "frame {idx} spans {} bytes from offset {}, but the checksum sidecar records \
+                    {} bytes from offset {}",
+                    found.size, found.offset, recorded.size, recorded.offset
+                ),
+            ));
+        }
+        if recorded.crc != found.crc {
+            mismatches.push(idx);
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check values of the standard CRC-32 (IEEE).
+    #[test]
+    fn crc32_check() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414fa339
+        );
+    }
+
+    #[test]
+    fn crc32_incremental() {
+        let bytes = b"The quick brown fox jumps over the lazy dog";
+        let mut crc = Crc32::new();
+        for chunk in bytes.chunks(5) {
+            crc.update(chunk);
+        }
+        assert_eq!(crc.finish(), crc32(bytes));
+    }
+
+    #[test]
+    fn sidecar_path_appends() {
+        assert_eq!(sidecar_path("a/traj.xtc"), PathBuf::from("a/traj.xtc.crc"));
+    }
+}
@@ -1,28 +1,58 @@
+use std::cell::Cell;
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom};
-use std::{cell::Cell, path::Path};
+use std::path::{Path, PathBuf};
 
 use glam::{Mat3, Vec3};
 use reader::read_nbytes;
 
-use crate::buffer::{Buffer, UnBuffered};
+use crate::buffer::{Buffer, BufferStats, UnBuffered};
 use crate::reader::{
-    read_boxvec, read_compressed_positions, read_f32, read_f32s, read_i32, read_u32,
+    decode_compressed_positions, read_boxvec, read_compressed_positions,
+    read_compressed_positions_scaled, read_f32, read_f32s, read_i32, read_quantized_positions,
+    read_u32, validate_precision, Prelude,
 };
-use crate::selection::{AtomSelection, FrameSelection};
+use crate::selection::{AtomSelection, FrameSelection, SelectionStrategy};
 
 pub mod buffer;
+pub mod checksum;
 pub mod reader;
 pub mod selection;
+pub mod writer;
 
 // See https://gitlab.com/gromacs/gromacs/-/blob/v2024.1/src/gromacs/fileio/xdrf.h?ref_type=tags#L78
 pub const XTC_1995_MAX_NATOMS: usize = 298261617;
 
+/// The largest number of atoms in a frame for which the positions are stored uncompressed.
+///
+/// The compressed positions are preceded by a precision, the extent of the integer positions,
+/// and a byte count, which together take more space than the plain floats of a few atoms. So, the
+/// xtc format stores the positions of frames with this many atoms or less as plain floats,
+/// without a precision. See [`Header::is_compressed`].
+// See `xdr3dfcoord` in https://gitlab.com/gromacs/gromacs/-/blob/v2024.1/src/gromacs/fileio/libxdrf.cpp.
+pub const SMALL_NATOMS_THRESHOLD: usize = 9;
+
 thread_local! {
     /// A scratch buffer to read encoded bytes into for subsequent decoding.
     static SCRATCH: Cell<Vec<u8>> = const { Cell::new(Vec::new()) };
 }
 
+/// Run `f` with the thread-local [`SCRATCH`] buffer, and put it back afterwards.
+///
+/// The buffer keeps its allocation between frames, so only the first frame in a thread pays for
+/// growing it.
+fn with_scratch<T>(f: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+    let mut scratch = SCRATCH.take();
+    let result = f(&mut scratch);
+    SCRATCH.set(scratch);
+    result
+}
+
+/// The box of a frame.
+///
+/// The box vectors `a`, `b`, and `c` are stored as the _columns_ of this matrix, such that
+/// `boxvec.x_axis` is `a`. Note that Gromacs writes the box vectors as the _rows_ of a 3×3
+/// matrix. See [`Frame::box_rows`] and [`Frame::box_cols`] for unambiguous accessors.
 pub type BoxVec = Mat3;
 
 #[repr(i32)]
@@ -39,6 +69,17 @@ impl Magic {
     fn to_be_bytes(&self) -> [u8; 4] {
         (*self as i32).to_be_bytes()
     }
+
+    /// The width in bytes of the byte count that precedes the compressed positions.
+    ///
+    /// The original format stores it as a 32-bit integer, and the 2023 format as a 64-bit
+    /// integer. See [`reader::read_nbytes`].
+    pub const fn nbytes_width(self) -> usize {
+        match self {
+            Magic::Xtc1995 => 4,
+            Magic::Xtc2023 => 8,
+        }
+    }
 }
 
 impl TryFrom<i32> for Magic {
@@ -63,28 +104,288 @@ impl std::fmt::Display for Magic {
     }
 }
 
+/// How [`XTCReader::seek_to_time`] picks a frame when no frame has exactly the requested time.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// The frame closest in time. When the time lies exactly halfway between two frames, the
+    /// earlier frame is picked.
+    #[default]
+    Nearest,
+    /// The last frame at or before the requested time.
+    Floor,
+    /// The first frame at or after the requested time.
+    Ceil,
+}
+
+/// The precision with which the positions of a [`Frame`] are stored.
+///
+/// When a frame holds 9 atoms or less, the xtc format stores their positions as plain floats,
+/// and such a frame has no meaningful precision. See [`Header::is_compressed`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Precision {
+    /// The positions are compressed, after being quantized to a grid with a spacing of
+    /// `1 / precision` nm.
+    Compressed(f32),
+    /// The positions are stored as uncompressed floats.
+    #[default]
+    Uncompressed,
+}
+
+impl Precision {
+    /// Returns the precision value if the positions are compressed, and [`None`] otherwise.
+    pub fn value(self) -> Option<f32> {
+        match self {
+            Self::Compressed(precision) => Some(precision),
+            Self::Uncompressed => None,
+        }
+    }
+}
+
+/// A mapping of the step and time of each frame, applied as the frames are read.
+///
+/// This is useful to present concatenated trajectories with overlapping steps as a single
+/// monotonic timeline. See [`XTCReader::set_step_remap`].
+#[derive(Clone)]
+pub struct StepRemap(std::sync::Arc<dyn Fn(usize, u64, f32) -> (u64, f32) + Send + Sync>);
+
+impl StepRemap {
+    /// Create a [`StepRemap`] from a function that takes the index of a frame in the trajectory,
+    /// its step, and its time, and returns the new step and time.
+    pub fn new(f: impl Fn(usize, u64, f32) -> (u64, f32) + Send + Sync + 'static) -> Self {
+        Self(std::sync::Arc::new(f))
+    }
+
+    /// Create a [`StepRemap`] that adds a constant offset to the step and time of each frame.
+    pub fn offset(step_offset: u64, time_offset: f32) -> Self {
+        Self::new(move |_, step, time| (step.saturating_add(step_offset), time + time_offset))
+    }
+
+    /// Apply the mapping to the step and time of a header of the frame at index `idx`.
+    fn apply(&self, idx: usize, header: &mut Header) {
+        (header.step, header.time) = (self.0)(idx, header.step, header.time);
+    }
+}
+
+impl std::fmt::Debug for StepRemap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StepRemap(..)")
+    }
+}
+
+/// The error for a frame whose time lies before that of the frame preceding it, as found by an
+/// [`XTCReader`] with strict time checking. See [`XTCReader::set_strict_time`].
+///
+/// It is carried by an [`io::Error`] of the kind [`io::ErrorKind::InvalidData`], and can be
+/// recovered from it with [`io::Error::get_ref`] and a downcast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonMonotonicTime {
+    /// The index of the offending frame.
+    pub frame: usize,
+    /// The time of the preceding frame in picoseconds.
+    pub prev: f32,
+    /// The time of the offending frame in picoseconds.
+    pub cur: f32,
+}
+
+impl std::fmt::Display for NonMonotonicTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { frame, prev, cur } = self;
+        write!(
+            f,
+            "time goes backward at frame {frame}: it has time {cur} ps, while the frame before it \
+            has time {prev} ps"
+        )
+    }
+}
+
+impl std::error::Error for NonMonotonicTime {}
+
+impl From<NonMonotonicTime> for io::Error {
+    fn from(err: NonMonotonicTime) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// The location and header values of a single xtc frame, as found by [`XTCReader::scan`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMeta {
+    /// Byte offset of the start of the frame.
+    pub offset: u64,
+    /// Size of the frame in bytes, including its header.
+    pub size: u64,
+    pub step: u64,
+    /// Time in picoseconds.
+    pub time: f32,
+    pub natoms: usize,
+    pub boxvec: BoxVec,
+}
+
+/// The metadata of a single xtc frame together with the values that precede its compressed
+/// positions, as read by [`XTCReader::read_frame_meta`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSummary {
+    pub meta: FrameMeta,
+    pub precision: Precision,
+    /// The smallest and largest integer positions in the frame, or [`None`] if the positions are
+    /// uncompressed.
+    ///
+    /// Divided by the precision, these bound the positions of all atoms in nm.
+    pub extent: Option<([i32; 3], [i32; 3])>,
+}
+
+/// The time spent reading and decoding a single frame, as measured by
+/// [`XTCReader::read_frame_with_timing`].
+///
+/// When `read` dominates, reading is I/O-bound, and buffered reading of a selection of atoms will
+/// likely pay off. When `decode` dominates, the cost lies in the decompression itself.
+///
+/// Timings of multiple frames can be summed with `+=`.
+#[cfg(feature = "profile")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTiming {
+    /// The time spent reading the header and the compressed bytes of the frame.
+    pub read: std::time::Duration,
+    /// The time spent decoding the positions from the compressed bytes.
+    pub decode: std::time::Duration,
+}
+
+#[cfg(feature = "profile")]
+impl std::ops::AddAssign for FrameTiming {
+    fn add_assign(&mut self, rhs: Self) {
+        self.read += rhs.read;
+        self.decode += rhs.decode;
+    }
+}
+
+/// The basic properties of an xtc file, as found by [`probe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeResult {
+    /// The magic number of the first frame, which sets the version of the format.
+    pub version: Magic,
+    pub natoms: usize,
+    pub first_step: u64,
+    /// Time of the first frame in picoseconds.
+    pub first_time: f32,
+}
+
+/// Checks whether the file at `path` looks like a readable xtc file.
+///
+/// Only the header of the first frame is read, which makes this the cheapest way to classify many
+/// files, for instance when scanning a directory.
+///
+/// # Errors
+///
+/// If the file cannot be opened, is too short to hold a header, or its header is invalid (e.g.,
+/// the magic number is wrong), a descriptive error is returned.
+pub fn probe<P: AsRef<Path>>(path: P) -> io::Result<ProbeResult> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+    let header = match Header::read_optional(&mut file) {
+        Ok(Some(header)) => header,
+        Ok(None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{path:?} is empty and holds no xtc frames"),
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{path:?} is too short to hold an xtc header of {} bytes",
+                    Header::SIZE
+                ),
+            ))
+        }
+        Err(err) => {
+            return Err(io::Error::new(
+                err.kind(),
+                format!("{path:?} is not a valid xtc file: {err}"),
+            ))
+        }
+    };
+
+    Ok(ProbeResult {
+        version: header.magic,
+        natoms: header.natoms,
+        first_step: header.step,
+        first_time: header.time,
+    })
+}
+
+/// The error for a [`Header`] in which the number of atoms does not match its repeated value.
+///
+/// It is returned as the inner error of an [`io::Error`] of the kind
+/// [`io::ErrorKind::InvalidData`], and can be retrieved through [`io::Error::get_ref`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NAtomsMismatch {
+    pub first: usize,
+    pub repeated: usize,
+}
+
+impl std::fmt::Display for NAtomsMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "could not read header: the number of atoms ({}) does not match its repeated value ({})",
+            self.first, self.repeated
+        )
+    }
+}
+
+impl std::error::Error for NAtomsMismatch {}
+
 /// The header of a single xtc frame.
+///
+/// The header has a fixed layout of [`Header::SIZE`] bytes for both [`Magic`] numbers, and holds
+/// no length of its own. Headers with an unknown magic number are rejected.
+#[derive(Debug)]
 pub struct Header {
     pub magic: Magic,
     pub natoms: usize,
-    pub step: u32,
+    /// The step of the frame.
+    ///
+    /// It is stored as a 32-bit value. See [`Frame::step`].
+    pub step: u64,
     pub time: f32,
     pub boxvec: Mat3,
     pub natoms_repeated: usize,
 }
 
 impl Header {
+    /// The size of a header in bytes: the magic number, the number of atoms, the step, the time,
+    /// the nine values of the box, and the repeated number of atoms.
     pub const SIZE: usize = 4 * (5 + 9);
 
+    /// Read a header.
+    ///
+    /// If the number of atoms does not match its repeated value, an error with a
+    /// [`NAtomsMismatch`] is returned.
     pub fn read(file: &mut impl Read) -> io::Result<Self> {
-        let magic = Magic::try_from(read_i32(file)?)
-            .map_err(|err| io::Error::other(format!("could not read header: {err}")))?;
+        Self::read_with(file, false)
+    }
+
+    /// Read a header, trusting the first number of atoms if it does not match its repeated value.
+    ///
+    /// Such a discrepancy is not an error, but is kept in the header, and can be inspected through
+    /// [`Header::natoms_mismatch`]. This allows salvaging the frames of imperfect files.
+    pub fn read_lenient(file: &mut impl Read) -> io::Result<Self> {
+        Self::read_with(file, true)
+    }
+
+    fn read_with(file: &mut impl Read, lenient: bool) -> io::Result<Self> {
+        let magic = Magic::try_from(read_i32(file)?).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("could not read header: {err}"),
+            )
+        })?;
         let natoms: usize = read_u32(file)?
             .try_into()
             .map_err(|err| io::Error::other(format!("could not read natoms: {err}")))?;
-        let step: u32 = read_i32(file)?
-            .try_into()
-            .map_err(|err| io::Error::other(format!("could not read step: {err}")))?;
+        // Gromacs writes the step as a signed 32-bit integer, such that steps beyond `i32::MAX`
+        // wrap around to negative values. Reading it as unsigned recovers them.
+        let step = read_u32(file)? as u64;
         let time = read_f32(file)?;
 
         // Read the frame data.
@@ -92,7 +393,15 @@ impl Header {
         let natoms_repeated = read_u32(file)?
             .try_into()
             .map_err(|err| io::Error::other(format!("could not read second natoms: {err}")))?;
-        assert_eq!(natoms, natoms_repeated);
+        if natoms != natoms_repeated {
+            let mismatch = NAtomsMismatch {
+                first: natoms,
+                repeated: natoms_repeated,
+            };
+            if !lenient {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, mismatch));
+            }
+        }
 
         Ok(Header {
             magic,
@@ -104,13 +413,79 @@ impl Header {
         })
     }
 
-    pub fn to_be_bytes(&self) -> [u8; Self::SIZE] {
-        let mut bytes = Vec::new();
-        bytes.extend(self.magic.to_be_bytes()); // i32
+    /// Read a header, or return [`None`] if the reader is exactly at its end.
+    ///
+    /// This distinguishes the clean end of a trajectory from a truncated header. If the header is
+    /// only partially present, an error of the kind [`io::ErrorKind::UnexpectedEof`] is returned.
+    pub fn read_optional(file: &mut impl Read) -> io::Result<Option<Self>> {
+        Self::read_optional_with(file, false)
+    }
 
-        let natoms = u32::try_from(self.natoms).unwrap().to_be_bytes();
-        bytes.extend(natoms); // u32
-        bytes.extend(i32::try_from(self.step).unwrap().to_be_bytes()); // i32
+    fn read_optional_with(file: &mut impl Read, lenient: bool) -> io::Result<Option<Self>> {
+        let mut first = [0; 1];
+        loop {
+            match file.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Self::read_with(&mut first.as_slice().chain(file), lenient).map(Some)
+    }
+
+    /// Returns the mismatch between the number of atoms and its repeated value, if they differ.
+    ///
+    /// Only a header that was read leniently can hold such a mismatch.
+    pub fn natoms_mismatch(&self) -> Option<NAtomsMismatch> {
+        (self.natoms != self.natoms_repeated).then_some(NAtomsMismatch {
+            first: self.natoms,
+            repeated: self.natoms_repeated,
+        })
+    }
+
+    /// Returns whether the positions in the frame described by this [`Header`] are compressed.
+    ///
+    /// When a frame holds 9 atoms or less, the xtc format stores their positions as plain
+    /// uncompressed floats. In that case, the frame has no precision, since the positions were
+    /// never quantized to begin with. Such a frame is read with [`Precision::Uncompressed`].
+    pub fn is_compressed(&self) -> bool {
+        self.natoms > SMALL_NATOMS_THRESHOLD
+    }
+
+    /// Returns the big-endian bytes of this [`Header`], as they are stored in an xtc file.
+    ///
+    /// # Errors
+    ///
+    /// If the number of atoms or the step does not fit in the 32-bit value it is stored as, or if
+    /// the number of atoms does not match its repeated value, an error of the kind
+    /// [`io::ErrorKind::InvalidInput`] is returned.
+    pub fn to_be_bytes(&self) -> io::Result<[u8; Self::SIZE]> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        let natoms = u32::try_from(self.natoms).map_err(|_| {
+            invalid(format!(
+                "the number of atoms ({}) does not fit in the 32-bit number of atoms of an xtc \
+                header",
+                self.natoms
+            ))
+        })?;
+        let step = u32::try_from(self.step).map_err(|_| {
+            invalid(format!(
+                "the step of the frame ({}) does not fit in the 32-bit step of an xtc header",
+                self.step
+            ))
+        })?;
+        if self.natoms != self.natoms_repeated {
+            return Err(invalid(format!(
+                "the number of atoms ({}) does not match its repeated value ({})",
+                self.natoms, self.natoms_repeated
+            )));
+        }
+
+        let mut bytes = Vec::with_capacity(Self::SIZE);
+        bytes.extend(self.magic.to_be_bytes()); // i32
+        bytes.extend(natoms.to_be_bytes()); // u32
+        bytes.extend(step.to_be_bytes()); // u32
         bytes.extend(self.time.to_be_bytes()); // f32
         bytes.extend(
             self.boxvec
@@ -119,191 +494,1526 @@ impl Header {
                 .iter()
                 .flatten(),
         ); // 9 × f32
-        assert_eq!(self.natoms, self.natoms_repeated);
-        bytes.extend(natoms); // u32
+        bytes.extend(natoms.to_be_bytes()); // u32
 
-        bytes.try_into().unwrap()
+        Ok(bytes.try_into().unwrap())
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Default, Clone)]
 pub struct Frame {
-    pub step: u32,
+    /// The step of the frame.
+    ///
+    /// The xtc format stores the step as a 32-bit value, which is read without sign, such that
+    /// steps up to `u32::MAX` are represented faithfully. The field is a `u64` so long simulations
+    /// do not wrap around in memory, for instance after a [`StepRemap`]. This used to be a `u32`;
+    /// code that relies on that can convert with `u32::try_from(frame.step)`.
+    pub step: u64,
     /// Time in picoseconds.
     pub time: f32,
     pub boxvec: BoxVec,
-    pub precision: f32,
+    /// The precision of the positions, or [`Precision::Uncompressed`] for a frame of 9 atoms or
+    /// less, which are stored as plain floats.
+    pub precision: Precision,
     pub positions: Vec<f32>,
+    /// The index of this frame in the trajectory, if it was read through a frame selection.
+    ///
+    /// This records where the frame came from, and is not considered when comparing frames.
+    pub index: Option<usize>,
+    /// The byte offset of this frame in its file, if it was read at an offset.
+    ///
+    /// This records where the frame came from, and is not considered when comparing frames.
+    pub offset: Option<u64>,
 }
 
-impl Frame {
-    /// Returns an iterator over the coordinates stored in this [`Frame`].
-    pub fn coords(&self) -> impl Iterator<Item = Vec3> + '_ {
-        self.positions.chunks_exact(3).map(Vec3::from_slice)
+/// Summarizes the frame without listing all of its positions, which would flood any log.
+///
+/// Only the number of positions and the positions of the first few atoms are shown. Since the
+/// positions are a public field, their number need not be a multiple of three, and this does not
+/// panic on such a malformed frame.
+impl std::fmt::Debug for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// The number of leading positions that are shown, those of the first three atoms.
+        const HEAD: usize = 9;
+        let head = &self.positions[..usize::min(HEAD, self.positions.len())];
+        f.debug_struct("Frame")
+            .field("step", &self.step)
+            .field("time", &self.time)
+            .field("natoms", &(self.positions.len() / 3))
+            .field("npositions", &self.positions.len())
+            .field("head", &head)
+            .field("boxvec", &self.boxvec)
+            .field("precision", &self.precision)
+            .field("index", &self.index)
+            .field("offset", &self.offset)
+            .finish_non_exhaustive()
     }
+}
 
-    /// Returns the number of atoms in this [`Frame`].
-    pub fn natoms(&self) -> usize {
-        let npos = self.positions.len();
-        assert_eq!(
-            npos % 3,
-            0,
-            "the number of single positions in a frame must always be a multiple of 3"
-        );
-        npos / 3
+/// A one-line summary of the frame, with the lengths of its box vectors in nanometers.
+///
+/// For example, `step 1000, time 20 ps, 47681 atoms, box 7.845 × 7.845 × 7.845 nm`.
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { step, time, .. } = self;
+        let natoms = self.positions.len() / 3;
+        write!(f, "step {step}, time {time} ps, {natoms} atoms, ")?;
+        if self.has_box() {
+            let BoxVec {
+                x_axis,
+                y_axis,
+                z_axis,
+            } = self.boxvec;
+            let [a, b, c] = [x_axis, y_axis, z_axis].map(Vec3::length);
+            write!(f, "box {a:.3} × {b:.3} × {c:.3} nm")
+        } else {
+            write!(f, "no box")
+        }
     }
 }
 
-/// Calculate the xdr padding for some number of bytes.
-#[doc(hidden)]
-pub fn padding(n: usize) -> usize {
-    (4 - (n % 4)) % 4
+impl PartialEq for Frame {
+    fn eq(&self, other: &Self) -> bool {
+        self.step == other.step
+            && self.time == other.time
+            && self.boxvec == other.boxvec
+            && self.precision == other.precision
+            && self.positions == other.positions
+    }
 }
 
-/// Read the positions in a frame after the header.
-///
-/// If successful, returns the number of compressed bytes that were read.
-///
-/// Internal use.
-#[doc(hidden)]
-pub fn read_positions<'s, 'r, B: buffer::Buffered<'s, 'r, R>, R: Read>(
-    file: &'r mut R,
-    header_natoms: usize,
-    scratch: &'s mut Vec<u8>,
-    frame: &mut Frame,
-    atom_selection: &AtomSelection,
-    magic: Magic,
-) -> io::Result<usize> {
-    // If the atom_selection specifies fewer atoms, we will only allocate up to that point.
-    let natoms_selected = atom_selection.natoms_selected(header_natoms);
-
-    // Resize the positions array for the selected number of atoms.
-    frame.positions.resize(natoms_selected * 3, f32::NAN);
-    frame.precision = read_f32(file)?;
-    read_compressed_positions::<B, R>(
-        file,
-        header_natoms,
-        &mut frame.positions,
-        frame.precision,
-        scratch,
-        atom_selection,
-        magic,
-    )
-}
+impl Frame {
+    /// Decodes a single self-contained frame from `bytes`, starting with its header.
+    ///
+    /// Any bytes after the frame are ignored.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes do not describe a complete frame, an error is returned.
+    pub fn decode_from_bytes(bytes: &[u8]) -> io::Result<Frame> {
+        let mut reader = XTCReader::new(bytes);
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        Ok(frame)
+    }
 
-#[derive(Debug, Clone)]
-pub struct XTCReader<R> {
-    pub file: R,
-    pub step: usize,
-}
+    /// Returns an iterator over the coordinates stored in this [`Frame`].
+    pub fn coords(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.positions.chunks_exact(3).map(Vec3::from_slice)
+    }
 
-impl XTCReader<std::fs::File> {
-    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let file = std::fs::File::open(path)?;
-        Ok(Self::new(file))
+    /// Returns the positions in this [`Frame`] as separate `x`, `y`, and `z` components.
+    ///
+    /// This structure-of-arrays layout suits vectorized operations over a single component, which
+    /// are awkward over the interleaved `positions`. See [`Frame::coords_soa_into`] to reuse
+    /// existing buffers.
+    pub fn coords_soa(&self) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+        let natoms = self.natoms();
+        let (mut x, mut y, mut z) = (vec![0.0; natoms], vec![0.0; natoms], vec![0.0; natoms]);
+        self.coords_soa_into(&mut x, &mut y, &mut z);
+        (x, y, z)
     }
-}
 
-impl<R: Read> XTCReader<R> {
-    pub fn new(reader: R) -> Self {
-        Self {
-            file: reader,
-            step: 0,
+    /// Writes the `x`, `y`, and `z` components of the positions in this [`Frame`] into separate
+    /// slices.
+    ///
+    /// # Panics
+    ///
+    /// If the length of any of the slices is not equal to the number of atoms in this frame, this
+    /// function panics.
+    pub fn coords_soa_into(&self, x: &mut [f32], y: &mut [f32], z: &mut [f32]) {
+        let natoms = self.natoms();
+        assert!(
+            x.len() == natoms && y.len() == natoms && z.len() == natoms,
+            "the component slices ({}, {}, {}) must have a length equal to the number of atoms in \
+            the frame ({natoms})",
+            x.len(),
+            y.len(),
+            z.len()
+        );
+        for (i, pos) in self.positions.chunks_exact(3).enumerate() {
+            x[i] = pos[0];
+            y[i] = pos[1];
+            z[i] = pos[2];
         }
     }
 
-    /// Read the header at the start of a frame.
-    ///
-    /// Assumes the internal reader is at the start of a new frame header.
-    pub fn read_header(&mut self) -> io::Result<Header> {
-        Header::read(&mut self.file)
+    /// Returns the position of the atom at index `i`, or `None` if it is out of range.
+    pub fn atom(&self, i: usize) -> Option<Vec3> {
+        let start = i.checked_mul(3)?;
+        self.positions
+            .get(start..start.checked_add(3)?)
+            .map(Vec3::from_slice)
     }
 
-    /// Read a small number of uncompressed positions.
+    /// Returns the flat positions of the atoms in `range`.
     ///
-    /// If successful, returns the number of compressed bytes that were read.
+    /// The returned slice holds three values per atom, in the same layout as `positions`.
     ///
     /// # Panics
     ///
-    /// `natoms` must be 9 or less, otherwise the positions must be decompressed and cannot be read
-    /// directly through this function.  
+    /// If `range` extends beyond the atoms in this [`Frame`], this function panics, just like
+    /// slice indexing does.
+    pub fn atom_slice(&self, range: std::ops::Range<usize>) -> &[f32] {
+        &self.positions[range.start * 3..range.end * 3]
+    }
+
+    /// Returns an iterator over consecutive groups of `group_size` coordinates in this [`Frame`].
     ///
-    /// Oh xtc, you are so fucking weird.
-    pub fn read_smol_positions(
-        &mut self,
-        natoms: usize,
-        frame: &mut Frame,
-        atom_selection: &AtomSelection,
-    ) -> io::Result<usize> {
+    /// This is useful when the atoms make up molecules of the same size, such as the three atoms
+    /// of each water molecule.
+    ///
+    /// # Panics
+    ///
+    /// If `group_size` is zero or does not divide the number of atoms in this frame, this function
+    /// panics.
+    pub fn coords_grouped(&self, group_size: usize) -> impl Iterator<Item = &[Vec3]> + '_ {
+        let natoms = self.natoms();
         assert!(
-            natoms <= 9,
-            "only read uncomprossed positions when the number of atoms is 9 or less"
+            group_size > 0 && natoms % group_size == 0,
+            "the group size ({group_size}) must divide the number of atoms in the frame ({natoms})"
         );
+        as_vec3s(&self.positions).chunks_exact(group_size)
+    }
 
-        // In case the number of atoms is very small, just read their uncompressed positions.
-        frame.positions.resize(natoms * 3, 0.0);
-        let mut buf = [0.0; 9 * 3]; // We have at most 9 atoms, so we handle them on the stack.
-        let buf = &mut buf[..natoms * 3];
-        read_f32s(&mut self.file, buf)?;
-        frame.positions.clear();
-        frame.positions.extend(
-            buf.chunks_exact(3)
-                .enumerate()
-                .filter_map(|(idx, pos): (usize, &[f32])| -> Option<[f32; 3]> {
-                    if atom_selection.is_included(idx).unwrap_or_default() {
-                        Some(pos.try_into().unwrap())
-                    } else {
-                        None
-                    }
-                })
-                .flatten(),
-        );
-        // TODO: It is unclear to me what to do with the precision in this case. It is
-        // basically invalid, or just irrelevant here, since we don't decode them. They were
-        // never compressed to begin with.
+    /// Returns the box of this [`Frame`] with one box vector per row, following the Gromacs
+    /// convention.
+    ///
+    /// The first row is box vector `a`, the second `b`, and the third `c`. This is the layout in
+    /// which the box is stored in an xtc file, and the layout used by xdrfile and MDAnalysis.
+    pub fn box_rows(&self) -> [[f32; 3]; 3] {
+        self.boxvec.to_cols_array_2d()
+    }
 
-        Ok(buf.len() * std::mem::size_of::<f32>())
+    /// Returns the box of this [`Frame`] with one box vector per column.
+    ///
+    /// This is the transpose of [`Frame::box_rows`]. The `i`th row holds the `i`th component of
+    /// the box vectors `a`, `b`, and `c`.
+    pub fn box_cols(&self) -> [[f32; 3]; 3] {
+        self.boxvec.transpose().to_cols_array_2d()
     }
 
-    /// A convenience function to read all frames in a trajectory.
+    /// Returns the lengths (nm) and angles (degrees) of the box of this [`Frame`].
     ///
-    /// It is likely more efficient to use [`XTCReader::read_frame`] if you are only interested in
-    /// the values of a single frame at a time.
-    pub fn read_all_frames(&mut self) -> io::Result<Box<[Frame]>> {
-        let mut frames = Vec::new();
-        loop {
-            let mut frame = Frame::default();
-            if let Err(err) = self.read_frame(&mut frame) {
-                match err.kind() {
-                    // We have found the end of the file. No more frames, we're done.
-                    io::ErrorKind::UnexpectedEof => break,
-                    // Something else went wrong...
-                    _ => Err(err)?,
-                }
+    /// The lengths are those of the box vectors `a`, `b`, and `c`. The angles are `alpha` between
+    /// `b` and `c`, `beta` between `a` and `c`, and `gamma` between `a` and `b`, like the unit cell
+    /// of a PDB file. An angle with a box vector of zero length is reported as 90 degrees, such
+    /// that a boxless frame gives lengths of zero and right angles.
+    pub fn box_lengths_and_angles(&self) -> ([f32; 3], [f32; 3]) {
+        // Work in double precision, such that nearly right angles come out right.
+        let [a, b, c] = self.box_rows().map(|v| v.map(|x| x as f64));
+        let dot = |u: [f64; 3], v: [f64; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+        let length = |v: [f64; 3]| dot(v, v).sqrt();
+        let angle = |u: [f64; 3], v: [f64; 3]| {
+            let norms = length(u) * length(v);
+            if norms == 0.0 {
+                return 90.0;
             }
-            frames.push(frame);
-        }
-        Ok(frames.into_boxed_slice())
+            (dot(u, v) / norms).clamp(-1.0, 1.0).acos().to_degrees() as f32
+        };
+        let lengths = [a, b, c].map(|v| length(v) as f32);
+        (lengths, [angle(b, c), angle(a, c), angle(a, b)])
     }
 
-    /// Reads and returns a [`Frame`] and advances one step.
-    pub fn read_frame(&mut self, frame: &mut Frame) -> io::Result<()> {
-        self.read_frame_with_selection(frame, &AtomSelection::All)
+    /// Returns whether this [`Frame`] has a box.
+    ///
+    /// Simulations in vacuum write a box of all zeros. For such a boxless frame, periodic boundary
+    /// conditions are meaningless.
+    pub fn has_box(&self) -> bool {
+        self.boxvec
+            .to_cols_array()
+            .iter()
+            .any(|v| v.abs() > f32::EPSILON)
     }
 
-    /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
-    pub fn read_frame_with_selection(
-        &mut self,
-        frame: &mut Frame,
-        atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
-        // Take the thread-local SCRATCH and use that while decoding the values.
-        let mut scratch = SCRATCH.take();
-        self.read_frame_with_scratch(frame, &mut scratch, atom_selection)
+    /// Returns whether all positions and box values of this [`Frame`] are finite.
+    ///
+    /// Decoding a corrupt frame can produce infinite or NaN positions, which would silently
+    /// propagate into any analysis. See [`XTCReader::set_strict_finite`] to reject such frames as
+    /// they are read.
+    pub fn is_finite(&self) -> bool {
+        self.positions.iter().all(|v| v.is_finite())
+            && self.boxvec.to_cols_array().iter().all(|v| v.is_finite())
     }
 
-    /// Reads and returns a [`Frame`] and advances one step, internally reading the compressed data
-    /// into `scratch`.
+    /// Returns the volume of the box of this [`Frame`] in nm³.
+    ///
+    /// Returns [`None`] if the frame has no box (see [`Frame::has_box`]).
+    pub fn volume(&self) -> Option<f32> {
+        self.has_box().then(|| self.boxvec.determinant().abs())
+    }
+
+    /// Returns the mass-weighted center of the positions in this [`Frame`].
+    ///
+    /// Since the xtc format does not store masses, they must be provided as `masses`, one for
+    /// each atom in this frame.
+    ///
+    /// # Panics
+    ///
+    /// If the number of `masses` is not equal to the number of atoms in this frame, this function
+    /// panics.
+    pub fn center_of_mass(&self, masses: &[f32]) -> Vec3 {
+        assert_eq!(
+            masses.len(),
+            self.natoms(),
+            "the number of masses must be equal to the number of atoms in the frame"
+        );
+        // Accumulate in double precision, since the sums can grow large for big systems.
+        let mut total = 0.0;
+        let mut weighted = [0.0; 3];
+        for (pos, &m) in self.positions.chunks_exact(3).zip(masses) {
+            total += m as f64;
+            for (w, &v) in weighted.iter_mut().zip(pos) {
+                *w += v as f64 * m as f64;
+            }
+        }
+        Vec3::from_array(weighted.map(|w| (w / total) as f32))
+    }
+
+    /// Returns the moment of inertia tensor of the positions in this [`Frame`], about their center.
+    ///
+    /// With `masses`, one for each atom, the tensor is mass-weighted and taken about the center of
+    /// mass. Without, each atom has unit mass and the tensor is taken about the center of
+    /// geometry. A frame without atoms, or without any mass, has a zero tensor.
+    ///
+    /// # Panics
+    ///
+    /// If the number of `masses` is not equal to the number of atoms in this frame, this function
+    /// panics.
+    pub fn inertia_tensor(&self, masses: Option<&[f32]>) -> Mat3 {
+        let mass = self.mass_fn(masses);
+        let Some((_, center)) = self.weighted_center(&mass) else {
+            return Mat3::ZERO;
+        };
+
+        let mut tensor = [[0.0; 3]; 3];
+        for (idx, pos) in self.positions.chunks_exact(3).enumerate() {
+            let m = mass(idx);
+            let r: [f64; 3] = std::array::from_fn(|i| pos[i] as f64 - center[i]);
+            let rr = r[0] * r[0] + r[1] * r[1] + r[2] * r[2];
+            for (i, row) in tensor.iter_mut().enumerate() {
+                for (j, t) in row.iter_mut().enumerate() {
+                    let delta = if i == j { rr } else { 0.0 };
+                    *t += m * (delta - r[i] * r[j]);
+                }
+            }
+        }
+        // The tensor is symmetric, so its rows are its columns.
+        Mat3::from_cols_array_2d(&tensor.map(|row| row.map(|t| t as f32)))
+    }
+
+    /// Returns the radius of gyration of the positions in this [`Frame`].
+    ///
+    /// With `masses`, one for each atom, the radius is mass-weighted and taken about the center of
+    /// mass. Without, each atom has unit mass and the radius is taken about the center of
+    /// geometry. A frame without atoms, or without any mass, has a radius of zero.
+    ///
+    /// # Panics
+    ///
+    /// If the number of `masses` is not equal to the number of atoms in this frame, this function
+    /// panics.
+    pub fn radius_of_gyration(&self, masses: Option<&[f32]>) -> f32 {
+        let mass = self.mass_fn(masses);
+        let Some((total, center)) = self.weighted_center(&mass) else {
+            return 0.0;
+        };
+
+        let mut sum = 0.0;
+        for (idx, pos) in self.positions.chunks_exact(3).enumerate() {
+            let r2: f64 = pos
+                .iter()
+                .zip(center)
+                .map(|(&v, c)| (v as f64 - c).powi(2))
+                .sum();
+            sum += mass(idx) * r2;
+        }
+        (sum / total).sqrt() as f32
+    }
+
+    /// Returns a coarse-grained [`Frame`] in which each group of consecutive atoms is replaced by
+    /// a single bead at its center.
+    ///
+    /// The atoms are divided into groups according to `group_sizes`, in order, such as one group
+    /// per residue to map an all-atom frame to one bead per residue. With `masses`, one for each
+    /// atom, each bead is placed at the center of mass of its group. Without, it is placed at the
+    /// center of geometry. A group without any mass also gets its center of geometry. The other
+    /// values of this frame, such as its step, time, and box, are carried over.
+    ///
+    /// # Panics
+    ///
+    /// If the `group_sizes` do not sum to the number of atoms in this frame, if any group is
+    /// empty, or if the number of `masses` is not equal to the number of atoms in this frame, this
+    /// function panics.
+    pub fn coarse_grain(&self, group_sizes: &[usize], masses: Option<&[f32]>) -> Frame {
+        assert_eq!(
+            group_sizes.iter().sum::<usize>(),
+            self.natoms(),
+            "the group sizes must sum to the number of atoms in the frame"
+        );
+        assert!(
+            !group_sizes.contains(&0),
+            "the group sizes must all be greater than zero"
+        );
+        let mass = self.mass_fn(masses);
+
+        let mut positions = Vec::with_capacity(group_sizes.len() * 3);
+        let mut start = 0;
+        for &size in group_sizes {
+            let group = &self.positions[start * 3..(start + size) * 3];
+            // Accumulate in double precision, like the other centers.
+            let mut total = 0.0;
+            let mut weighted = [0.0; 3];
+            let mut geometric = [0.0; 3];
+            for (i, pos) in group.chunks_exact(3).enumerate() {
+                let m = mass(start + i);
+                total += m;
+                for ((w, g), &v) in weighted.iter_mut().zip(&mut geometric).zip(pos) {
+                    *w += v as f64 * m;
+                    *g += v as f64;
+                }
+            }
+            let center = if total != 0.0 {
+                weighted.map(|w| w / total)
+            } else {
+                geometric.map(|g| g / size as f64)
+            };
+            positions.extend(center.map(|c| c as f32));
+            start += size;
+        }
+
+        Frame { positions, ..*self }
+    }
+
+    /// Combine `frames` into a single [`Frame`], by concatenating their positions in order.
+    ///
+    /// This recombines selections of the same frame that were read separately, such as a protein
+    /// and a ligand that were decoded with different strategies. The step, time, box, and
+    /// precision are carried over, as are the index and offset if all frames agree on them.
+    /// Merging no frames results in an empty default frame.
+    ///
+    /// # Errors
+    ///
+    /// If the frames disagree on their step, time, box, or precision, they cannot be selections of
+    /// the same frame, and an error of kind [`io::ErrorKind::InvalidInput`] is returned.
+    pub fn merge(frames: &[Frame]) -> io::Result<Frame> {
+        let Some((first, rest)) = frames.split_first() else {
+            return Ok(Frame::default());
+        };
+        for (idx, frame) in rest.iter().enumerate() {
+            let mismatch = if frame.step != first.step {
+                "step"
+            } else if frame.time != first.time {
+                "time"
+            } else if frame.boxvec != first.boxvec {
+                "box"
+            } else if frame.precision != first.precision {
+                "precision"
+            } else {
+                continue;
+            };
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot merge frames with a different {mismatch} (frame {} differs from frame 0)",
+                    idx + 1
+                ),
+            ));
+        }
+
+        let natoms = frames.iter().map(Frame::natoms).sum::<usize>();
+        let mut positions = Vec::with_capacity(natoms * 3);
+        for frame in frames {
+            positions.extend_from_slice(&frame.positions);
+        }
+        Ok(Frame {
+            positions,
+            index: first
+                .index
+                .filter(|_| rest.iter().all(|f| f.index == first.index)),
+            offset: first
+                .offset
+                .filter(|_| rest.iter().all(|f| f.offset == first.offset)),
+            ..*first
+        })
+    }
+
+    /// Returns a function that gives the mass of the atom at an index, which is one if no `masses`
+    /// are provided.
+    ///
+    /// # Panics
+    ///
+    /// If the number of `masses` is not equal to the number of atoms in this frame, this function
+    /// panics.
+    fn mass_fn<'m>(&self, masses: Option<&'m [f32]>) -> impl Fn(usize) -> f64 + 'm {
+        if let Some(masses) = masses {
+            assert_eq!(
+                masses.len(),
+                self.natoms(),
+                "the number of masses must be equal to the number of atoms in the frame"
+            );
+        }
+        move |idx| masses.map_or(1.0, |masses| masses[idx] as f64)
+    }
+
+    /// Returns the total mass and the mass-weighted center of the positions in this [`Frame`],
+    /// or [`None`] if the total mass is zero.
+    fn weighted_center(&self, mass: impl Fn(usize) -> f64) -> Option<(f64, [f64; 3])> {
+        // Accumulate in double precision, since the sums can grow large for big systems.
+        let mut total = 0.0;
+        let mut center = [0.0; 3];
+        for (idx, pos) in self.positions.chunks_exact(3).enumerate() {
+            let m = mass(idx);
+            total += m;
+            for (c, &v) in center.iter_mut().zip(pos) {
+                *c += v as f64 * m;
+            }
+        }
+        (total != 0.0).then(|| (total, center.map(|c| c / total)))
+    }
+
+    /// Returns the principal moments of inertia and the principal axes of the positions in this
+    /// [`Frame`].
+    ///
+    /// These are the eigenvalues and eigenvectors of the [`Frame::inertia_tensor`], with the same
+    /// meaning of `masses`. The moments are sorted in ascending order, and the axes are the
+    /// corresponding columns of the returned matrix. The first axis is thus the long axis of the
+    /// molecule. The axes are orthonormal and form a right-handed system, such that the matrix is
+    /// a rotation.
+    ///
+    /// For degenerate cases, such as a single atom or collinear atoms, the moments that coincide
+    /// get an arbitrary, but still orthonormal, set of axes.
+    ///
+    /// # Panics
+    ///
+    /// If the number of `masses` is not equal to the number of atoms in this frame, this function
+    /// panics.
+    pub fn principal_axes(&self, masses: Option<&[f32]>) -> (Vec3, Mat3) {
+        let tensor = self.inertia_tensor(masses).to_cols_array_2d();
+        let (values, vectors) = symmetric_eigen(tensor.map(|row| row.map(|t| t as f64)));
+        let axes = Mat3::from_cols_array_2d(&vectors.map(|v| v.map(|x| x as f32)));
+        (Vec3::from_array(values.map(|v| v as f32)), axes)
+    }
+
+    /// Returns the distances between all pairs of atoms in the [`AtomSelection`], as the upper
+    /// triangle of the distance matrix.
+    ///
+    /// For `n` selected atoms, the `n * (n - 1) / 2` distances are ordered row by row, as the pairs
+    /// `(0, 1), (0, 2), ..., (0, n - 1), (1, 2), ...` of the selected atoms. If `pbc` is set and
+    /// this frame has a box, the minimum image convention is applied to each distance. See
+    /// [`Frame::minimum_image`].
+    ///
+    /// # Note
+    ///
+    /// The number of pairs grows quadratically with the number of selected atoms, and so do the
+    /// time and memory this function takes. Selecting 10 000 atoms already yields some 50 million
+    /// distances. When only the close pairs are of interest, [`Frame::distances_within`] keeps the
+    /// memory use in check.
+    pub fn distance_matrix(&self, selection: &AtomSelection, pbc: bool) -> Vec<f32> {
+        let coords = self.selected_coords(selection);
+        let n = coords.len();
+        let mut distances = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+        for (i, &a) in coords.iter().enumerate() {
+            for &b in &coords[i + 1..] {
+                distances.push(self.separation(a, b, pbc).length());
+            }
+        }
+        distances
+    }
+
+    /// Returns the pairs of atoms in the [`AtomSelection`] that lie within `cutoff` of each other,
+    /// along with their distance.
+    ///
+    /// Each pair is given by the indices of its atoms in this frame, with the first index below
+    /// the second. The pairs are ordered like in [`Frame::distance_matrix`], and `pbc` has the
+    /// same meaning. All pairs are still visited, but only the close ones are stored, which keeps
+    /// the memory use down for contact and hydrogen bond analyses.
+    pub fn distances_within(
+        &self,
+        selection: &AtomSelection,
+        cutoff: f32,
+        pbc: bool,
+    ) -> Vec<(usize, usize, f32)> {
+        let indices = self.selected_indices(selection);
+        let cutoff2 = cutoff * cutoff;
+        let mut pairs = Vec::new();
+        for (i, &a) in indices.iter().enumerate() {
+            for &b in &indices[i + 1..] {
+                let d2 = self
+                    .separation(self.coords_at(a), self.coords_at(b), pbc)
+                    .length_squared();
+                if d2 <= cutoff2 {
+                    pairs.push((a, b, d2.sqrt()));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Returns the shortest periodic image of the separation vector `d`, according to the box of
+    /// this [`Frame`].
+    ///
+    /// The box vectors `c`, `b`, and `a` are subtracted in that order, like Gromacs does for a box
+    /// in its canonical form (see [`writer::validate_box`]). For a rectangular box, this gives the
+    /// exact minimum image. For a triclinic box, it is exact for separations up to half the
+    /// shortest box height, which covers any sensible cutoff.
+    ///
+    /// A box vector with a diagonal element of zero, such as the third vector of a slab, is not
+    /// periodic and is skipped. If this frame has no box, `d` is returned as is.
+    pub fn minimum_image(&self, mut d: Vec3) -> Vec3 {
+        let (a, b, c) = (self.boxvec.x_axis, self.boxvec.y_axis, self.boxvec.z_axis);
+        if c.z != 0.0 {
+            d -= c * (d.z / c.z).round();
+        }
+        if b.y != 0.0 {
+            d -= b * (d.y / b.y).round();
+        }
+        if a.x != 0.0 {
+            d -= a * (d.x / a.x).round();
+        }
+        d
+    }
+
+    /// Returns the separation vector from `a` to `b`, as a minimum image if `pbc` is set.
+    fn separation(&self, a: Vec3, b: Vec3, pbc: bool) -> Vec3 {
+        let d = b - a;
+        if pbc {
+            self.minimum_image(d)
+        } else {
+            d
+        }
+    }
+
+    /// Returns the indices of the atoms in this [`Frame`] that are included in the `selection`.
+    fn selected_indices(&self, selection: &AtomSelection) -> Vec<usize> {
+        (0..self.natoms())
+            .map_while(|idx| selection.is_included(idx).map(|included| (idx, included)))
+            .filter_map(|(idx, included)| included.then_some(idx))
+            .take(selection.natoms_selected(self.natoms()))
+            .collect()
+    }
+
+    /// Returns the coordinates of the atoms in this [`Frame`] that are included in the
+    /// `selection`.
+    fn selected_coords(&self, selection: &AtomSelection) -> Vec<Vec3> {
+        self.selected_indices(selection)
+            .into_iter()
+            .map(|idx| self.coords_at(idx))
+            .collect()
+    }
+
+    /// Returns the coordinates of the atom at `idx`.
+    fn coords_at(&self, idx: usize) -> Vec3 {
+        Vec3::from_slice(&self.positions[idx * 3..idx * 3 + 3])
+    }
+
+    /// Returns a new [`Frame`] with only the positions of the atoms in the [`AtomSelection`].
+    ///
+    /// The step, time, box, and precision are the same as those of this frame. The result is
+    /// equal to reading the frame with the same selection.
+    pub fn subset(&self, selection: &AtomSelection) -> Frame {
+        let natoms = selection.natoms_selected(self.natoms());
+        let positions = self
+            .positions
+            .chunks_exact(3)
+            .enumerate()
+            .filter(|&(idx, _)| selection.is_included(idx).unwrap_or_default())
+            .take(natoms)
+            .flat_map(|(_, pos)| pos)
+            .copied()
+            .collect();
+        Frame { positions, ..*self }
+    }
+
+    /// Returns the number of atoms in this [`Frame`].
+    pub fn natoms(&self) -> usize {
+        let npos = self.positions.len();
+        assert_eq!(
+            npos % 3,
+            0,
+            "the number of single positions in a frame must always be a multiple of 3"
+        );
+        npos / 3
+    }
+}
+
+/// A frame with its positions quantized to the grid on which they are stored, as read by
+/// [`XTCReader::read_frame_quantized`].
+///
+/// The quantized positions are integers relative to the `offset`, which is the smallest integer
+/// position in the frame. They are lossless with respect to the precision of the frame, while
+/// taking up half the memory of `f32` positions.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QuantizedFrame {
+    pub step: u64,
+    /// Time in picoseconds.
+    pub time: f32,
+    pub boxvec: BoxVec,
+    pub precision: f32,
+    /// The integer position that is subtracted from each quantized position, per dimension.
+    pub offset: [i32; 3],
+    pub positions: Vec<i16>,
+}
+
+impl QuantizedFrame {
+    /// The factor by which a quantized position, with the `offset` added back, is multiplied to
+    /// get the position in nm.
+    pub fn scale(&self) -> f32 {
+        self.precision.recip()
+    }
+
+    /// Returns the positions in nm, equal to those that [`XTCReader::read_frame`] would read.
+    pub fn dequantize(&self) -> Vec<f32> {
+        let scale = self.scale();
+        self.positions
+            .chunks_exact(3)
+            .flat_map(|pos| (0..3).map(move |i| (pos[i] as i32 + self.offset[i]) as f32 * scale))
+            .collect()
+    }
+
+    /// Returns the number of atoms in this [`QuantizedFrame`].
+    pub fn natoms(&self) -> usize {
+        self.positions.len() / 3
+    }
+}
+
+/// Frames that share a single box, as read by [`XTCReader::read_frames_shared_box`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SharedBoxFrames {
+    /// The box shared by all frames.
+    pub boxvec: BoxVec,
+    /// The step of each frame.
+    pub steps: Vec<u64>,
+    /// The time of each frame in picoseconds.
+    pub times: Vec<f32>,
+    /// The selected positions of each frame.
+    pub positions: Vec<Vec<f32>>,
+}
+
+/// The frames of a read within a memory budget, as returned by [`XTCReader::read_budgeted`].
+#[derive(Debug)]
+pub enum Budgeted<I> {
+    /// All frames, read at once since their positions fit within the budget.
+    Materialized(Vec<Frame>),
+    /// An iterator that reads the frames one by one, since their positions exceed the budget.
+    Streaming(I),
+}
+
+/// Calculate the xdr padding for some number of bytes.
+#[doc(hidden)]
+pub fn padding(n: usize) -> usize {
+    (4 - (n % 4)) % 4
+}
+
+/// Read the positions in a frame after the header.
+///
+/// If successful, returns the number of compressed bytes that were read.
+///
+/// Internal use.
+#[doc(hidden)]
+pub fn read_positions<'s, 'r, B: buffer::Buffered<'s, 'r, R>, R: Read>(
+    file: &'r mut R,
+    header_natoms: usize,
+    scratch: &'s mut Vec<u8>,
+    frame: &mut Frame,
+    atom_selection: &AtomSelection,
+    magic: Magic,
+) -> io::Result<usize> {
+    // If the atom_selection specifies fewer atoms, we will only allocate up to that point.
+    let natoms_selected = atom_selection.natoms_selected(header_natoms);
+
+    // Resize the positions array for the selected number of atoms.
+    frame.positions.resize(natoms_selected * 3, f32::NAN);
+    let precision = read_f32(file)?;
+    frame.precision = Precision::Compressed(precision);
+    read_compressed_positions::<B, R>(
+        file,
+        header_natoms,
+        &mut frame.positions,
+        precision,
+        scratch,
+        atom_selection,
+        magic,
+    )
+}
+
+/// Returns the eigenvalues and eigenvectors of a symmetric 3×3 matrix.
+///
+/// The eigenvalues are sorted in ascending order, and each eigenvector is a unit vector. The
+/// eigenvectors form a right-handed system. They are found with the cyclic Jacobi method, which
+/// converges for any symmetric matrix and leaves a diagonal matrix, such as a zero matrix,
+/// untouched.
+fn symmetric_eigen(mut a: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    const SWEEPS: usize = 64;
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    for _ in 0..SWEEPS {
+        let off = a[0][1].powi(2) + a[0][2].powi(2) + a[1][2].powi(2);
+        let diag = a[0][0].powi(2) + a[1][1].powi(2) + a[2][2].powi(2);
+        if off <= f64::EPSILON.powi(2) * diag || off == 0.0 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            // Rotate in the pq plane such that the off-diagonal element a[p][q] vanishes.
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            for row in &mut a {
+                let (ap, aq) = (row[p], row[q]);
+                row[p] = c * ap - s * aq;
+                row[q] = s * ap + c * aq;
+            }
+            let (ap, aq) = (a[p], a[q]);
+            a[p] = std::array::from_fn(|k| c * ap[k] - s * aq[k]);
+            a[q] = std::array::from_fn(|k| s * ap[k] + c * aq[k]);
+            for row in &mut v {
+                let (vp, vq) = (row[p], row[q]);
+                row[p] = c * vp - s * vq;
+                row[q] = s * vp + c * vq;
+            }
+        }
+    }
+
+    // The columns of v are the eigenvectors. Sort them by their eigenvalues.
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| a[i][i].total_cmp(&a[j][j]));
+    let values = order.map(|i| a[i][i]);
+    let mut vectors = order.map(|i| [v[0][i], v[1][i], v[2][i]]);
+
+    // Make the system right-handed by choosing the direction of the last axis.
+    let [x, y, z] = vectors;
+    let cross = [
+        x[1] * y[2] - x[2] * y[1],
+        x[2] * y[0] - x[0] * y[2],
+        x[0] * y[1] - x[1] * y[0],
+    ];
+    if cross[0] * z[0] + cross[1] * z[1] + cross[2] * z[2] < 0.0 {
+        vectors[2] = z.map(|v| -v);
+    }
+    (values, vectors)
+}
+
+// A Vec3 must consist of exactly three f32s for `flatten_vec3s` and `as_vec3s` to be sound.
+const _: () = assert!(std::mem::size_of::<Vec3>() == 3 * std::mem::size_of::<f32>());
+const _: () = assert!(std::mem::align_of::<Vec3>() == std::mem::align_of::<f32>());
+
+/// View a slice of [`Vec3`]s as a flat slice of `f32`s.
+fn flatten_vec3s(coords: &mut [Vec3]) -> &mut [f32] {
+    let len = coords.len() * 3;
+    // Safety: A Vec3 is `repr(C)` and consists of three f32s without padding, as asserted above.
+    // Its alignment is that of an f32.
+    unsafe { std::slice::from_raw_parts_mut(coords.as_mut_ptr().cast::<f32>(), len) }
+}
+
+/// View a flat slice of `f32`s as a slice of [`Vec3`]s.
+///
+/// Any trailing values that do not make up a whole [`Vec3`] are left out.
+fn as_vec3s(positions: &[f32]) -> &[Vec3] {
+    let len = positions.len() / 3;
+    // Safety: A Vec3 is `repr(C)` and consists of three f32s without padding, as asserted above.
+    // Its alignment is that of an f32.
+    unsafe { std::slice::from_raw_parts(positions.as_ptr().cast::<Vec3>(), len) }
+}
+
+/// The size of the window through which [`XTCReader::scan`] reads the start of each frame.
+///
+/// It holds the header, precision, prelude, and number of compressed bytes of a frame, and a
+/// number of small frames in full.
+const SCAN_WINDOW: usize = 4096;
+
+/// The number of frames that are inspected by [`XTCReader::offsets_uniform`].
+const UNIFORM_PROBE: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct XTCReader<R> {
+    pub file: R,
+    pub step: usize,
+    /// The exclusive frame offsets of the whole file, once they have been determined.
+    offsets: Option<Box<[u64]>>,
+    /// Whether headers are read with [`Header::read_lenient`].
+    lenient: bool,
+    /// The number of headers read since the first frame in which the number of atoms did not
+    /// match its repeated value.
+    natoms_mismatches: usize,
+    /// The mapping applied to the step and time of each header that is read.
+    remap: Option<StepRemap>,
+    /// The path this reader was opened from, if any, such that it can be reopened.
+    path: Option<PathBuf>,
+    /// The byte offset at which the xtc data starts, and to which [`XTCReader::home`] returns.
+    base: u64,
+    /// Whether frames whose time lies before that of the previous frame are rejected.
+    strict_time: bool,
+    /// Whether frames with non-finite positions or box values are rejected.
+    strict_finite: bool,
+    /// The step at which the last header was read, and its time, for the strict time check.
+    prev_time: Option<(usize, f32)>,
+}
+
+impl XTCReader<std::fs::File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(&path)?;
+        Ok(Self {
+            path: Some(path.as_ref().to_path_buf()),
+            ..Self::new(file)
+        })
+    }
+
+    /// Open a file as an [`XTCReader`] that starts reading at the byte `offset`.
+    ///
+    /// This allows reading xtc data that is preceded by other data, such as a preamble with
+    /// metadata or the xtc data being embedded in a larger container. The frames are counted from
+    /// the `offset`, and [`XTCReader::home`] returns to it rather than to the start of the file.
+    /// Frame offsets, such as those of [`XTCReader::determine_offsets`], remain positions within
+    /// the whole file.
+    ///
+    /// # Errors
+    ///
+    /// Errors from opening the file and seeking to the `offset` are passed through.
+    pub fn open_at<P: AsRef<Path>>(path: P, offset: u64) -> io::Result<Self> {
+        let mut reader = Self::open(path)?;
+        reader.file.seek(SeekFrom::Start(offset))?;
+        reader.base = offset;
+        Ok(reader)
+    }
+
+    /// Open a file as an [`XTCReader`], and determine the offsets of all of its frames up front.
+    ///
+    /// This is a shorthand for [`XTCReader::open`] followed by [`XTCReader::index`].
+    ///
+    /// # Errors
+    ///
+    /// Any invalid header results in an error. If the last frame extends beyond the end of the
+    /// file, an error of the kind [`io::ErrorKind::UnexpectedEof`] is returned.
+    pub fn open_indexed<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = Self::open(path)?;
+        reader.index()?;
+        Ok(reader)
+    }
+
+    /// Open the path this reader was opened from anew, and go back to the first frame.
+    ///
+    /// Unlike [`XTCReader::home`], this gives a fresh file handle, which sees the current contents
+    /// of the path even if the file has been replaced since it was opened. Any cached frame
+    /// offsets are forgotten. The lenient mode, strict time checking, and step remapping are
+    /// kept.
+    ///
+    /// # Errors
+    ///
+    /// If this reader was not opened from a path, an error of kind
+    /// [`io::ErrorKind::Unsupported`] is returned. Errors from opening the file are passed
+    /// through.
+    pub fn reopen(&mut self) -> io::Result<()> {
+        self.reopen_with(|file| file)
+    }
+
+    /// Create an [`XTCReader`] from a raw file descriptor, such as one handed down by a parent
+    /// process.
+    ///
+    /// This avoids opening the trajectory by its path when it is already open. Reading starts at
+    /// the current position of the descriptor, and [`XTCReader::home`] returns to the start of
+    /// the file. Since the path of the file is not known, the reader cannot be reopened.
+    ///
+    /// # Safety
+    ///
+    /// The `fd` must be an open file descriptor that is not owned by anything else, since the
+    /// reader takes ownership of it and closes it when dropped. See
+    /// [`FromRawFd::from_raw_fd`](std::os::unix::io::FromRawFd::from_raw_fd).
+    #[cfg(unix)]
+    pub unsafe fn from_raw_fd(fd: std::os::unix::io::RawFd) -> Self {
+        use std::os::unix::io::FromRawFd;
+        // Safety: Upheld by the caller.
+        Self::new(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// Create an [`XTCReader`] from a raw file handle, such as one handed down by a parent
+    /// process.
+    ///
+    /// This avoids opening the trajectory by its path when it is already open. Reading starts at
+    /// the current position of the handle, and [`XTCReader::home`] returns to the start of the
+    /// file. Since the path of the file is not known, the reader cannot be reopened.
+    ///
+    /// # Safety
+    ///
+    /// The `handle` must be an open file handle that is not owned by anything else, since the
+    /// reader takes ownership of it and closes it when dropped. See
+    /// [`FromRawHandle::from_raw_handle`](std::os::windows::io::FromRawHandle::from_raw_handle).
+    #[cfg(windows)]
+    pub unsafe fn from_raw_handle(handle: std::os::windows::io::RawHandle) -> Self {
+        use std::os::windows::io::FromRawHandle;
+        // Safety: Upheld by the caller.
+        Self::new(unsafe { File::from_raw_handle(handle) })
+    }
+}
+
+impl<R: Read> XTCReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            file: reader,
+            step: 0,
+            offsets: None,
+            lenient: false,
+            natoms_mismatches: 0,
+            remap: None,
+            path: None,
+            base: 0,
+            strict_time: false,
+            strict_finite: false,
+            prev_time: None,
+        }
+    }
+
+    /// Replace the reader by `wrap`ping a fresh handle to the path this reader was opened from.
+    fn reopen_with(&mut self, wrap: impl FnOnce(File) -> R) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "cannot reopen a reader that was not opened from a path",
+            ));
+        };
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(self.base))?;
+        self.file = wrap(file);
+        self.step = 0;
+        self.offsets = None;
+        self.prev_time = None;
+        self.natoms_mismatches = 0;
+        Ok(())
+    }
+
+    /// Set whether headers in which the number of atoms does not match its repeated value are
+    /// accepted.
+    ///
+    /// By default, such a header results in an error. In lenient mode, the first value is trusted
+    /// and the discrepancy is counted, see [`XTCReader::natoms_mismatches`] and
+    /// [`Header::read_lenient`].
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Returns the number of frames read in lenient mode whose number of atoms did not match its
+    /// repeated value.
+    ///
+    /// The count starts over when the reader returns to the first frame.
+    pub fn natoms_mismatches(&self) -> usize {
+        self.natoms_mismatches
+    }
+
+    /// Set whether a frame whose time lies before that of the frame preceding it is rejected.
+    ///
+    /// Corrupt or mistakenly concatenated trajectories may have their time go backward. By
+    /// default, such frames are read like any other. In strict mode, they result in a
+    /// [`NonMonotonicTime`] error, carried by an [`io::Error`] of the kind
+    /// [`io::ErrorKind::InvalidData`]. The times are checked after any [`StepRemap`] is applied.
+    ///
+    /// The check applies to the headers found by [`XTCReader::scan`], and thereby to all reads of
+    /// a [`FrameSelection`], as well as to frames that are read one after the other. Frames that
+    /// are read at an offset are not compared to the frame read before them, since they need not
+    /// follow it. Enabling strict mode forgets any cached frame offsets, such that they are
+    /// checked when they are determined anew.
+    pub fn set_strict_time(&mut self, strict: bool) {
+        if strict && !self.strict_time {
+            self.offsets = None;
+        }
+        self.strict_time = strict;
+    }
+
+    /// Set whether a frame with non-finite positions or box values is rejected.
+    ///
+    /// By default, such frames are read like any other. In strict mode, reading a [`Frame`] checks
+    /// [`Frame::is_finite`] after decoding, and a failing frame results in an error of the kind
+    /// [`io::ErrorKind::InvalidData`]. The reader has then advanced past that frame, such that the
+    /// next frame can still be read.
+    pub fn set_strict_finite(&mut self, strict: bool) {
+        self.strict_finite = strict;
+    }
+
+    /// Set the mapping that is applied to the step and time of each frame as it is read, or
+    /// remove it by passing [`None`].
+    ///
+    /// The index passed to the mapping is the [`XTCReader::step`] at which the frame is read. The
+    /// mapping applies to the headers read through this reader, including those of
+    /// [`XTCReader::scan`].
+    pub fn set_step_remap(&mut self, remap: Option<StepRemap>) {
+        self.remap = remap;
+    }
+
+    /// Read the header at the start of a frame.
+    ///
+    /// Assumes the internal reader is at the start of a new frame header.
+    pub fn read_header(&mut self) -> io::Result<Header> {
+        let mut header = Header::read_with(&mut self.file, self.lenient)?;
+        if let Some(remap) = &self.remap {
+            remap.apply(self.step, &mut header);
+        }
+        self.check_time(&header)?;
+        self.count_natoms_mismatch(&header);
+        Ok(header)
+    }
+
+    /// Read the header at the start of a frame, or return [`None`] at the end of the reader.
+    ///
+    /// See [`Header::read_optional`].
+    fn read_header_optional(&mut self) -> io::Result<Option<Header>> {
+        let mut header = Header::read_optional_with(&mut self.file, self.lenient)?;
+        if let (Some(remap), Some(header)) = (&self.remap, &mut header) {
+            remap.apply(self.step, header);
+        }
+        if let Some(header) = &header {
+            self.check_time(header)?;
+            self.count_natoms_mismatch(header);
+        }
+        Ok(header)
+    }
+
+    /// Count the `header` if its number of atoms does not match its repeated value.
+    ///
+    /// See [`XTCReader::natoms_mismatches`].
+    fn count_natoms_mismatch(&mut self, header: &Header) {
+        if header.natoms_mismatch().is_some() {
+            self.natoms_mismatches += 1;
+        }
+    }
+
+    /// In strict mode, check that the time of a `header` that was just read at the current step
+    /// does not lie before that of the header read at an earlier step.
+    ///
+    /// See [`XTCReader::set_strict_time`].
+    fn check_time(&mut self, header: &Header) -> io::Result<()> {
+        if !self.strict_time {
+            return Ok(());
+        }
+        if let Some((prev_step, prev)) = self.prev_time {
+            if prev_step < self.step && header.time < prev {
+                return Err(NonMonotonicTime {
+                    frame: self.step,
+                    prev,
+                    cur: header.time,
+                }
+                .into());
+            }
+        }
+        self.prev_time = Some((self.step, header.time));
+        Ok(())
+    }
+
+    /// In strict mode, check that a `frame` that was just read is finite.
+    ///
+    /// See [`XTCReader::set_strict_finite`].
+    fn check_finite(strict: bool, frame: &Frame) -> io::Result<()> {
+        if !strict || frame.is_finite() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "frame at step {} contains non-finite positions or box values",
+                frame.step
+            ),
+        ))
+    }
+
+    /// Read a small number of uncompressed positions.
+    ///
+    /// If successful, returns the number of compressed bytes that were read.
+    ///
+    /// # Panics
+    ///
+    /// `natoms` must be 9 or less, otherwise the positions must be decompressed and cannot be read
+    /// directly through this function.  
+    ///
+    /// Oh xtc, you are so fucking weird.
+    pub fn read_smol_positions(
+        &mut self,
+        natoms: usize,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<usize> {
+        assert!(
+            natoms <= SMALL_NATOMS_THRESHOLD,
+            "only read uncomprossed positions when the number of atoms is \
+            {SMALL_NATOMS_THRESHOLD} or less"
+        );
+
+        // In case the number of atoms is very small, just read their uncompressed positions.
+        let mut buf = [0.0; SMALL_NATOMS_THRESHOLD * 3];
+        let n = self.read_smol_selected(natoms, &mut buf, atom_selection)?;
+        frame.positions.clear();
+        frame.positions.extend_from_slice(&buf[..n]);
+        // The positions were never compressed, so there is no precision to speak of.
+        frame.precision = Precision::Uncompressed;
+
+        Ok(natoms * 3 * std::mem::size_of::<f32>())
+    }
+
+    /// Reads the positions of a frame for which the `header` has just been read into
+    /// `positions`, which must be sized for the selected atoms.
+    fn read_positions_into_slice(
+        &mut self,
+        header: &Header,
+        positions: &mut [f32],
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        if !header.is_compressed() {
+            let mut buf = [0.0; SMALL_NATOMS_THRESHOLD * 3];
+            let n = self.read_smol_selected(header.natoms, &mut buf, atom_selection)?;
+            for (position, &value) in positions.iter_mut().zip(&buf[..n]) {
+                *position = value;
+            }
+        } else {
+            let precision = read_f32(&mut self.file)?;
+            with_scratch(|scratch| {
+                read_compressed_positions::<UnBuffered, R>(
+                    &mut self.file,
+                    header.natoms,
+                    positions,
+                    precision,
+                    scratch,
+                    atom_selection,
+                    header.magic,
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Read the uncompressed positions of a frame of `natoms` atoms into `buf`, and gather the
+    /// positions of the atoms in the [`AtomSelection`] at its start.
+    ///
+    /// Returns the number of values that were gathered. The positions of at most
+    /// [`SMALL_NATOMS_THRESHOLD`] atoms are stored uncompressed, so they fit on the stack.
+    fn read_smol_selected(
+        &mut self,
+        natoms: usize,
+        buf: &mut [f32; SMALL_NATOMS_THRESHOLD * 3],
+        atom_selection: &AtomSelection,
+    ) -> io::Result<usize> {
+        let buf = &mut buf[..natoms * 3];
+        read_f32s(&mut self.file, buf)?;
+        let mut n = 0;
+        for idx in 0..natoms {
+            if atom_selection.is_included(idx).unwrap_or_default() {
+                buf.copy_within(idx * 3..idx * 3 + 3, n);
+                n += 3;
+            }
+        }
+        Ok(n)
+    }
+
+    /// A convenience function to read all frames in a trajectory.
+    ///
+    /// It is likely more efficient to use [`XTCReader::read_frame`] if you are only interested in
+    /// the values of a single frame at a time.
+    ///
+    /// A trajectory that ends in the middle of a frame, such as one of a simulation that crashed
+    /// while writing, gives the complete frames before it. To detect such a truncated frame, read
+    /// the frames through [`XTCReader::read_next_frame`].
+    ///
+    /// # Errors
+    ///
+    /// Reaching the end of the file ends the read. Any other reader error is passed through.
+    pub fn read_all_frames(&mut self) -> io::Result<Box<[Frame]>> {
+        self.read_all_frames_with_capacity(0)
+    }
+
+    /// Read all frames in a trajectory, reserving space for `n` frames up front.
+    ///
+    /// When the number of frames is known, for instance through [`XTCReader::frame_count`],
+    /// passing it here avoids reallocating the list of frames as it grows.
+    ///
+    /// See [`XTCReader::read_all_frames`].
+    pub fn read_all_frames_with_capacity(&mut self, n: usize) -> io::Result<Box<[Frame]>> {
+        let mut frames = Vec::with_capacity(n);
+        loop {
+            match self.read_next_frame(&AtomSelection::All) {
+                Ok(Some(frame)) => frames.push(frame),
+                Ok(None) => break,
+                // A truncated last frame ends the trajectory.
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(frames.into_boxed_slice())
+    }
+
+    /// Fold over all remaining frames in a trajectory, reusing a single [`Frame`] and scratch
+    /// buffer for decoding.
+    ///
+    /// This is the streaming counterpart to [`XTCReader::read_all_frames`]. Only one frame is
+    /// held in memory at a time, so running statistics (such as a mean position or its variance)
+    /// can be accumulated in a single pass with a memory footprint that does not grow with the
+    /// number of frames.
+    ///
+    /// Like [`XTCReader::read_all_frames`], a truncated last frame ends the fold without being
+    /// passed to `f`.
+    ///
+    /// # Errors
+    ///
+    /// Reaching the end of the file ends the fold. Any other reader error is passed through.
+    pub fn fold_frames<A, F: FnMut(A, &Frame) -> A>(&mut self, init: A, mut f: F) -> io::Result<A> {
+        let mut frame = Frame::default();
+        let mut scratch = Vec::new();
+        let mut acc = init;
+        loop {
+            let read = self.read_header_optional().and_then(|header| match header {
+                Some(header) => self
+                    .read_frame_body::<UnBuffered>(
+                        header,
+                        &mut frame,
+                        &mut scratch,
+                        &AtomSelection::All,
+                    )
+                    .map(|_| true),
+                None => Ok(false),
+            });
+            match read {
+                Ok(true) => acc = f(acc, &frame),
+                Ok(false) => break,
+                // A truncated last frame ends the trajectory.
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Reads and returns the next [`Frame`] according to the [`AtomSelection`], and advances one
+    /// step.
+    ///
+    /// Returns [`None`] when the reader is exactly at the end of the trajectory. Unlike
+    /// [`XTCReader::read_frame`], this separates the clean end of a trajectory from a truncated
+    /// frame, without the need to inspect the kind of the error.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. A frame that is cut off results in an
+    /// error of the kind [`io::ErrorKind::UnexpectedEof`].
+    pub fn read_next_frame(&mut self, atom_selection: &AtomSelection) -> io::Result<Option<Frame>> {
+        let Some(header) = self.read_header_optional()? else {
+            return Ok(None);
+        };
+        let mut frame = Frame::default();
+        with_scratch(|scratch| {
+            self.read_frame_body::<UnBuffered>(header, &mut frame, scratch, atom_selection)
+        })?;
+        Ok(Some(frame))
+    }
+
+    /// Reads and returns a [`Frame`] and advances one step.
+    pub fn read_frame(&mut self, frame: &mut Frame) -> io::Result<()> {
+        self.read_frame_with_selection(frame, &AtomSelection::All)
+    }
+
+    /// Reads the next frame according to the [`AtomSelection`] with its positions quantized, and
+    /// advances one step.
+    ///
+    /// See [`QuantizedFrame`] for how to recover the positions.
+    ///
+    /// # Errors
+    ///
+    /// The positions of frames with 9 atoms or less are not stored on a grid, the positions of a
+    /// frame may span more grid points than fit in an `i16`, and a corrupted frame may hold a
+    /// precision that is not finite and positive. In these cases, an error of the kind
+    /// [`io::ErrorKind::InvalidData`] is returned. Any reader errors are passed through.
+    pub fn read_frame_quantized(
+        &mut self,
+        frame: &mut QuantizedFrame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        let header = self.read_header()?;
+        if !header.is_compressed() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "cannot quantize the positions of a frame with {} atoms, since positions are \
+                    only compressed for frames with more than {SMALL_NATOMS_THRESHOLD} atoms",
+                    header.natoms
+                ),
+            ));
+        }
+
+        let natoms_selected = atom_selection.natoms_selected(header.natoms);
+        frame.positions.resize(natoms_selected * 3, 0);
+        frame.precision = read_f32(&mut self.file)?;
+        validate_precision(frame.precision)?;
+        frame.offset = with_scratch(|scratch| {
+            read_quantized_positions::<UnBuffered, R>(
+                &mut self.file,
+                header.natoms,
+                &mut frame.positions,
+                scratch,
+                atom_selection,
+                header.magic,
+            )
+        })?;
+
+        self.step += 1;
+
+        frame.step = header.step;
+        frame.time = header.time;
+        frame.boxvec = header.boxvec;
+
+        Ok(())
+    }
+
+    /// Reads the positions of the next frame into the caller-owned `positions` according to the
+    /// [`AtomSelection`], and advances one step.
+    ///
+    /// This decouples the storage of the positions from [`Frame`], such that callers can pool
+    /// their position buffers across frames and files. The contents of `positions` are replaced
+    /// by the selected positions, three values per atom. Returns the [`Header`] of the frame,
+    /// which carries its step, time, and box.
+    pub fn read_frame_into(
+        &mut self,
+        positions: &mut Vec<f32>,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<Header> {
+        let header = self.read_header()?;
+        let natoms_selected = atom_selection.natoms_selected(header.natoms);
+        positions.resize(natoms_selected * 3, f32::NAN);
+        self.read_positions_into_slice(&header, positions, atom_selection)?;
+
+        self.step += 1;
+
+        Ok(header)
+    }
+
+    /// Reads the positions of the next frame directly into `coords` according to the
+    /// [`AtomSelection`], and advances one step.
+    ///
+    /// This is an alternative to [`XTCReader::read_frame_with_selection`] for consumers that work
+    /// with [`Vec3`]s, and avoids a separate pass over [`Frame::coords`]. The contents of `coords`
+    /// are replaced by the selected positions. Returns the [`Header`] of the frame, which carries
+    /// its step, time, and box.
+    pub fn read_frame_into_vec3(
+        &mut self,
+        coords: &mut Vec<Vec3>,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<Header> {
+        let header = self.read_header()?;
+        let natoms_selected = atom_selection.natoms_selected(header.natoms);
+        coords.resize(natoms_selected, Vec3::NAN);
+        self.read_positions_into_slice(&header, flatten_vec3s(coords), atom_selection)?;
+
+        self.step += 1;
+
+        Ok(header)
+    }
+
+    /// Reads a [`Frame`] according to the [`AtomSelection`] with its positions and box multiplied
+    /// by `scale`, and advances one step.
+    ///
+    /// This gives the positions directly in another unit, such as Ångström for a `scale` of 10.0,
+    /// without a separate pass over them. The `scale` is folded into the factor by which the
+    /// compressed integer positions are converted to floats. The [`Frame::precision`] remains that
+    /// of the stored positions.
+    pub fn read_frame_scaled(
+        &mut self,
+        frame: &mut Frame,
+        scale: f32,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        let header = self.read_header()?;
+
+        if !header.is_compressed() {
+            self.read_smol_positions(header.natoms, frame, atom_selection)?;
+            frame.positions.iter_mut().for_each(|v| *v *= scale);
+        } else {
+            let natoms_selected = atom_selection.natoms_selected(header.natoms);
+            frame.positions.resize(natoms_selected * 3, f32::NAN);
+            let precision = read_f32(&mut self.file)?;
+            frame.precision = Precision::Compressed(precision);
+            with_scratch(|scratch| {
+                read_compressed_positions_scaled::<UnBuffered, R>(
+                    &mut self.file,
+                    header.natoms,
+                    &mut frame.positions,
+                    precision,
+                    scale,
+                    scratch,
+                    atom_selection,
+                    header.magic,
+                )
+            })?;
+        }
+
+        self.step += 1;
+
+        frame.step = header.step;
+        frame.time = header.time;
+        frame.boxvec = header.boxvec * scale;
+        frame.index = None;
+        frame.offset = None;
+
+        Self::check_finite(self.strict_finite, frame)
+    }
+
+    /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
+    pub fn read_frame_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        with_scratch(|scratch| self.read_frame_with_scratch(frame, scratch, atom_selection))
+    }
+
+    /// Reads and returns a [`Frame`] according to the [`AtomSelection`] and [`SelectionStrategy`],
+    /// and advances one step.
+    ///
+    /// The positions in the compressed stream depend on those before them, so decoding always
+    /// walks every atom up to the last selected one. With [`SelectionStrategy::DuringDecode`], the
+    /// selection is checked for each of these atoms as it is decoded. With
+    /// [`SelectionStrategy::AfterDecode`], the whole frame is decoded and the selected atoms are
+    /// gathered in place afterwards, which keeps the selection out of the decoding loop.
+    ///
+    /// When the selected atoms sit near the start of the frame, decoding during the read leaves
+    /// the remainder undecoded, so it is the better choice there. For selections that reach to the
+    /// end of the frame, the `read_frame_{sparse,dense}_*` benchmarks compare both strategies on
+    /// `adk_oplsaa.xtc` (47681 atoms). Selecting every 300th atom, gathering after the decode took
+    /// 1.2 to 1.5 times as long per frame, since all positions are written out before most are
+    /// discarded. Selecting every other atom, it took 1.0 to 1.4 times as long. In no run was it
+    /// faster, so [`SelectionStrategy::DuringDecode`] is the default, and
+    /// [`SelectionStrategy::AfterDecode`] mainly serves to rule out the selection logic while
+    /// debugging.
+    pub fn read_frame_with_strategy(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+        strategy: SelectionStrategy,
+    ) -> io::Result<()> {
+        match strategy {
+            SelectionStrategy::DuringDecode => {
+                self.read_frame_with_selection(frame, atom_selection)
+            }
+            SelectionStrategy::AfterDecode => {
+                self.read_frame_with_selection(frame, &AtomSelection::All)?;
+                // The selected atoms are in increasing order, so each is moved to an index at or
+                // before its own, and we can gather them in place.
+                let natoms = atom_selection.natoms_selected(frame.natoms());
+                let mut selected = 0;
+                for idx in 0..frame.natoms() {
+                    if selected == natoms {
+                        break;
+                    }
+                    match atom_selection.is_included(idx) {
+                        Some(true) => {}
+                        Some(false) => continue,
+                        None => break,
+                    }
+                    frame
+                        .positions
+                        .copy_within(idx * 3..idx * 3 + 3, selected * 3);
+                    selected += 1;
+                }
+                frame.positions.truncate(selected * 3);
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads and returns a [`Frame`] and advances one step, internally reading the compressed data
+    /// into `scratch`.
     ///
     /// # Note
     ///
@@ -333,9 +2043,19 @@ impl<R: Read> XTCReader<R> {
     ) -> io::Result<()> {
         // Start of by reading the header.
         let header = self.read_header()?;
+        self.read_frame_body::<B>(header, frame, scratch, atom_selection)
+    }
 
+    /// Reads the positions of a frame for which the `header` has just been read.
+    fn read_frame_body<'s, 'r, B: buffer::Buffered<'s, 'r, R>>(
+        &'r mut self,
+        header: Header,
+        frame: &mut Frame,
+        scratch: &'s mut Vec<u8>,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
         // Now, we read the atoms.
-        if header.natoms <= 9 {
+        if !header.is_compressed() {
             self.read_smol_positions(header.natoms, frame, atom_selection)?;
         } else {
             read_positions::<B, R>(
@@ -353,18 +2073,229 @@ impl<R: Read> XTCReader<R> {
         frame.step = header.step;
         frame.time = header.time;
         frame.boxvec = header.boxvec;
+        // Where the frame came from is only known to the callers that read it at an offset.
+        frame.index = None;
+        frame.offset = None;
 
-        Ok(())
+        Self::check_finite(self.strict_finite, frame)
+    }
+}
+
+impl XTCReader<io::BufReader<File>> {
+    /// Open a file as an [`XTCReader`] that reads through a [`io::BufReader`].
+    ///
+    /// Sequential reads of many frames benefit from buffering, since the values in the headers
+    /// are otherwise read from the file one by one. The methods that seek within the file work
+    /// the same as for an unbuffered reader.
+    pub fn open_buffered<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(&path)?;
+        Ok(Self {
+            path: Some(path.as_ref().to_path_buf()),
+            ..Self::new(io::BufReader::new(file))
+        })
+    }
+
+    /// Open the path this reader was opened from anew, and go back to the first frame.
+    ///
+    /// See [`XTCReader::reopen`].
+    ///
+    /// # Errors
+    ///
+    /// If this reader was not opened from a path, an error of kind
+    /// [`io::ErrorKind::Unsupported`] is returned. Errors from opening the file are passed
+    /// through.
+    pub fn reopen(&mut self) -> io::Result<()> {
+        self.reopen_with(io::BufReader::new)
     }
 }
 
-impl XTCReader<File> {
+impl<R: Read + Seek> XTCReader<R> {
     /// Reset the reader to its initial position.
     ///
-    /// Go back to the first frame.
+    /// Go back to the first frame. For a reader opened with [`XTCReader::open_at`], this is the
+    /// offset it was opened at.
     pub fn home(&mut self) -> io::Result<()> {
-        self.file.seek(SeekFrom::Start(0))?;
+        self.file.seek(SeekFrom::Start(self.base))?;
         self.step = 0;
+        self.prev_time = None;
+        self.natoms_mismatches = 0;
+        Ok(())
+    }
+
+    /// Scans the frames in this [`XTCReader<R>`] from its current position, and returns the
+    /// location and header values of each frame.
+    ///
+    /// Only the headers are read. The positions are skipped without being decoded. If `until` is
+    /// provided, at most that many frames are scanned. The position of the reader is left
+    /// unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn scan(&mut self, until: Option<usize>) -> io::Result<Vec<FrameMeta>> {
+        // Remember where we start so we can return to it later.
+        let start_pos = self.file.stream_position()?;
+        // Read the values we need from each frame through a small buffer, such that a frame takes
+        // a single read rather than a read for every value.
+        let mut file = io::BufReader::with_capacity(SCAN_WINDOW, &mut self.file);
+
+        let mut metas: Vec<FrameMeta> = Vec::new();
+        let mut offset = start_pos;
+        while until.map_or(true, |until| metas.len() < until) {
+            let Some(mut header) = Header::read_optional_with(&mut file, self.lenient)? else {
+                break;
+            };
+            if let Some(remap) = &self.remap {
+                remap.apply(self.step + metas.len(), &mut header);
+            }
+            if let (true, Some(prev)) = (self.strict_time, metas.last()) {
+                if header.time < prev.time {
+                    file.seek(SeekFrom::Start(start_pos))?;
+                    return Err(NonMonotonicTime {
+                        frame: self.step + metas.len(),
+                        prev: prev.time,
+                        cur: header.time,
+                    }
+                    .into());
+                }
+            }
+
+            // The number of bytes after the header that we read, and the number we skip.
+            let (read, skip) = if !header.is_compressed() {
+                // Know how many bytes are in this frame until the next header since the positions
+                // are uncompressed.
+                (0, header.natoms as u64 * 3 * 4)
+            } else {
+                // We need to read the nbytes value to get the offset until the next header.
+                file.seek_relative(32)?;
+                // The size of the buffer is stored either as a 64 or 32-bit integer, depending on
+                // the magic number in the header.
+                let nbytes = read_nbytes(&mut file, header.magic)? as u64;
+                let width = header.magic.nbytes_width() as u64;
+                (32 + width, nbytes + padding(nbytes as usize) as u64)
+            };
+            file.seek_relative(skip as i64)?;
+            // Keep track of the position ourselves, since asking the file for it costs a syscall.
+            let end = offset + Header::SIZE as u64 + read + skip;
+            metas.push(FrameMeta {
+                offset,
+                size: end - offset,
+                step: header.step,
+                time: header.time,
+                natoms: header.natoms,
+                boxvec: header.boxvec,
+            });
+            offset = end;
+        }
+
+        // Return back to where we started.
+        file.seek(SeekFrom::Start(start_pos))?;
+
+        Ok(metas)
+    }
+
+    /// Returns an iterator over the [`Header`]s of the frames in this [`XTCReader<R>`] from its
+    /// current position.
+    ///
+    /// This is the lazy counterpart to [`XTCReader::scan`]. Each header is read as the iterator
+    /// advances, and the positions after it are skipped without being decoded. Only one header is
+    /// held at a time, so the metadata of a trajectory of any length can be processed in constant
+    /// memory. The reader advances one step for each header, like it does for each frame that is
+    /// read, and is left after the last header that was yielded.
+    ///
+    /// Every header takes a few small reads. For an unbuffered file, that means a few system calls
+    /// per frame, which [`XTCReader::open_buffered`] avoids.
+    ///
+    /// # Errors
+    ///
+    /// Any reader errors are yielded by the iterator, after which it stops.
+    pub fn headers(&mut self) -> impl Iterator<Item = io::Result<Header>> + '_ {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let result = self.read_header_optional().and_then(|header| {
+                let Some(header) = header else {
+                    return Ok(None);
+                };
+                self.skip_positions(&header)?;
+                self.step += 1;
+                Ok(Some(header))
+            });
+            done = !matches!(result, Ok(Some(_)));
+            result.transpose()
+        })
+    }
+
+    /// Reads the metadata of the next frame without decoding its positions, and advances one step.
+    ///
+    /// Besides the header values that [`XTCReader::scan`] collects, this reads the precision and
+    /// the extent of the integer positions that precede the compressed positions. The compressed
+    /// positions themselves are skipped, which makes this far cheaper than reading the frame.
+    ///
+    /// # Errors
+    ///
+    /// If the frame is incomplete or its precision is invalid, an error is returned. For a frame
+    /// that extends beyond the end of the reader, that error is of the kind
+    /// [`io::ErrorKind::UnexpectedEof`].
+    pub fn read_frame_meta(&mut self) -> io::Result<FrameSummary> {
+        let offset = self.file.stream_position()?;
+        let header = self.read_header()?;
+        let (precision, extent, size) = if !header.is_compressed() {
+            let skip = header.natoms as u64 * 3 * 4;
+            self.skip_exact(skip)?;
+            (Precision::Uncompressed, None, skip)
+        } else {
+            let precision = read_f32(&mut self.file)?;
+            validate_precision(precision)?;
+            let Prelude { minint, maxint, .. } = Prelude::read(&mut self.file)?;
+            let nbytes = read_nbytes(&mut self.file, header.magic)?;
+            let skip = (nbytes + padding(nbytes)) as u64;
+            self.skip_exact(skip)?;
+            let width = header.magic.nbytes_width() as u64;
+            let extent = Some((minint, maxint));
+            (Precision::Compressed(precision), extent, 32 + width + skip)
+        };
+        self.step += 1;
+
+        Ok(FrameSummary {
+            meta: FrameMeta {
+                offset,
+                size: Header::SIZE as u64 + size,
+                step: header.step,
+                time: header.time,
+                natoms: header.natoms,
+                boxvec: header.boxvec,
+            },
+            precision,
+            extent,
+        })
+    }
+
+    /// Skips over `n` bytes, and makes sure that they are present in the reader.
+    ///
+    /// A seek beyond the end of a file succeeds, so the last of the skipped bytes is read to
+    /// detect a reader that ends early.
+    fn skip_exact(&mut self, n: u64) -> io::Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        self.file.seek(SeekFrom::Current(n as i64 - 1))?;
+        self.file.read_exact(&mut [0])
+    }
+
+    /// Skips over the positions of a frame for which the `header` has just been read.
+    fn skip_positions(&mut self, header: &Header) -> io::Result<()> {
+        let skip = if !header.is_compressed() {
+            header.natoms as u64 * 3 * 4
+        } else {
+            // Skip the precision and the prelude to get to the number of compressed bytes.
+            self.file.seek(SeekFrom::Current(32))?;
+            let nbytes = read_nbytes(&mut self.file, header.magic)?;
+            (nbytes + padding(nbytes)) as u64
+        };
+        self.file.seek(SeekFrom::Current(skip as i64))?;
         Ok(())
     }
 
@@ -376,48 +2307,78 @@ impl XTCReader<File> {
     /// frame offsets _from_ its position are determined. If you wish to determine the offsets from
     /// the initial reader position, call [`XTCReader::home`] before calling this function.
     ///
+    /// When determined from the start of the file, the offsets of all frames are cached for later
+    /// calls. See [`XTCReader::invalidate_offsets`].
+    ///
     /// # Errors
     ///
     /// This function will pass through any reader errors.
     pub fn determine_offsets_exclusive(&mut self, until: Option<usize>) -> io::Result<Box<[u64]>> {
-        let file = &mut self.file;
-        // Remember where we start so we can return to it later.
-        let start_pos = file.stream_position()?;
-
-        let mut offsets = Vec::new();
-
-        while until.map_or(true, |until| offsets.len() < until) {
-            let header = match Header::read(file) {
-                Ok(header) => header,
-                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
-                Err(err) => Err(err)?,
-            };
+        // The offsets of the whole file are cached, so we can only use them from the start.
+        let from_start = self.file.stream_position()? == self.base;
+        if let (true, Some(offsets)) = (from_start, &self.offsets) {
+            let n = until.map_or(offsets.len(), |until| usize::min(until, offsets.len()));
+            return Ok(offsets[..n].into());
+        }
 
-            let skip = if header.natoms <= 9 {
-                // Know how many bytes are in this frame until the next header since the positions
-                // are uncompressed.
-                header.natoms as u64 * 3 * 4
-            } else {
-                // We need to read the nbytes value to get the offset until the next header.
-                file.seek(SeekFrom::Current(32))?;
-                // The size of the buffer is stored either as a 64 or 32-bit integer, depending on
-                // the magic number in the header.
-                let nbytes = read_nbytes(file, header.magic)? as u64;
-                nbytes + padding(nbytes as usize) as u64
-            };
-            let offset = file.seek(SeekFrom::Current(skip as i64))?;
-            offsets.push(offset);
+        let metas = self.scan(until)?;
+        let offsets: Box<[u64]> = metas.iter().map(|meta| meta.offset + meta.size).collect();
+        if from_start && until.is_none() {
+            self.offsets = Some(offsets.clone());
         }
+        Ok(offsets)
+    }
 
-        // Return back to where we started.
-        file.seek(SeekFrom::Start(start_pos))?;
+    /// Determine the offsets of all frames from the first frame, and check that the last frame
+    /// ends within the reader.
+    ///
+    /// This trades a scan of all headers up front for early error reporting, which suits tools
+    /// that will access the frames at random. The offsets are cached, such that subsequent reads
+    /// of frame selections need no further scan. See [`XTCReader::determine_offsets_exclusive`].
+    /// Options that affect reading the headers, such as [`XTCReader::set_lenient`], should be set
+    /// before calling this function. The position of the reader is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Any invalid header results in an error. If the last frame extends beyond the end of the
+    /// reader, an error of the kind [`io::ErrorKind::UnexpectedEof`] is returned.
+    pub fn index(&mut self) -> io::Result<()> {
+        let pos = self.file.stream_position()?;
+        self.file.seek(SeekFrom::Start(self.base))?;
+        let offsets = self.determine_offsets_exclusive(None);
+        let len = self.file.seek(SeekFrom::End(0));
+        self.file.seek(SeekFrom::Start(pos))?;
+        let (offsets, len) = (offsets?, len?);
+        if let Some(&end) = offsets.last() {
+            if end > len {
+                self.offsets = None;
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "the last of {} frames ends at byte {end}, beyond the end of the reader \
+                        at byte {len}",
+                        offsets.len()
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
 
-        Ok(offsets.into_boxed_slice())
+    /// Forget the cached frame offsets.
+    ///
+    /// The offsets of all frames in the file are cached once they have been determined from the
+    /// start of the file, such that subsequent reads of frame selections do not need to scan the
+    /// file again. If the file may have changed since, for instance because it is still being
+    /// written to, call this function to have the offsets determined anew.
+    pub fn invalidate_offsets(&mut self) {
+        self.offsets = None;
     }
 
     /// Returns the offsets of this [`XTCReader<R>`] from its current position.
     ///
-    /// The last value points to the start of the last frame.
+    /// The first value is the current position of the reader, and the last value points to the
+    /// start of the last frame. If there are no frames, the offsets are empty.
     ///
     /// If this function is called when the internal reader is not at its starting position, the
     /// frame offsets _from_ its position are determined. If you wish to determine the offsets from
@@ -427,30 +2388,167 @@ impl XTCReader<File> {
     ///
     /// This function will pass through any reader errors.
     pub fn determine_offsets(&mut self, until: Option<usize>) -> io::Result<Box<[u64]>> {
-        let mut offsets = vec![0];
+        let start = self.file.stream_position()?;
         let exclusive = self.determine_offsets_exclusive(until)?;
-        offsets.extend(exclusive.iter().take(exclusive.len().saturating_sub(1)));
+        let Some((_, starts)) = exclusive.split_last() else {
+            return Ok(Box::default());
+        };
+        let mut offsets = Vec::with_capacity(exclusive.len());
+        offsets.push(start);
+        offsets.extend(starts);
         Ok(offsets.into_boxed_slice())
     }
 
-    /// Returns the frame sizes of this [`XTCReader<R>`].
+    /// Returns the number of frames in this [`XTCReader<R>`] from its current position.
+    ///
+    /// Only the frame headers are read to determine the count. The position of the reader is
+    /// left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn frame_count(&mut self) -> io::Result<usize> {
+        Ok(self.determine_offsets_exclusive(None)?.len())
+    }
+
+    /// Returns whether all frames in this [`XTCReader<R>`] from its current position have the
+    /// same number of atoms.
+    ///
+    /// Only the frame headers are read, and the position of the reader is left unchanged. A
+    /// trajectory without any frames is considered uniform.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn has_uniform_natoms(&mut self) -> io::Result<bool> {
+        let metas = self.scan(None)?;
+        Ok(metas
+            .windows(2)
+            .all(|pair| pair[0].natoms == pair[1].natoms))
+    }
+
+    /// Returns the offset of the first frame and the size of each frame, if the frames of this
+    /// [`XTCReader<R>`] from its current position appear to all be of the same size.
+    ///
+    /// Only the headers of the first few frames are read, rather than the headers of all frames.
+    /// If these frames share a size and the remainder of the file is a whole number of frames of
+    /// that size, the offset of frame `i` is `base + i * stride` for the returned `(base, stride)`.
+    /// This allows the offsets of a long trajectory to be determined without scanning it.
+    ///
+    /// Frames of 9 atoms or less store their positions uncompressed, so they have a fixed size.
+    /// The compressed positions of larger frames usually vary in size from frame to frame. Note
+    /// that a change in size after the inspected frames that happens to leave the file a whole
+    /// number of strides long cannot be detected. When in doubt, use
+    /// [`XTCReader::determine_offsets`], which reads every header.
+    ///
+    /// If there are no frames, or the frames differ in size, [`None`] is returned. The position of
+    /// the reader is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn offsets_uniform(&mut self) -> io::Result<Option<(u64, u64)>> {
+        let metas = self.scan(Some(UNIFORM_PROBE))?;
+        let Some(first) = metas.first() else {
+            return Ok(None);
+        };
+        let (base, stride) = (first.offset, first.size);
+        if metas.iter().any(|meta| meta.size != stride) {
+            return Ok(None);
+        }
+
+        let end = self.file.seek(SeekFrom::End(0))?;
+        self.file.seek(SeekFrom::Start(base))?;
+        if (end - base) % stride != 0 {
+            return Ok(None);
+        }
+        Ok(Some((base, stride)))
+    }
+
+    /// Returns the frame sizes of this [`XTCReader<R>`] from its current position.
+    ///
+    /// The size of each frame is the difference between its entries in
+    /// [`XTCReader::determine_offsets_exclusive`] and [`XTCReader::determine_offsets`].
     ///
     /// # Errors
     ///
     /// This function will pass through any reader errors.
     pub fn determine_frame_sizes(&mut self, until: Option<usize>) -> io::Result<Box<[u64]>> {
-        let starts = self.determine_offsets_exclusive(until)?;
-        let ends = starts.iter().clone().skip(1);
+        let starts = self.determine_offsets(until)?;
+        let ends = self.determine_offsets_exclusive(until)?;
         Ok(starts
             .iter()
-            .zip(ends)
+            .zip(ends.iter())
             .map(|(s, e)| e - s)
             .collect::<Vec<_>>()
             .into_boxed_slice())
     }
 
+    /// Returns the bytes of `count` frames, starting at the frame at index `start`, verbatim.
+    ///
+    /// The bytes include the headers and the padded compressed positions of the frames, so they
+    /// form a valid xtc trajectory by themselves. This is the primitive for lossless slicing and
+    /// concatenation, since no positions are decoded or encoded again. Like
+    /// [`XTCReader::determine_offsets`], the frames are counted from the current position of the
+    /// reader, and the position is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// If the trajectory holds fewer than `start + count` frames, an error of kind
+    /// [`io::ErrorKind::UnexpectedEof`] is returned. Any reader errors are passed through.
+    pub fn read_raw_frames(&mut self, start: usize, count: usize) -> io::Result<Vec<u8>> {
+        let pos = self.file.stream_position()?;
+        let until = start.checked_add(count).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the range of {count} frames from frame {start} overflows"),
+            )
+        })?;
+        let ends = self.determine_offsets_exclusive(Some(until))?;
+        if ends.len() < until {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "cannot read frames {start} to {until} (exclusive), since the trajectory \
+                    holds only {} frames",
+                    ends.len()
+                ),
+            ));
+        }
+
+        let from = match start {
+            0 => pos,
+            _ => ends[start - 1],
+        };
+        let to = match count {
+            0 => from,
+            _ => ends[until - 1],
+        };
+        // The offsets follow from the byte counts in the frames, which are not checked against
+        // the length of the stream. So, the buffer only grows with the bytes that are read.
+        let len = to - from;
+        let mut bytes = Vec::new();
+        self.file.seek(SeekFrom::Start(from))?;
+        let read = (&mut self.file).take(len).read_to_end(&mut bytes)?;
+        self.file.seek(SeekFrom::Start(pos))?;
+        if (read as u64) < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "frames {start} to {until} (exclusive) span {len} bytes, but only {read} bytes \
+                    could be read"
+                ),
+            ));
+        }
+        Ok(bytes)
+    }
+
     /// Seeks to offset, then reads and returns a [`Frame`] and advances one step.
     ///
+    /// The frame is taken to be at the current [`XTCReader::step`], which is the index that is
+    /// passed to the [`StepRemap`] set through [`XTCReader::set_step_remap`]. When the mapping
+    /// depends on the index, set the step to the index of the frame at `offset` beforehand.
+    ///
     /// # Note
     ///
     /// The `BUFFERED` const generic value can be used to set whether the frame reader will read in
@@ -466,10 +2564,35 @@ impl XTCReader<File> {
         atom_selection: &AtomSelection,
     ) -> io::Result<()> {
         self.file.seek(SeekFrom::Start(offset))?;
+        // The frame at the offset need not follow the one read before it.
+        self.prev_time = None;
         match BUFFERED {
-            false => self.read_frame_with_selection(frame, atom_selection),
-            true => self.read_frame_with_selection_buffered(frame, atom_selection),
+            false => self.read_frame_with_selection(frame, atom_selection)?,
+            true => self.read_frame_with_selection_buffered(frame, atom_selection)?,
         }
+        frame.offset = Some(offset);
+        Ok(())
+    }
+
+    /// Reads a [`Frame`] at an offset like [`XTCReader::read_frame_at_offset`], but leaves the
+    /// reader where it was.
+    ///
+    /// The position, the step, and the time that [`XTCReader::set_strict_time`] compares against
+    /// are restored after the read, also if it fails. This allows random reads to be interleaved
+    /// with sequential reads through [`XTCReader::read_frame`].
+    pub fn peek_frame_at_offset<const BUFFERED: bool>(
+        &mut self,
+        frame: &mut Frame,
+        offset: u64,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        let pos = self.file.stream_position()?;
+        let (step, prev_time) = (self.step, self.prev_time);
+        let result = self.read_frame_at_offset::<BUFFERED>(frame, offset, atom_selection);
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.step = step;
+        self.prev_time = prev_time;
+        result
     }
 
     /// Append [`Frame`]s to the `frames` buffer according to a [`Selection`].
@@ -492,7 +2615,19 @@ impl XTCReader<File> {
         frame_selection: &FrameSelection,
         atom_selection: &AtomSelection,
     ) -> io::Result<usize> {
+        let base = self.step;
         let offsets = self.determine_offsets(frame_selection.until())?;
+        // The frames in a list are visited directly, rather than checking every offset up to the
+        // last frame in the list.
+        if let FrameSelection::FrameList(list) = frame_selection {
+            let listed = list.range(..offsets.len());
+            for &idx in listed.clone() {
+                self.step = base + idx;
+                self.read_frame_at_index::<BUFFERED>(frames, idx, offsets[idx], atom_selection)?;
+            }
+            return Ok(listed.count());
+        }
+
         let mut n = 0;
         for (idx, &offset) in offsets.iter().enumerate() {
             match frame_selection.is_included(idx) {
@@ -500,24 +2635,593 @@ impl XTCReader<File> {
                 Some(false) => continue,
                 None => break,
             }
-            let mut frame = Frame::default();
-            self.read_frame_at_offset::<BUFFERED>(&mut frame, offset, atom_selection)?;
-            frames.extend(Some(frame));
+            self.step = base + idx;
+            self.read_frame_at_index::<BUFFERED>(frames, idx, offset, atom_selection)?;
             n += 1;
         }
 
         Ok(n)
     }
 
+    /// Reads the frame at index `idx`, which starts at `offset`, and appends it to `frames`.
+    fn read_frame_at_index<const BUFFERED: bool>(
+        &mut self,
+        frames: &mut impl Extend<Frame>,
+        idx: usize,
+        offset: u64,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        let mut frame = Frame::default();
+        self.read_frame_at_offset::<BUFFERED>(&mut frame, offset, atom_selection)?;
+        frame.index = Some(idx);
+        frames.extend(Some(frame));
+        Ok(())
+    }
+
+    /// Reads the positions of the frames in a [`FrameSelection`] into a single contiguous buffer,
+    /// according to the [`AtomSelection`].
+    ///
+    /// Returns the buffer along with its shape `[nframes, natoms, 3]`, in row-major order. The
+    /// positions of each frame are decoded directly into their place in the buffer, without
+    /// allocating a [`Frame`] for each of them. Like [`XTCReader::read_frames`], the frames are
+    /// determined from the current position of the reader.
+    ///
+    /// # Errors
+    ///
+    /// If the selected frames do not all have the same number of selected atoms, they cannot be
+    /// stacked, and an error of the kind [`io::ErrorKind::InvalidData`] is returned. Any reader
+    /// errors are passed through.
+    pub fn read_contiguous(
+        &mut self,
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<(Vec<f32>, [usize; 3])> {
+        let base = self.step;
+        let selected = self.scan_selected(frame_selection)?;
+
+        let natoms = selected
+            .first()
+            .map_or(0, |(_, meta)| atom_selection.natoms_selected(meta.natoms));
+        for (idx, meta) in &selected {
+            let n = atom_selection.natoms_selected(meta.natoms);
+            if n != natoms {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "cannot stack frames with different numbers of atoms: frame {idx} has \
+                        {n} selected atoms, while the first selected frame has {natoms}"
+                    ),
+                ));
+            }
+        }
+
+        let shape = [selected.len(), natoms, 3];
+        let stride = natoms * 3;
+        let mut positions = vec![f32::NAN; shape.iter().product()];
+        for (i, (idx, meta)) in selected.iter().enumerate() {
+            self.file.seek(SeekFrom::Start(meta.offset))?;
+            self.step = base + idx;
+            let header = self.read_header()?;
+            let frame_positions = &mut positions[i * stride..(i + 1) * stride];
+            self.read_positions_into_slice(&header, frame_positions, atom_selection)?;
+            self.step += 1;
+        }
+
+        Ok((positions, shape))
+    }
+
+    /// Reads the frames in a [`FrameSelection`] that all share the same box, according to the
+    /// [`AtomSelection`].
+    ///
+    /// In a trajectory with a constant box, as in an NVT simulation, storing the box with every
+    /// frame is pure overhead. Here, the box is returned once, along with the step, time, and
+    /// positions of each frame. Like [`XTCReader::read_frames`], the frames are determined from the
+    /// current position of the reader. The boxes are compared through the frame headers before
+    /// any positions are decoded.
+    ///
+    /// If no frames are selected, the returned box is [`BoxVec::ZERO`].
+    ///
+    /// # Errors
+    ///
+    /// If the box of any selected frame differs from that of the first selected frame, an error
+    /// of the kind [`io::ErrorKind::InvalidData`] is returned. Any reader errors are passed
+    /// through.
+    pub fn read_frames_shared_box(
+        &mut self,
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<SharedBoxFrames> {
+        let base = self.step;
+        let selected = self.scan_selected(frame_selection)?;
+
+        let boxvec = selected
+            .first()
+            .map_or(BoxVec::ZERO, |(_, meta)| meta.boxvec);
+        if let Some((idx, _)) = selected.iter().find(|(_, meta)| meta.boxvec != boxvec) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the box of frame {idx} differs from that of the first selected frame"),
+            ));
+        }
+
+        let mut frames = SharedBoxFrames {
+            boxvec,
+            steps: Vec::with_capacity(selected.len()),
+            times: Vec::with_capacity(selected.len()),
+            positions: Vec::with_capacity(selected.len()),
+        };
+        for (idx, meta) in &selected {
+            self.file.seek(SeekFrom::Start(meta.offset))?;
+            self.step = base + idx;
+            let header = self.read_header()?;
+            let mut positions = vec![f32::NAN; atom_selection.natoms_selected(header.natoms) * 3];
+            self.read_positions_into_slice(&header, &mut positions, atom_selection)?;
+            frames.steps.push(header.step);
+            frames.times.push(header.time);
+            frames.positions.push(positions);
+            self.step += 1;
+        }
+
+        Ok(frames)
+    }
+
+    /// Scans the frames from the current position, and returns the index and location of each
+    /// frame that is included in the [`FrameSelection`].
+    fn scan_selected(
+        &mut self,
+        frame_selection: &FrameSelection,
+    ) -> io::Result<Vec<(usize, FrameMeta)>> {
+        let metas = self.scan(frame_selection.until())?;
+        Ok(metas
+            .into_iter()
+            .enumerate()
+            .map_while(|(idx, meta)| {
+                frame_selection
+                    .is_included(idx)
+                    .map(|included| (idx, meta, included))
+            })
+            .filter(|&(_, _, included)| included)
+            .map(|(idx, meta, _)| (idx, meta))
+            .collect())
+    }
+
+    /// Returns an iterator over the [`Frame`]s in a [`FrameSelection`], each paired with its
+    /// original index in the trajectory.
+    ///
+    /// Frames are read lazily, according to the [`AtomSelection`]. Like [`XTCReader::read_frames`],
+    /// the frame offsets are determined from the current position of the reader.
+    ///
+    /// # Errors
+    ///
+    /// Any reader errors are yielded by the iterator. If the frame offsets cannot be determined,
+    /// the iterator yields that error and stops.
+    pub fn selected_frames<'a>(
+        &'a mut self,
+        frame_selection: &'a FrameSelection,
+        atom_selection: &'a AtomSelection,
+    ) -> impl Iterator<Item = io::Result<(usize, Frame)>> + 'a {
+        let base = self.step;
+        let (offsets, err) = match self.determine_offsets(frame_selection.until()) {
+            Ok(offsets) => (offsets, None),
+            Err(err) => (Box::default(), Some(err)),
+        };
+        let frames = offsets
+            .into_vec()
+            .into_iter()
+            .enumerate()
+            .map_while(|(idx, offset)| {
+                frame_selection
+                    .is_included(idx)
+                    .map(|included| (idx, offset, included))
+            })
+            .filter(|&(_, _, included)| included)
+            .map(move |(idx, offset, _)| {
+                let mut frame = Frame::default();
+                self.step = base + idx;
+                self.read_frame_at_offset::<false>(&mut frame, offset, atom_selection)?;
+                frame.index = Some(idx);
+                Ok((idx, frame))
+            });
+        err.map(Err).into_iter().chain(frames)
+    }
+
+    /// Estimates the number of bytes the positions of the selected frames will take up once they
+    /// are decoded.
+    ///
+    /// Only the headers are scanned, from the current position of the reader. This can be used to
+    /// decide whether reading all selected frames at once will fit in memory, before doing so.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn estimate_decoded_size(
+        &mut self,
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<u64> {
+        let metas = self.scan(frame_selection.until())?;
+        let mut size = 0;
+        for (idx, meta) in metas.iter().enumerate() {
+            match frame_selection.is_included(idx) {
+                Some(true) => {}
+                Some(false) => continue,
+                None => break,
+            }
+            let natoms = atom_selection.natoms_selected(meta.natoms) as u64;
+            size += natoms * 3 * std::mem::size_of::<f32>() as u64;
+        }
+        Ok(size)
+    }
+
+    /// Reads the [`Frame`]s in a [`FrameSelection`] all at once if their positions fit within
+    /// `max_bytes`, and returns an iterator that reads them one by one otherwise.
+    ///
+    /// The size of the positions is determined up front with
+    /// [`XTCReader::estimate_decoded_size`], which only reads the headers. This gives a single
+    /// entry point that is fast for small reads, but does not run out of memory on a trajectory
+    /// that turns out to be larger than expected. Like [`XTCReader::read_frames`], the frames are
+    /// determined from the current position of the reader, and their
+    /// [`index`](Frame::index) is set.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. When streaming, any reader errors are
+    /// yielded by the iterator. See [`XTCReader::selected_frames`].
+    pub fn read_budgeted<'a>(
+        &'a mut self,
+        frame_selection: &'a FrameSelection,
+        atom_selection: &'a AtomSelection,
+        max_bytes: u64,
+    ) -> io::Result<Budgeted<impl Iterator<Item = io::Result<Frame>> + 'a>> {
+        let size = self.estimate_decoded_size(frame_selection, atom_selection)?;
+        let frames = self
+            .selected_frames(frame_selection, atom_selection)
+            .map(|frame| frame.map(|(_, frame)| frame));
+        if size > max_bytes {
+            return Ok(Budgeted::Streaming(frames));
+        }
+        Ok(Budgeted::Materialized(frames.collect::<io::Result<_>>()?))
+    }
+
+    /// Returns the times of the first and last frames in this [`XTCReader<R>`] from its current
+    /// position.
+    ///
+    /// Only those two headers are read. The location of the last frame is taken from the frame
+    /// offsets, which are cached after they have been determined once (see
+    /// [`XTCReader::determine_offsets_exclusive`]). The position and step of the reader are left
+    /// unchanged, and the headers are not subject to [`XTCReader::set_strict_time`].
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. If there are no frames, an error of the
+    /// kind [`io::ErrorKind::UnexpectedEof`] is returned.
+    pub fn time_bounds(&mut self) -> io::Result<(f32, f32)> {
+        let start = self.file.stream_position()?;
+        let offsets = self.determine_offsets(None)?;
+        let Some(&last) = offsets.last() else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the trajectory contains no frames",
+            ));
+        };
+
+        // These headers are not part of the sequence of frames that the strict time check follows.
+        let mut first = Header::read_with(&mut self.file, self.lenient)?;
+        if let Some(remap) = &self.remap {
+            remap.apply(self.step, &mut first);
+        }
+        self.file.seek(SeekFrom::Start(last))?;
+        let mut last = Header::read_with(&mut self.file, self.lenient)?;
+        if let Some(remap) = &self.remap {
+            remap.apply(self.step + offsets.len() - 1, &mut last);
+        }
+        self.file.seek(SeekFrom::Start(start))?;
+
+        Ok((first.time, last.time))
+    }
+
+    /// Positions the reader at the frame with the requested `time`, and returns the index of that
+    /// frame.
+    ///
+    /// If no frame has exactly that time, the frame is picked according to the [`Rounding`]. The
+    /// reader is first reset to its initial position, and only the headers are read to find the
+    /// frame. The next read will return the picked frame.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. If the `time` is not a number or lies
+    /// outside the times of the first and last frames, an error of the kind
+    /// [`io::ErrorKind::InvalidInput`] is returned. If the times of the frames are not strictly
+    /// increasing, which is the case for corrupt or concatenated trajectories, an error of the kind
+    /// [`io::ErrorKind::InvalidData`] is returned.
+    pub fn seek_to_time(&mut self, time: f32, rounding: Rounding) -> io::Result<usize> {
+        self.home()?;
+        let metas = self.scan(None)?;
+        if let Some(idx) = metas.windows(2).position(|w| w[0].time >= w[1].time) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "frame times are not strictly increasing: frame {} has time {} ps and frame {} has time {} ps",
+                    idx,
+                    metas[idx].time,
+                    idx + 1,
+                    metas[idx + 1].time
+                ),
+            ));
+        }
+        let (first, last) = match (metas.first(), metas.last()) {
+            (Some(first), Some(last)) => (first.time, last.time),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot seek to time {time} ps in a trajectory without frames"),
+                ))
+            }
+        };
+        if !(first..=last).contains(&time) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("time {time} ps lies outside of the trajectory ({first} to {last} ps)"),
+            ));
+        }
+
+        // The first frame at or after the requested time. It exists, since time <= last.
+        let ceil = metas.partition_point(|meta| meta.time < time);
+        let idx = if metas[ceil].time == time {
+            ceil
+        } else {
+            // Since time > first, there is a frame before the ceiling.
+            let floor = ceil - 1;
+            match rounding {
+                Rounding::Floor => floor,
+                Rounding::Ceil => ceil,
+                Rounding::Nearest => {
+                    if time - metas[floor].time <= metas[ceil].time - time {
+                        floor
+                    } else {
+                        ceil
+                    }
+                }
+            }
+        };
+
+        self.file.seek(SeekFrom::Start(metas[idx].offset))?;
+        self.step = idx;
+        self.prev_time = None;
+        Ok(idx)
+    }
+
+    /// Returns an iterator over the [`Frame`]s from the current position of the reader, in
+    /// reverse order.
+    ///
+    /// The frame offsets are determined up front from the frame headers, after which the frames
+    /// are read lazily from the last to the first. Only one frame is decoded at a time.
+    ///
+    /// # Errors
+    ///
+    /// Any reader errors are yielded by the iterator. If the frame offsets cannot be determined,
+    /// the iterator yields that error and stops.
+    pub fn frames_rev(&mut self) -> impl Iterator<Item = io::Result<Frame>> + '_ {
+        let (offsets, err) = match self.determine_offsets(None) {
+            Ok(offsets) => (offsets, None),
+            Err(err) => (Box::default(), Some(err)),
+        };
+        let base = self.step;
+        let frames = offsets
+            .into_vec()
+            .into_iter()
+            .enumerate()
+            .rev()
+            .map(move |(idx, offset)| {
+                let mut frame = Frame::default();
+                self.step = base + idx;
+                self.read_frame_at_offset::<false>(&mut frame, offset, &AtomSelection::All)?;
+                frame.index = Some(idx);
+                Ok(frame)
+            });
+        err.map(Err).into_iter().chain(frames)
+    }
+
+    /// Reads the last [`Frame`] of the trajectory according to the [`AtomSelection`].
+    ///
+    /// The reader is first reset to its initial position. To find the last frame, only the
+    /// headers of the preceding frames are read, which is much cheaper than decoding all frames.
+    /// Afterwards, the reader is positioned at the end of the trajectory.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. If the trajectory contains no frames,
+    /// this results in an error of the kind [`io::ErrorKind::UnexpectedEof`].
+    pub fn read_last_frame(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        self.home()?;
+        let offsets = self.determine_offsets(None)?;
+        let &last = offsets.last().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the trajectory contains no frames",
+            )
+        })?;
+        self.step = offsets.len() - 1;
+        self.read_frame_at_offset::<false>(frame, last, atom_selection)?;
+        frame.index = Some(offsets.len() - 1);
+        Ok(())
+    }
+
     /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
     pub fn read_frame_with_selection_buffered(
         &mut self,
         frame: &mut Frame,
         atom_selection: &AtomSelection,
     ) -> io::Result<()> {
-        // Take the thread-local SCRATCH and use that while decoding the values.
-        let mut scratch = SCRATCH.take();
-        self.read_frame_with_scratch_buffered(frame, &mut scratch, atom_selection)
+        with_scratch(|scratch| {
+            self.read_frame_with_scratch_buffered(frame, scratch, atom_selection)
+        })
+    }
+
+    /// Reads a [`Frame`] according to the [`AtomSelection`] in a buffered manner, advances one
+    /// step, and returns the [`BufferStats`] of the read.
+    ///
+    /// The stats tell how much of the compressed data had to be read to decode the selected
+    /// positions, which helps to decide between buffered and unbuffered reading for a selection.
+    /// See [`BufferStats`]. For a frame of 9 atoms or less, the positions are not compressed, and
+    /// [`None`] is returned.
+    pub fn read_frame_with_stats(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<Option<BufferStats>> {
+        let header = self.read_header()?;
+
+        let stats = if !header.is_compressed() {
+            self.read_smol_positions(header.natoms, frame, atom_selection)?;
+            None
+        } else {
+            let natoms_selected = atom_selection.natoms_selected(header.natoms);
+            frame.positions.resize(natoms_selected * 3, f32::NAN);
+            let precision = read_f32(&mut self.file)?;
+            frame.precision = Precision::Compressed(precision);
+            let stats = with_scratch(|scratch| {
+                decode_compressed_positions::<Buffer<R>, R>(
+                    &mut self.file,
+                    header.natoms,
+                    &mut frame.positions,
+                    precision,
+                    1.0,
+                    scratch,
+                    atom_selection,
+                    header.magic,
+                )
+            })?;
+            Some(stats)
+        };
+
+        self.step += 1;
+
+        frame.step = header.step;
+        frame.time = header.time;
+        frame.boxvec = header.boxvec;
+        frame.index = None;
+        frame.offset = None;
+
+        Self::check_finite(self.strict_finite, frame)?;
+        Ok(stats)
+    }
+
+    /// Reads a [`Frame`] according to the [`AtomSelection`], advances one step, and returns the
+    /// time spent reading and decoding it.
+    ///
+    /// To separate the two, all compressed bytes of the frame are read into memory before they
+    /// are decoded, like an unbuffered read. For a frame of 9 atoms or less, the positions are not
+    /// compressed, and all time is spent reading.
+    #[cfg(feature = "profile")]
+    pub fn read_frame_with_timing(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<FrameTiming> {
+        let start = std::time::Instant::now();
+        let header = self.read_header()?;
+
+        let timing = if !header.is_compressed() {
+            self.read_smol_positions(header.natoms, frame, atom_selection)?;
+            FrameTiming {
+                read: start.elapsed(),
+                decode: std::time::Duration::ZERO,
+            }
+        } else {
+            let precision = read_f32(&mut self.file)?;
+            let width = header.magic.nbytes_width();
+            let mut bytes = vec![0; reader::NBYTES_POSITIONS_PRELUDE + width];
+            self.file.read_exact(&mut bytes)?;
+            let nbytes = read_nbytes(
+                &mut &bytes[reader::NBYTES_POSITIONS_PRELUDE..],
+                header.magic,
+            )?;
+            // The byte count is not trusted for allocation, so the buffer only grows with the bytes
+            // that are actually present in the reader.
+            let len = nbytes.saturating_add(padding(nbytes));
+            let read = (&mut self.file).take(len as u64).read_to_end(&mut bytes)?;
+            if read < len {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "the compressed positions span {len} bytes, but only {read} bytes could \
+                        be read"
+                    ),
+                ));
+            }
+            let read = start.elapsed();
+
+            let start = std::time::Instant::now();
+            let natoms_selected = atom_selection.natoms_selected(header.natoms);
+            frame.positions.resize(natoms_selected * 3, f32::NAN);
+            frame.precision = Precision::Compressed(precision);
+            with_scratch(|scratch| {
+                decode_compressed_positions::<UnBuffered, &[u8]>(
+                    &mut bytes.as_slice(),
+                    header.natoms,
+                    &mut frame.positions,
+                    precision,
+                    1.0,
+                    scratch,
+                    atom_selection,
+                    header.magic,
+                )
+            })?;
+            FrameTiming {
+                read,
+                decode: start.elapsed(),
+            }
+        };
+
+        self.step += 1;
+
+        frame.step = header.step;
+        frame.time = header.time;
+        frame.boxvec = header.boxvec;
+        frame.index = None;
+        frame.offset = None;
+
+        Self::check_finite(self.strict_finite, frame)?;
+        Ok(timing)
+    }
+
+    /// Append [`Frame`]s to the `frames` buffer according to a [`FrameSelection`] like
+    /// [`XTCReader::read_frames`], and return the [`FrameTiming`] of each frame that was read.
+    ///
+    /// See [`XTCReader::read_frame_with_timing`].
+    #[cfg(feature = "profile")]
+    pub fn read_frames_with_timing(
+        &mut self,
+        frames: &mut impl Extend<Frame>,
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<Vec<FrameTiming>> {
+        let base = self.step;
+        let offsets = self.determine_offsets(frame_selection.until())?;
+        let mut timings = Vec::new();
+        for (idx, &offset) in offsets.iter().enumerate() {
+            match frame_selection.is_included(idx) {
+                Some(true) => {}
+                Some(false) => continue,
+                None => break,
+            }
+            self.step = base + idx;
+            self.file.seek(SeekFrom::Start(offset))?;
+            // The frame at the offset need not follow the one read before it.
+            self.prev_time = None;
+            let mut frame = Frame::default();
+            timings.push(self.read_frame_with_timing(&mut frame, atom_selection)?);
+            frame.index = Some(idx);
+            frame.offset = Some(offset);
+            frames.extend(Some(frame));
+        }
+
+        Ok(timings)
     }
 
     /// Reads and returns a [`Frame`] and advances one step, internally reading the compressed data
@@ -539,6 +3243,6 @@ impl XTCReader<File> {
         scratch: &mut Vec<u8>,
         atom_selection: &AtomSelection,
     ) -> io::Result<()> {
-        self.read_frame_with_scratch_impl::<Buffer>(frame, scratch, atom_selection)
+        self.read_frame_with_scratch_impl::<Buffer<R>>(frame, scratch, atom_selection)
     }
 }
@@ -5,14 +5,20 @@
 use std::fs::File;
 use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
 use std::num::{NonZeroU64, ParseIntError};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use molly::buffer::{Buffer, UnBuffered};
+use molly::checksum;
 use molly::reader::{read_nbytes, NBYTES_POSITIONS_PRELUDE};
 use molly::selection::{AtomSelection, FrameSelection, Range};
-use molly::{padding, read_positions, Frame, Header, Magic, XTCReader, XTC_1995_MAX_NATOMS};
+use molly::writer::{validate_box, write_compressed_positions};
+use molly::{
+    padding, read_positions, BoxVec, Frame, FrameMeta, Header, Magic, XTCReader,
+    SMALL_NATOMS_THRESHOLD, XTC_1995_MAX_NATOMS,
+};
 
 fn filter_frames(
     reader: &mut XTCReader<File>,
@@ -28,7 +34,7 @@ fn filter_frames(
 
     let mut scratch = Vec::new();
 
-    let frame_selection = args.frame_selection.unwrap_or_default();
+    let frame_selection = args.resolve_frame_selection(reader)?;
     let atom_selection = args.atom_selection.unwrap_or_default();
 
     let until = if args.reverse || args.reverse_frame_selection {
@@ -36,7 +42,15 @@ fn filter_frames(
     } else {
         frame_selection.until()
     };
-    let mut offsets = reader.determine_offsets(until)?;
+    // The offset of each frame together with its on-disk size, which follow from the offsets of
+    // the ends of the frames.
+    let start = reader.file.stream_position()?;
+    let ends = reader.determine_offsets_exclusive(until)?;
+    let mut offsets: Vec<(u64, u64)> = std::iter::once(start)
+        .chain(ends.iter().copied())
+        .zip(ends.iter().copied())
+        .map(|(start, end)| (start, end - start))
+        .collect();
     let mut range: Box<[usize]> = (0..offsets.len()).collect();
     let enumerated_offsets = {
         // Reversing the frame order and reversing the frame selection have some non-obvious
@@ -57,9 +71,20 @@ fn filter_frames(
         range.iter().zip(offsets.iter().copied())
     };
 
+    let mut progress = if args.progress {
+        let total_bytes = enumerated_offsets
+            .clone()
+            .filter(|(&idx, _)| frame_selection.is_included(idx) == Some(true))
+            .map(|(_, (_, size))| size)
+            .sum();
+        Some(Progress::new(total_bytes))
+    } else {
+        None
+    };
+
     let mut stdout = std::io::stdout();
     let mut frame = Frame::default();
-    for (&idx, offset) in enumerated_offsets {
+    for (&idx, (offset, size)) in enumerated_offsets {
         match frame_selection.is_included(idx) {
             Some(true) => {}
             Some(false) => continue,
@@ -74,19 +99,27 @@ fn filter_frames(
         // Start of by reading the header.
         let header = reader.read_header()?;
 
-        if args.times || args.steps {
+        if args.times || args.steps || args.boxes || args.volume {
+            let mut columns = Vec::new();
             if args.times {
-                write!(stdout, "{:.3}\t", header.time)?;
+                columns.push(format!("{:.3}", header.time));
             }
             if args.steps {
-                write!(stdout, "{}", header.step)?;
+                columns.push(header.step.to_string());
+            }
+            if args.boxes {
+                // The box vectors are stored as the columns of the boxvec.
+                columns.extend(header.boxvec.to_cols_array().map(|v| format!("{v:.5}")));
             }
-            writeln!(stdout, "")?;
+            if args.volume {
+                columns.push(format!("{:.5}", header.boxvec.determinant().abs()));
+            }
+            writeln!(stdout, "{}", columns.join("\t"))?;
         }
 
         // Now, we read the atoms.
         let natoms_frame = header.natoms; // The number of atoms specified for the frame.
-        let nbytes = if natoms_frame <= 9 {
+        let nbytes = if !header.is_compressed() {
             // In this case, the positions are uncompressed. Each consists of three f32s, so we're
             // done pretty quickly.
             reader.read_smol_positions(natoms_frame, &mut frame, &atom_selection)?
@@ -126,10 +159,11 @@ fn filter_frames(
             magic: forced_magic.unwrap_or(header.magic),
             natoms,
             natoms_repeated: natoms,
+            boxvec: args.set_box.unwrap_or(header.boxvec),
             ..header
         };
         // And write it.
-        writer.write_all(&header.to_be_bytes())?;
+        writer.write_all(&header.to_be_bytes()?)?;
 
         if header.magic == Magic::Xtc1995 && header.natoms > XTC_1995_MAX_NATOMS {
             eprintln!(
@@ -141,11 +175,23 @@ fn filter_frames(
             )
         }
 
-        if natoms <= 9 {
+        if natoms <= SMALL_NATOMS_THRESHOLD {
             // The number of positions is small. We encode the positions as uncompressed floats.
             for pos in &frame.positions {
                 writer.write_all(&pos.to_be_bytes())?;
             }
+        } else if let AtomSelection::Mask(_)
+        | AtomSelection::IndexList(_)
+        | AtomSelection::Stride { .. } = atom_selection
+        {
+            // A mask, index list, or stride may select any set of atoms, so the compressed stream
+            // cannot simply be cut short. Instead, we encode the selected positions anew.
+            let precision = frame
+                .precision
+                .value()
+                .expect("a frame of more than 9 atoms is compressed");
+            writer.write_all(&precision.to_be_bytes())?;
+            write_compressed_positions(writer, &frame.positions, precision, header.magic)?;
         } else {
             // TODO: Consider 're-using' the scratch buffer!! It will contain (more than) the bytes we want to write out!
             // TODO: Invent some sort of SCRATCH mechanism here again.
@@ -165,10 +211,15 @@ fn filter_frames(
             // forced to a different value through the secret command line option :)
             let nbytes_old = read_nbytes(&mut reader.file, old_magic)?;
             // Check whether we totally messed up.
-            assert!(
-                nbytes <= nbytes_old as usize,
-                "the new number of bytes ({nbytes}) must never be greater than the old number of bytes ({nbytes_old})"
-            );
+            if nbytes > nbytes_old as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "frame {idx}: the new number of bytes ({nbytes}) must never be greater \
+                        than the old number of bytes ({nbytes_old})"
+                    ),
+                ));
+            }
 
             // Write the new number of upcoming bytes.
             match header.magic {
@@ -180,12 +231,394 @@ fn filter_frames(
             reader.file.read_exact(&mut bytes[..nbytes])?;
             writer.write_all(&bytes)?;
         }
+
+        if let Some(progress) = &mut progress {
+            progress.update(size);
+        }
+    }
+
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+
+    Ok(())
+}
+
+/// A summary of what would be written for a selection, as reported by `--dry-run`.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct DryRun {
+    nframes: usize,
+    /// The smallest and largest number of atoms written for a frame, if any frames are selected.
+    natoms: Option<(usize, usize)>,
+    /// The estimated size of the output in bytes.
+    nbytes: u64,
+}
+
+impl DryRun {
+    /// Summarize the frames described by `metas` that are selected by the `frame_selection` and
+    /// `atom_selection`, when written with `magic`.
+    ///
+    /// The size of a compressed frame is estimated by assuming that the compressed positions
+    /// shrink in proportion to the number of selected atoms.
+    fn new(
+        metas: &[FrameMeta],
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+        magic: Magic,
+    ) -> Self {
+        let nbytes_size = magic.nbytes_width();
+        // The bytes between the header and the compressed positions.
+        let overhead = (4 + NBYTES_POSITIONS_PRELUDE + nbytes_size) as u64;
+
+        let mut summary = Self::default();
+        for (idx, meta) in metas.iter().enumerate() {
+            if frame_selection.is_included(idx) != Some(true) {
+                continue;
+            }
+            let natoms = atom_selection.natoms_selected(meta.natoms);
+
+            let positions = if natoms <= SMALL_NATOMS_THRESHOLD {
+                natoms as u64 * 3 * 4
+            } else {
+                let compressed = meta.size.saturating_sub(Header::SIZE as u64 + overhead);
+                let nbytes = compressed * natoms as u64 / meta.natoms as u64;
+                overhead + nbytes + padding(nbytes as usize) as u64
+            };
+            summary.nframes += 1;
+            summary.nbytes += Header::SIZE as u64 + positions;
+            summary.natoms = Some(match summary.natoms {
+                Some((min, max)) => (min.min(natoms), max.max(natoms)),
+                None => (natoms, natoms),
+            });
+        }
+        summary
     }
+}
+
+/// Report what would be written according to `args`, without writing anything.
+fn dry_run(reader: &mut XTCReader<File>, args: WriteArgs) -> std::io::Result<()> {
+    let frame_selection = args.resolve_frame_selection(reader)?;
+    let atom_selection = args.atom_selection.unwrap_or_default();
+
+    let mut metas = reader.scan(None)?;
+    if args.reverse_frame_selection {
+        // The frame selection is applied to the reversed frame indices.
+        metas.reverse();
+    }
+    let magic = match args.force_magic {
+        Some(magic) => Magic::try_from(magic).map_err(std::io::Error::other)?,
+        None if metas.is_empty() => Magic::Xtc1995,
+        None => {
+            reader.home()?;
+            reader.read_header()?.magic
+        }
+    };
+
+    let summary = DryRun::new(&metas, &frame_selection, &atom_selection, magic);
+    let natoms = match summary.natoms {
+        None => "0".to_string(),
+        Some((min, max)) if min == max => min.to_string(),
+        Some((min, max)) => format!("{min}-{max}"),
+    };
+    eprintln!("dry run, nothing was written to {:?}", args.output);
+    eprintln!("nframes: {}", summary.nframes);
+    eprintln!("natoms:  {natoms} per frame");
+    eprintln!(
+        "size:    ~{} bytes ({:.1} MB)",
+        summary.nbytes,
+        summary.nbytes as f64 / 1e6
+    );
 
     Ok(())
 }
 
-fn frame_selection_parser(selection: &str) -> Result<FrameSelection, ParseIntError> {
+/// Throttled progress reporting to standard error.
+struct Progress {
+    start: Instant,
+    last_report: Option<Instant>,
+    total_bytes: u64,
+    bytes: u64,
+    frames: usize,
+}
+
+impl Progress {
+    /// The minimal time between two progress reports.
+    const INTERVAL: Duration = Duration::from_millis(500);
+
+    fn new(total_bytes: u64) -> Self {
+        Self {
+            start: Instant::now(),
+            last_report: None,
+            total_bytes,
+            bytes: 0,
+            frames: 0,
+        }
+    }
+
+    /// Register a frame of `nbytes` that was just processed, and report if it is time to.
+    fn update(&mut self, nbytes: u64) {
+        self.bytes += nbytes;
+        self.frames += 1;
+
+        let now = Instant::now();
+        if self
+            .last_report
+            .is_some_and(|last| now.duration_since(last) < Self::INTERVAL)
+        {
+            return;
+        }
+        self.last_report = Some(now);
+        self.report();
+    }
+
+    fn report(&self) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let fps = self.frames as f64 / elapsed;
+        let mbps = self.bytes as f64 / elapsed / 1e6;
+        let fraction = match self.total_bytes {
+            0 => 1.0,
+            total => self.bytes as f64 / total as f64,
+        };
+        let remaining = self.total_bytes.saturating_sub(self.bytes) as f64;
+        let eta = if self.bytes > 0 {
+            remaining * elapsed / self.bytes as f64
+        } else {
+            f64::NAN
+        };
+        eprint!(
+            "\r{} frames, {fps:.1} frames/s, {mbps:.1} MB/s, {:5.1}%, ETA {eta:.0} s   ",
+            self.frames,
+            fraction * 100.0
+        );
+    }
+
+    /// Print a final report and end the progress line.
+    fn finish(&self) {
+        self.report();
+        eprintln!();
+    }
+}
+
+/// Returns the path for the output file with number `n`, derived from `output`.
+///
+/// For example, `out.xtc` will become `out.0003.xtc` for `n = 3`.
+fn numbered_path(output: &Path, n: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(extension) => format!("{stem}.{n:04}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{n:04}"),
+    };
+    output.with_file_name(name)
+}
+
+fn split_frames(args: SplitArgs) -> std::io::Result<()> {
+    let mut reader = XTCReader::open(&args.input)?;
+    let ends = reader.determine_offsets_exclusive(None)?;
+    let starts = std::iter::once(0).chain(ends.iter().copied());
+    let frames = starts.zip(ends.iter().copied()).collect::<Vec<_>>();
+
+    // Divide the frames into chunks, each of which will be written to its own file.
+    let mut chunks: Vec<&[(u64, u64)]> = Vec::new();
+    match (args.frames_per_file, args.max_size) {
+        (Some(frames_per_file), _) => {
+            chunks.extend(frames.chunks(frames_per_file.get() as usize));
+        }
+        (None, Some(max_size)) => {
+            let mut first = 0;
+            for (idx, &(start, end)) in frames.iter().enumerate() {
+                let chunk_start = frames[first].0;
+                // Always write at least one frame to each file.
+                if idx > first && end - chunk_start > max_size.get() {
+                    chunks.push(&frames[first..idx]);
+                    first = idx;
+                }
+                if end - start > max_size.get() {
+                    eprintln!(
+                        "WARNING: Frame {idx} ({} bytes) exceeds the maximum file size ({max_size} bytes)",
+                        end - start
+                    );
+                }
+            }
+            if first < frames.len() {
+                chunks.push(&frames[first..]);
+            }
+        }
+        (None, None) => unreachable!("either --frames-per-file or --max-size must be set"),
+    }
+
+    for (n, chunk) in chunks.into_iter().enumerate() {
+        let path = numbered_path(&args.output, n + 1);
+        let mut writer = BufWriter::new(File::create(&path)?);
+        // The frames in a chunk are contiguous, so we can copy them over in one go.
+        let (start, end) = match (chunk.first(), chunk.last()) {
+            (Some(&(start, _)), Some(&(_, end))) => (start, end),
+            _ => continue,
+        };
+        reader.file.seek(SeekFrom::Start(start))?;
+        let copied = io::copy(&mut (&mut reader.file).take(end - start), &mut writer)?;
+        if copied != end - start {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "expected to copy {} bytes to {path:?}, found {copied}",
+                    end - start
+                ),
+            ));
+        }
+        writer.flush()?;
+        if args.checksum {
+            checksum::stamp(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn verify(args: VerifyArgs) -> std::io::Result<()> {
+    // Reading all frames catches structural damage, such as a truncated or garbled frame.
+    let mut reader = XTCReader::open(&args.input)?;
+    let mut nframes = 0;
+    while reader.read_next_frame(&AtomSelection::All)?.is_some() {
+        nframes += 1;
+    }
+
+    let sidecar = checksum::sidecar_path(&args.input);
+    if !sidecar.exists() {
+        println!("{nframes} frames read without errors");
+        println!("no checksum sidecar found at {sidecar:?}, so the frame bytes were not checked");
+        return Ok(());
+    }
+
+    let mismatches = checksum::verify(&args.input)?;
+    if mismatches.is_empty() {
+        println!("{nframes} frames read without errors, all matching their checksums");
+        return Ok(());
+    }
+    for idx in &mismatches {
+        eprintln!("ERROR: Frame {idx} does not match its checksum");
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} of {nframes} frames do not match their checksums",
+            mismatches.len()
+        ),
+    ))
+}
+
+/// The differences between the positions of two trajectories, as reported by `molly diff`.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Diff {
+    /// The number of frames that were compared.
+    nframes: usize,
+    /// The number of atom positions that were compared, over all frames.
+    natoms: usize,
+    /// The first frame and atom at which the positions differ by more than the tolerance.
+    first: Option<(usize, usize)>,
+    /// The number of atom positions that differ by more than the tolerance.
+    ndiffering: usize,
+    /// The first frame at which the number of atoms differs, with both numbers of atoms.
+    natoms_mismatch: Option<(usize, usize, usize)>,
+    /// The largest distance (nm) between two positions.
+    max: f32,
+    /// The sum of the distances (nm) between the positions, from which the mean is derived.
+    sum: f64,
+}
+
+impl Diff {
+    /// Compare the positions of the frames `a` and `b` at index `idx`.
+    ///
+    /// If the frames hold a different number of atoms, the atoms they have in common are compared.
+    fn compare(&mut self, idx: usize, a: &Frame, b: &Frame, tolerance: f32) {
+        if a.natoms() != b.natoms() && self.natoms_mismatch.is_none() {
+            self.natoms_mismatch = Some((idx, a.natoms(), b.natoms()));
+        }
+        for (atom, (a, b)) in a.coords().zip(b.coords()).enumerate() {
+            let distance = a.distance(b);
+            if distance > tolerance {
+                self.ndiffering += 1;
+                self.first.get_or_insert((idx, atom));
+            }
+            self.max = self.max.max(distance);
+            self.sum += distance as f64;
+            self.natoms += 1;
+        }
+        self.nframes += 1;
+    }
+
+    /// The mean distance (nm) between the compared positions.
+    fn mean(&self) -> f64 {
+        match self.natoms {
+            0 => 0.0,
+            n => self.sum / n as f64,
+        }
+    }
+}
+
+fn diff(args: DiffArgs) -> std::io::Result<()> {
+    let mut a = XTCReader::open(&args.a)?;
+    let mut b = XTCReader::open(&args.b)?;
+    let mut summary = Diff::default();
+    let (nframes_a, nframes_b) = loop {
+        let idx = summary.nframes;
+        match (
+            a.read_next_frame(&AtomSelection::All)?,
+            b.read_next_frame(&AtomSelection::All)?,
+        ) {
+            (Some(frame_a), Some(frame_b)) => {
+                summary.compare(idx, &frame_a, &frame_b, args.tolerance)
+            }
+            // Count the frames that are left over, without decoding them.
+            (Some(_), None) => break (idx + 1 + a.frame_count()?, idx),
+            (None, Some(_)) => break (idx, idx + 1 + b.frame_count()?),
+            (None, None) => break (idx, idx),
+        }
+    };
+
+    println!(
+        "compared {} frames, {} atom positions",
+        summary.nframes, summary.natoms
+    );
+    println!("max deviation:  {:.6} nm", summary.max);
+    println!("mean deviation: {:.6} nm", summary.mean());
+    match summary.first {
+        Some((frame, atom)) => println!(
+            "{} positions differ by more than {} nm, first at frame {frame}, atom {atom}",
+            summary.ndiffering, args.tolerance
+        ),
+        None => println!("no positions differ by more than {} nm", args.tolerance),
+    }
+
+    let mut problems = Vec::new();
+    if nframes_a != nframes_b {
+        eprintln!(
+            "ERROR: {:?} has {nframes_a} frames, while {:?} has {nframes_b} frames",
+            args.a, args.b
+        );
+        problems.push("the number of frames differs");
+    }
+    if let Some((frame, natoms_a, natoms_b)) = summary.natoms_mismatch {
+        eprintln!(
+            "ERROR: Frame {frame} has {natoms_a} atoms in {:?}, but {natoms_b} atoms in {:?}",
+            args.a, args.b
+        );
+        problems.push("the number of atoms differs");
+    }
+    if summary.first.is_some() {
+        problems.push("positions differ beyond the tolerance");
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("the trajectories differ: {}", problems.join(", ")),
+    ))
+}
+
+fn frame_selection_parser(
+    selection: &str,
+) -> Result<FrameSelection, Box<dyn std::error::Error + Send + Sync>> {
     let mut components = selection.split(':');
     let start = components
         .next()
@@ -202,10 +635,128 @@ fn frame_selection_parser(selection: &str) -> Result<FrameSelection, ParseIntErr
         .and_then(|s| if s.is_empty() { None } else { Some(s) })
         .map(|s| NonZeroU64::from_str(s))
         .transpose()?;
-    Ok(FrameSelection::Range(Range::new(start, end, step)))
+    Ok(FrameSelection::Range(Range::try_new(start, end, step)?))
+}
+
+/// A selection of frames by their time, as resolved by [`TimeSelection::resolve`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TimeSelection {
+    /// The earliest time (ps) to include.
+    start: Option<f32>,
+    /// The latest time (ps) to include.
+    stop: Option<f32>,
+    /// The interval (ps) between the selected frames.
+    every: Option<f32>,
+}
+
+impl TimeSelection {
+    /// Returns the [`FrameSelection`] of the frames with the times in `metas` that fall within
+    /// this selection.
+    ///
+    /// The first frame at or after `start` is selected, followed by the first frame at or after
+    /// each multiple of `every` from its time. Times are compared with a tolerance of a few ulps,
+    /// such that the rounding of the stored times does not skip frames.
+    fn resolve(&self, metas: &[FrameMeta]) -> FrameSelection {
+        let every = self.every.map(f64::from).unwrap_or_default();
+        let tolerance = |time: f64| time.abs() * 4.0 * f32::EPSILON as f64;
+        // The time of the first selected frame, and the next time to select a frame at.
+        let mut anchor: Option<f64> = None;
+        let mut next: Option<f64> = None;
+        let mut selected = Vec::new();
+        for (idx, meta) in metas.iter().enumerate() {
+            let time = meta.time as f64;
+            if self
+                .start
+                .map(f64::from)
+                .is_some_and(|start| time < start - tolerance(start))
+            {
+                continue;
+            }
+            if self
+                .stop
+                .map(f64::from)
+                .is_some_and(|stop| time > stop + tolerance(stop))
+            {
+                continue;
+            }
+            if next.is_some_and(|next| time < next - tolerance(next)) {
+                continue;
+            }
+            selected.push(idx);
+            if every > 0.0 {
+                // Step to the first multiple of the interval beyond this frame. This is computed
+                // directly rather than by repeated addition, which stalls once the interval falls
+                // below the spacing of the floats at large times.
+                let anchor = *anchor.get_or_insert(time);
+                let n = ((time + tolerance(time) - anchor) / every).floor() + 1.0;
+                next = Some(anchor + every * n);
+            }
+        }
+        FrameSelection::framelist_from_iter(selected)
+    }
+}
+
+fn time_selection_parser(
+    selection: &str,
+) -> Result<TimeSelection, Box<dyn std::error::Error + Send + Sync>> {
+    let mut components = selection.split(':');
+    let mut next = || -> Result<Option<f32>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(s) = components.next().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let value: f32 = s.parse()?;
+        if !value.is_finite() {
+            return Err(format!("a time must be a finite number, found {s}").into());
+        }
+        Ok(Some(value))
+    };
+    let (start, stop, every) = (next()?, next()?, next()?);
+    if let (Some(start), Some(stop)) = (start, stop) {
+        if start > stop {
+            return Err(format!(
+                "the start time ({start} ps) may not exceed the stop time ({stop} ps)"
+            )
+            .into());
+        }
+    }
+    if every.is_some_and(|every| every <= 0.0) {
+        return Err("the interval between frames must be greater than zero".into());
+    }
+    Ok(TimeSelection { start, stop, every })
+}
+
+fn box_parser(boxvec: &str) -> Result<BoxVec, Box<dyn std::error::Error + Send + Sync>> {
+    let values = boxvec
+        .split(',')
+        .map(|s| s.trim().parse())
+        .collect::<Result<Vec<f32>, _>>()?;
+    let boxvec = match values[..] {
+        [a, b, c] => BoxVec::from_diagonal(glam::Vec3::new(a, b, c)),
+        [ax, ay, az, bx, by, bz, cx, cy, cz] => {
+            BoxVec::from_cols_array(&[ax, ay, az, bx, by, bz, cx, cy, cz])
+        }
+        _ => {
+            return Err(format!(
+                "a box must be given as 3 or 9 comma-separated values, found {}",
+                values.len()
+            )
+            .into())
+        }
+    };
+    validate_box(&boxvec)?;
+    Ok(boxvec)
 }
 
 fn atom_selection_parser(selection: &str) -> Result<AtomSelection, ParseIntError> {
+    if selection.contains(',') {
+        let indices = selection
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .collect::<Result<Vec<u32>, _>>()?;
+        return Ok(AtomSelection::from_index_list(&indices));
+    }
+
     let until: u32 = selection.parse()?;
     Ok(AtomSelection::Until(until))
 }
@@ -217,10 +768,18 @@ fn atom_selection_parser(selection: &str) -> Result<AtomSelection, ParseIntError
 /// By Marieke Westendorp, 2024.
 /// <ma3ke.cyber@gmail.com>
 #[derive(Parser)]
-#[command(version)]
+#[command(
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Input path (xtc).
-    input: PathBuf,
+    #[arg(required = true)]
+    input: Option<PathBuf>,
 
     #[command(flatten)]
     write: Option<WriteArgs>,
@@ -234,6 +793,82 @@ struct Args {
     info: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Split an xtc trajectory into a number of smaller files.
+    ///
+    /// Frames are copied over as a whole, without decoding their positions. The output files are
+    /// numbered, starting at one. For example, an output path of `out.xtc` will produce
+    /// `out.0001.xtc`, `out.0002.xtc`, etc.
+    Split(SplitArgs),
+    /// Check that an xtc trajectory is intact.
+    ///
+    /// All frames are read and decoded to check the structure of the trajectory. If a checksum
+    /// sidecar exists next to the trajectory (see `--checksum`), the bytes of each frame are also
+    /// checked against their recorded checksums, which detects corruption that still decodes.
+    Verify(VerifyArgs),
+    /// Compare the positions of two xtc trajectories, frame by frame.
+    ///
+    /// Reports the first frame and atom at which the positions differ by more than the
+    /// tolerance, along with the largest and mean distance between the positions. This is useful
+    /// to check that a trajectory survived filtering or writing unchanged. Exits with an error if
+    /// the positions differ beyond the tolerance, or if the number of frames or atoms differs.
+    Diff(DiffArgs),
+}
+
+#[derive(Parser)]
+struct DiffArgs {
+    /// First input path (xtc).
+    a: PathBuf,
+
+    /// Second input path (xtc).
+    b: PathBuf,
+
+    /// The largest distance (nm) between two positions that is not considered a difference.
+    ///
+    /// By default, any difference is reported.
+    #[arg(long, default_value_t = 0.0)]
+    tolerance: f32,
+}
+
+#[derive(Parser)]
+struct VerifyArgs {
+    /// Input path (xtc).
+    input: PathBuf,
+}
+
+#[derive(Parser)]
+struct SplitArgs {
+    /// Input path (xtc).
+    input: PathBuf,
+
+    /// Output path (xtc), from which the numbered output paths are derived.
+    output: PathBuf,
+
+    /// The number of frames to write to each file.
+    ///
+    /// The last file may contain fewer frames.
+    #[arg(
+        long,
+        required_unless_present = "max_size",
+        conflicts_with = "max_size"
+    )]
+    frames_per_file: Option<NonZeroU64>,
+
+    /// The maximum size of each file in bytes.
+    ///
+    /// Each file contains at least one frame, so a file may exceed this size if a single frame
+    /// is larger than the maximum size.
+    #[arg(long)]
+    max_size: Option<NonZeroU64>,
+
+    /// Write a checksum sidecar for each output file.
+    ///
+    /// See the `--checksum` option for writing a trajectory.
+    #[arg(long)]
+    checksum: bool,
+}
+
 #[derive(Parser)]
 struct WriteArgs {
     /// Output path (xtc).
@@ -241,8 +876,9 @@ struct WriteArgs {
 
     /// Frame selection in the format `start:stop:step`. Each of these values optional.
     ///
-    // TODO: Make these examples into unit tests for the frame_selection_parser and its atom counterpart.
-    // TODO: Verify that I didn't make any mistakes in these examples, once everything is up and running.
+    /// Like a Python slice, frame indices start at zero, `start` is inclusive and `stop` is
+    /// exclusive.
+    ///
     /// - `:100` will select the first 100 frames.
     ///
     /// - `3:14` will select the 4th up to and including the 14th frames, 11 frames in total.
@@ -251,16 +887,35 @@ struct WriteArgs {
     #[arg(short, long, value_parser=frame_selection_parser)]
     frame_selection: Option<FrameSelection>,
 
-    // TODO: Consider explaining why this seemingly silly limitation exists. It may be confusing
-    // to just drop it here, but explaining it is also quite the ride.
-    /// Atom selection single `stop` value.
+    /// Frame selection by time in the format `start:stop:every`, in picoseconds. Each of these
+    /// values is optional.
     ///
-    /// For each frame that is read, the compressed positions up to the provided index will be
-    /// stored into the output file.
+    /// Unlike `--frame-selection`, both `start` and `stop` are inclusive, like the `-b` and `-e`
+    /// options of `gmx trjconv`. Of the frames between them, the first is selected, followed by
+    /// the first frame at or after each multiple of `every` from there. The times are resolved to
+    /// frame indices by reading the frame headers before the positions are read.
+    ///
+    /// - `10000:50000` selects the frames from 10 ns up to and including 50 ns.
+    ///
+    /// - `::100` selects a frame every 100 ps.
+    #[arg(long, value_parser=time_selection_parser, conflicts_with = "frame_selection")]
+    time_selection: Option<TimeSelection>,
+
+    /// Atom selection as a single `stop` value, or as a comma-separated list of atom indices.
+    ///
+    /// For a single `stop` value, the compressed positions up to the provided index will be
+    /// copied into the output file for each frame that is read. This is fast, since the
+    /// compressed data does not need to be encoded again.
     ///
-    // TODO: Verify that I didn't make any mistakes in these examples, once everything is up and running.
     /// - `1312` selects the first 1312 atoms.
     ///
+    /// For a list of indices, the selected positions are compressed anew for each frame.
+    /// Indices start at zero. A single index can be selected by adding a trailing comma.
+    ///
+    /// - `0,3,5,7` selects the 1st, 4th, 6th, and 8th atoms.
+    ///
+    /// - `12,` selects only the 13th atom.
+    ///
     /// Note that according to the xtc format, when the number of atoms in the frame is equal to
     /// or less than 9 (natoms <= 9), the atoms will be stored in an uncompressed manner.
     #[arg(short, long, value_parser=atom_selection_parser)]
@@ -297,49 +952,103 @@ struct WriteArgs {
     #[arg(long)]
     steps: bool,
 
+    /// Print the box vectors (nm) for the selected frames to standard output.
+    ///
+    /// The nine values are printed as the box vectors `a`, `b`, and `c` in that order, separated
+    /// by tabs. They are printed after the times and steps, if those are active.
+    #[arg(long = "box")]
+    boxes: bool,
+
+    /// Print the box volume (nm³) for the selected frames to standard output.
+    ///
+    /// Printed as the last column, after the times, steps, and box vectors, if those are active.
+    #[arg(long)]
+    volume: bool,
+
+    /// Print the progress, throughput, and estimated time remaining to standard error.
+    #[arg(long)]
+    progress: bool,
+
+    /// Write this box for every frame instead of its own box, for instance after re-boxing.
+    ///
+    /// Given as three comma-separated lengths (nm) `a,b,c` for a rectangular box, or as nine
+    /// values for the box vectors `a`, `b`, and `c` in that order, like the box printed by
+    /// `--box`. The box must be in the canonical Gromacs form.
+    #[arg(long, value_parser=box_parser)]
+    set_box: Option<BoxVec>,
+
+    /// Write a sidecar file with a checksum of each frame next to the output file.
+    ///
+    /// The sidecar is written to the output path with `.crc` appended. The trajectory itself is
+    /// left untouched, since the xtc format has no place for checksums. Use `molly verify` to
+    /// check the trajectory against its checksums later.
+    #[arg(long)]
+    checksum: bool,
+
+    /// Report the number of selected frames, the atoms per frame, and the estimated size of the
+    /// output to standard error, without writing anything.
+    ///
+    /// The selections are evaluated and the frame headers are scanned, but no positions are read.
+    /// This is useful to check a selection before writing a large trajectory.
+    #[arg(long, conflicts_with = "checksum")]
+    dry_run: bool,
+
     /// Force set the magic number of the output file.
     #[arg(long, hide = true)]
     force_magic: Option<i32>,
 }
 
+impl WriteArgs {
+    /// Returns the selected frames, resolving a time selection to frame indices by scanning the
+    /// headers of `reader`.
+    fn resolve_frame_selection(
+        &self,
+        reader: &mut XTCReader<File>,
+    ) -> std::io::Result<FrameSelection> {
+        Ok(match self.time_selection {
+            Some(time_selection) => time_selection.resolve(&reader.scan(None)?),
+            None => self.frame_selection.clone().unwrap_or_default(),
+        })
+    }
+}
+
 fn main() -> std::io::Result<()> {
     let args = Args::parse();
 
-    let file = std::fs::File::open(&args.input).unwrap_or_else(|err| {
-        eprintln!(
-            "ERROR: Failed to read trajectory from {:?}: {err}",
-            &args.input
-        );
+    match args.command {
+        Some(Command::Split(split)) => return split_frames(split),
+        Some(Command::Verify(verify_args)) => return verify(verify_args),
+        Some(Command::Diff(diff_args)) => return diff(diff_args),
+        None => {}
+    }
+
+    let input = args
+        .input
+        .expect("an input path must be available if no subcommand is passed");
+    let file = std::fs::File::open(&input).unwrap_or_else(|err| {
+        eprintln!("ERROR: Failed to read trajectory from {:?}: {err}", &input);
         std::process::exit(1)
     });
     let mut reader = XTCReader::new(file);
 
     if args.info {
-        let offsets = reader.determine_offsets(None)?;
-        let name = args
-            .input
+        let metas = reader.scan(None)?;
+        let name = input
             .file_name()
             .and_then(|name| name.to_str())
             .unwrap_or_default();
         println!("name:    {name}",);
-        println!("path:    {:?}", &args.input);
-        println!("nframes: {}", offsets.len());
-        let headers = offsets
-            .iter()
-            .map(|&offset| -> io::Result<Header> {
-                reader.file.seek(SeekFrom::Start(offset))?;
-                reader.read_header()
-            })
-            .collect::<io::Result<Vec<_>>>()?;
-        let natoms = headers
+        println!("path:    {:?}", &input);
+        println!("nframes: {}", metas.len());
+        let natoms = metas
             .first()
-            .map(|header| header.natoms.to_string())
+            .map(|meta| meta.natoms.to_string())
             .unwrap_or("?".to_string());
         println!("natoms:  {natoms}");
-        let (first, last) = (headers.first(), headers.last());
+        let (first, last) = (metas.first(), metas.last());
 
-        let first_step = first.map(|header| header.step);
-        let last_step = last.map(|header| header.step);
+        let first_step = first.map(|meta| meta.step);
+        let last_step = last.map(|meta| meta.step);
         let steps = match (first_step, last_step) {
             (None, None) => "?".to_string(),
             (None, Some(_)) => unreachable!(),
@@ -348,8 +1057,8 @@ fn main() -> std::io::Result<()> {
         };
         println!("steps:   {steps}");
 
-        let first_time = first.map(|header| header.time);
-        let last_time = last.map(|header| header.time);
+        let first_time = first.map(|meta| meta.time);
+        let last_time = last.map(|meta| meta.time);
         let times = match (first_time, last_time) {
             (None, None) => "?".to_string(),
             (None, Some(_)) => unreachable!(),
@@ -358,18 +1067,34 @@ fn main() -> std::io::Result<()> {
         };
         println!("time:    {times} ps");
 
-        let magic = match first {
+        // The scan leaves the reader at the start, so we can read the first header from here.
+        let first = match first {
+            Some(_) => Some(reader.read_header()?),
+            None => None,
+        };
+        let magic = match &first {
             Some(Header { magic, .. }) => magic.to_string(),
             None => "?".to_string(),
         };
         println!("magic:   {magic}");
 
+        let compressed = match &first {
+            Some(header) if header.is_compressed() => "yes",
+            Some(_) => "no (9 atoms or less, positions are stored as plain floats)",
+            None => "?",
+        };
+        println!("packed:  {compressed}");
+
         return Ok(());
     }
 
     let write = args
         .write
         .expect("write arguments must be available if --info is not passed");
+    if write.dry_run {
+        return dry_run(&mut reader, write);
+    }
+    let stamp = write.checksum.then(|| write.output.clone());
     let mut writer = BufWriter::new(std::fs::File::create(&write.output).unwrap_or_else(|err| {
         eprintln!(
             "ERROR: Failed to write processed trajectory to {:?}: {err}",
@@ -377,5 +1102,374 @@ fn main() -> std::io::Result<()> {
         );
         std::process::exit(1)
     }));
-    filter_frames(&mut reader, &mut writer, write)
+    filter_frames(&mut reader, &mut writer, write)?;
+    if let Some(output) = stamp {
+        writer.flush()?;
+        checksum::stamp(output)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the indices below `n` that are included in a [`FrameSelection`].
+    fn included(selection: &FrameSelection, n: usize) -> Vec<usize> {
+        (0..n)
+            .filter(|&idx| selection.is_included(idx) == Some(true))
+            .collect()
+    }
+
+    #[test]
+    fn frame_selection_first_n() {
+        let selection = frame_selection_parser(":100").unwrap();
+        assert_eq!(included(&selection, 1000), Vec::from_iter(0..100));
+    }
+
+    /// `3:14` selects the 4th up to and including the 14th frames, 11 frames in total.
+    #[test]
+    fn frame_selection_start_stop() {
+        let selection = frame_selection_parser("3:14").unwrap();
+        let indices = included(&selection, 1000);
+        assert_eq!(indices.len(), 11);
+        assert_eq!(indices.first(), Some(&3));
+        assert_eq!(indices.last(), Some(&13));
+    }
+
+    #[test]
+    fn frame_selection_stepped() {
+        let selection = frame_selection_parser(":100:2").unwrap();
+        let indices = included(&selection, 1000);
+        assert_eq!(indices.len(), 50);
+        assert_eq!(indices, Vec::from_iter((0..100).step_by(2)));
+    }
+
+    #[test]
+    fn frame_selection_empty_components() {
+        for s in ["", ":", "::"] {
+            let selection = frame_selection_parser(s).unwrap();
+            assert_eq!(included(&selection, 100), Vec::from_iter(0..100));
+        }
+    }
+
+    #[test]
+    fn frame_selection_invalid() {
+        assert!(frame_selection_parser("a:b").is_err());
+        assert!(frame_selection_parser("::0").is_err());
+        assert!(frame_selection_parser("-1:").is_err());
+        assert!(frame_selection_parser("100:50").is_err());
+    }
+
+    /// Returns the frame metadata for frames at the given `times`.
+    fn metas(times: &[f32]) -> Vec<FrameMeta> {
+        times
+            .iter()
+            .map(|&time| FrameMeta {
+                offset: 0,
+                size: 0,
+                step: 0,
+                time,
+                natoms: 0,
+                boxvec: BoxVec::ZERO,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn time_selection_start_stop() {
+        let times: Vec<f32> = (0..100).map(|i| i as f32 * 10.0).collect();
+        let selection = time_selection_parser("100:200").unwrap();
+        let indices = included(&selection.resolve(&metas(&times)), 100);
+        assert_eq!(indices, Vec::from_iter(10..=20));
+    }
+
+    #[test]
+    fn time_selection_every() {
+        // Times that do not add up exactly, as stored by a simulation with a step of 0.1 ps.
+        let times: Vec<f32> = (0..1000).map(|i| i as f32 * 0.1).collect();
+        let selection = time_selection_parser("5::2.5").unwrap();
+        let indices = included(&selection.resolve(&metas(&times)), 1000);
+        assert_eq!(indices, Vec::from_iter((50..1000).step_by(25)));
+    }
+
+    #[test]
+    fn time_selection_irregular() {
+        let times = [0.0, 3.0, 4.0, 9.0, 10.0, 11.0, 12.0, 20.0];
+        let selection = time_selection_parser("::5").unwrap();
+        let indices = included(&selection.resolve(&metas(&times)), times.len());
+        // The first frames at or after 0, 5, 10, and 15 ps.
+        assert_eq!(indices, [0, 3, 4, 7]);
+    }
+
+    /// Frames just outside of the `start` and `stop` times are excluded.
+    #[test]
+    fn time_selection_inclusive_bounds() {
+        let times: Vec<f32> = [99999, 100000, 100001, 499999, 500000, 500001]
+            .iter()
+            .map(|&i| i as f32 * 0.1)
+            .collect();
+        let selection = time_selection_parser("10000:50000").unwrap();
+        let indices = included(&selection.resolve(&metas(&times)), times.len());
+        assert_eq!(indices, [1, 2, 3, 4]);
+    }
+
+    /// At large times, the interval may be smaller than the spacing between the stored times.
+    #[test]
+    fn time_selection_large_times() {
+        let times: Vec<f32> = (0..100).map(|i| 1e7 + i as f32 * 10.0).collect();
+        let selection = time_selection_parser("::0.5").unwrap();
+        let indices = included(&selection.resolve(&metas(&times)), times.len());
+        assert_eq!(indices, Vec::from_iter(0..100));
+
+        let selection = time_selection_parser("::30").unwrap();
+        let indices = included(&selection.resolve(&metas(&times)), times.len());
+        assert_eq!(indices, Vec::from_iter((0..100).step_by(3)));
+    }
+
+    #[test]
+    fn time_selection_invalid() {
+        assert!(time_selection_parser("a:").is_err());
+        assert!(time_selection_parser("::0").is_err());
+        assert!(time_selection_parser("::-5").is_err());
+        assert!(time_selection_parser("500:100").is_err());
+        assert!(time_selection_parser("nan:").is_err());
+    }
+
+    /// Returns the metadata of `nframes` compressed frames of 100 atoms and 1000 bytes each.
+    fn compressed_metas(nframes: usize) -> Vec<FrameMeta> {
+        metas(&vec![0.0; nframes])
+            .into_iter()
+            .map(|meta| FrameMeta {
+                size: 1000,
+                natoms: 100,
+                ..meta
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dry_run_all() {
+        let summary = DryRun::new(
+            &compressed_metas(4),
+            &FrameSelection::All,
+            &AtomSelection::All,
+            Magic::Xtc1995,
+        );
+        assert_eq!(summary.nframes, 4);
+        assert_eq!(summary.natoms, Some((100, 100)));
+        // Without a selection of atoms, the frames stay the same size.
+        assert_eq!(summary.nbytes, 4 * 1000);
+    }
+
+    #[test]
+    fn dry_run_selection() {
+        let metas = compressed_metas(4);
+        let frame_selection = frame_selection_parser(":2").unwrap();
+
+        let atom_selection = atom_selection_parser("50").unwrap();
+        let summary = DryRun::new(&metas, &frame_selection, &atom_selection, Magic::Xtc1995);
+        assert_eq!(summary.nframes, 2);
+        assert_eq!(summary.natoms, Some((50, 50)));
+        // The header and prelude, followed by half of the 908 compressed bytes, padded.
+        assert_eq!(summary.nbytes, 2 * (56 + 36 + 456));
+
+        // Few enough atoms to be written as uncompressed floats.
+        let atom_selection = atom_selection_parser("0,1,").unwrap();
+        let summary = DryRun::new(&metas, &frame_selection, &atom_selection, Magic::Xtc1995);
+        assert_eq!(summary.natoms, Some((2, 2)));
+        assert_eq!(summary.nbytes, 2 * (56 + 2 * 12));
+    }
+
+    #[test]
+    fn dry_run_empty() {
+        let frame_selection = frame_selection_parser("10:").unwrap();
+        let summary = DryRun::new(
+            &compressed_metas(4),
+            &frame_selection,
+            &AtomSelection::All,
+            Magic::Xtc2023,
+        );
+        assert_eq!(summary, DryRun::default());
+    }
+
+    /// Returns a frame with atoms along the x axis, spaced 1 nm apart.
+    fn line(natoms: usize) -> Frame {
+        Frame {
+            positions: (0..natoms).flat_map(|i| [i as f32, 0.0, 0.0]).collect(),
+            ..Frame::default()
+        }
+    }
+
+    #[test]
+    fn diff_identical() {
+        let mut summary = Diff::default();
+        for idx in 0..3 {
+            summary.compare(idx, &line(5), &line(5), 0.0);
+        }
+        assert_eq!(summary.nframes, 3);
+        assert_eq!(summary.natoms, 15);
+        assert_eq!(summary.first, None);
+        assert_eq!(summary.max, 0.0);
+        assert_eq!(summary.mean(), 0.0);
+    }
+
+    #[test]
+    fn diff_tolerance() {
+        let a = line(4);
+        let mut b = line(4);
+        b.positions[3 * 2 + 1] += 0.5; // Atom 2 moves 0.5 nm.
+        b.positions[3 * 3 + 2] += 0.001; // Atom 3 moves 0.001 nm.
+
+        let mut summary = Diff::default();
+        summary.compare(0, &a, &a, 0.01);
+        summary.compare(1, &a, &b, 0.01);
+        assert_eq!(summary.first, Some((1, 2)));
+        assert_eq!(summary.ndiffering, 1);
+        assert_eq!(summary.max, 0.5);
+        assert!((summary.mean() - 0.501 / 8.0).abs() < 1e-6);
+
+        let mut summary = Diff::default();
+        summary.compare(0, &a, &b, 0.0);
+        assert_eq!(summary.first, Some((0, 2)));
+        assert_eq!(summary.ndiffering, 2);
+    }
+
+    #[test]
+    fn diff_natoms() {
+        let mut summary = Diff::default();
+        summary.compare(0, &line(4), &line(4), 0.0);
+        summary.compare(1, &line(4), &line(3), 0.0);
+        summary.compare(2, &line(2), &line(3), 0.0);
+        assert_eq!(summary.natoms_mismatch, Some((1, 4, 3)));
+        // The atoms the frames have in common are compared.
+        assert_eq!(summary.natoms, 4 + 3 + 2);
+        assert_eq!(summary.first, None);
+    }
+
+    #[test]
+    fn box_rectangular() {
+        let boxvec = box_parser("2,3,4").unwrap();
+        assert_eq!(
+            boxvec.to_cols_array(),
+            [2.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn box_triclinic() {
+        let values = [4.0, 0.0, 0.0, 1.0, 4.0, 0.0, -2.0, 1.5, 4.0];
+        let s = values.map(|v| v.to_string()).join(",");
+        assert_eq!(box_parser(&s).unwrap().to_cols_array(), values);
+    }
+
+    #[test]
+    fn box_invalid() {
+        assert!(box_parser("2,3").is_err());
+        assert!(box_parser("2,3,x").is_err());
+        assert!(box_parser("2,-3,4").is_err());
+        // The vector b may not be skewed by more than half of a.
+        assert!(box_parser("4,0,0,3,4,0,0,0,4").is_err());
+    }
+
+    #[test]
+    fn atom_selection_until() {
+        let selection = atom_selection_parser("1312").unwrap();
+        assert!(matches!(selection, AtomSelection::Until(1312)));
+        assert!(atom_selection_parser("").is_err());
+        assert!(atom_selection_parser("-5").is_err());
+    }
+
+    #[test]
+    fn split_numbered_path() {
+        let path = numbered_path(Path::new("out.xtc"), 3);
+        assert_eq!(path, Path::new("out.0003.xtc"));
+        let path = numbered_path(Path::new("some/dir/traj.xtc"), 12);
+        assert_eq!(path, Path::new("some/dir/traj.0012.xtc"));
+        let path = numbered_path(Path::new("out"), 1);
+        assert_eq!(path, Path::new("out.0001"));
+    }
+
+    /// A temporary output file with a unique name, which is removed when dropped.
+    struct TempOutput(PathBuf);
+
+    impl TempOutput {
+        fn new(name: &str) -> Self {
+            let name = format!("molly_main_{name}_{}.xtc", std::process::id());
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempOutput {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Filters the adk trajectory with the `atom_selection`, and checks the written frames
+    /// against the selected positions of the original frames.
+    fn filter_atoms(name: &str, atom_selection: &str) -> std::io::Result<usize> {
+        const INPUT: &str = "tests/trajectories/adk_oplsaa.xtc";
+        let output = TempOutput::new(name);
+        let args = WriteArgs::try_parse_from([
+            "molly",
+            output.0.to_str().unwrap(),
+            "--atom-selection",
+            atom_selection,
+        ])
+        .unwrap();
+        let selection = args.atom_selection.clone().unwrap();
+        let mut writer = BufWriter::new(File::create(&output.0)?);
+        filter_frames(&mut XTCReader::open(INPUT)?, &mut writer, args)?;
+        writer.flush()?;
+
+        let expected = XTCReader::open(INPUT)?.read_all_frames()?;
+        let mut reader = XTCReader::open(&output.0)?;
+        let metas = reader.scan(None)?;
+        let frames = reader.read_all_frames()?;
+        assert_eq!(frames.len(), expected.len());
+        for ((frame, meta), expected) in frames.iter().zip(&metas).zip(expected.iter()) {
+            let expected = expected.subset(&selection);
+            assert_eq!(meta.natoms, expected.natoms());
+            let tolerance = expected.precision.value().map_or(0.0, |p| 1.0 / p);
+            for (a, b) in frame.positions.iter().zip(&expected.positions) {
+                assert!((a - b).abs() <= tolerance, "{a} != {b}");
+            }
+            assert_eq!(frame.natoms(), expected.natoms());
+        }
+        Ok(frames[0].natoms())
+    }
+
+    #[test]
+    fn filter_index_list() -> std::io::Result<()> {
+        let natoms = filter_atoms("index_list", "0,5,17,100,101,102,500,1000,2000,3000,3340")?;
+        assert_eq!(natoms, 11);
+        Ok(())
+    }
+
+    /// A selection of up to 9 atoms is written as uncompressed floats.
+    #[test]
+    fn filter_index_list_small() -> std::io::Result<()> {
+        let natoms = filter_atoms("index_list_small", "0,1,2,3,4,5,6,7,3000")?;
+        assert_eq!(natoms, SMALL_NATOMS_THRESHOLD);
+        let natoms = filter_atoms("index_list_single", "1500,")?;
+        assert_eq!(natoms, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn atom_selection_index_list() {
+        let selection = atom_selection_parser("0,3,5,7").unwrap();
+        let included = (0..10)
+            .filter(|&idx| selection.is_included(idx) == Some(true))
+            .collect::<Vec<_>>();
+        assert_eq!(included, [0, 3, 5, 7]);
+
+        let selection = atom_selection_parser("12,").unwrap();
+        assert_eq!(selection.is_included(12), Some(true));
+        assert_eq!(selection.is_included(11), Some(false));
+
+        assert!(atom_selection_parser("1,a").is_err());
+        assert!(atom_selection_parser("1,-2").is_err());
+    }
 }
@@ -1,8 +1,8 @@
 use std::io::{self, Read};
 
-use crate::buffer::Buffered;
+use crate::buffer::{BufferStats, Buffered};
 use crate::selection::AtomSelection;
-use crate::{BoxVec, Magic};
+use crate::{padding, BoxVec, Magic};
 
 struct DecodeState {
     lastbits: usize,
@@ -10,6 +10,16 @@ struct DecodeState {
 }
 
 // TODO: I have a constexpr laying around for this somewhere.
+/// The table of sizes used to encode the differences between neighbouring atoms.
+///
+/// The compressed positions of an xtc frame store most atoms relative to the atom before them.
+/// These small differences are packed into a number of bits that depends on an index into this
+/// table. The `smallidx` in the prelude of the compressed positions selects the initial entry, and
+/// the index is adjusted as the stream is read or written. Each entry is roughly 2^(1/3) times
+/// the previous one, such that a step in the index changes the number of bits for three packed
+/// values by about one.
+///
+/// The entries before [`FIRSTIDX`] are zero and are never used.
 #[rustfmt::skip]
 pub const MAGICINTS: [i32; 73] = [
     0,        0,        0,       0,       0,       0,       0,       0,       0,       8,
@@ -21,6 +31,7 @@ pub const MAGICINTS: [i32; 73] = [
     1048576,  1321122,  1664510, 2097152, 2642245, 3329021, 4194304, 5284491, 6658042, 8388607,
     10568983, 13316085, 16777216
 ];
+/// The index of the first usable entry in [`MAGICINTS`].
 pub const FIRSTIDX: usize = 9; // Note that MAGICINTS[FIRSTIDX-1] == 0.
 
 /// The number of bytes that together form the prelude of `maxint`, `minint`, and `smallidx`.
@@ -32,6 +43,12 @@ pub const NBYTES_POSITIONS_PRELUDE: usize = 7 * 4;
 /// If successful, returns the number of compressed bytes that were read.
 ///
 /// `header_natoms` must be greater than or equal to the number of `positions`.
+///
+/// # Errors
+///
+/// If the `precision` is not finite and positive, an error of kind
+/// [`io::ErrorKind::InvalidData`] is returned before anything is decoded. Any reader errors are
+/// passed through.
 pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
     file: &'r mut R,
     header_natoms: usize,
@@ -41,6 +58,185 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
     atom_selection: &AtomSelection,
     magic: Magic,
 ) -> io::Result<usize> {
+    read_compressed_positions_scaled::<B, R>(
+        file,
+        header_natoms,
+        positions,
+        precision,
+        1.0,
+        scratch,
+        atom_selection,
+        magic,
+    )
+}
+
+#[inline]
+/// The low-level decompression routine, with the positions multiplied by `scale`.
+///
+/// The `scale` is folded into the factor that converts the integer positions to floats, so the
+/// conversion to another unit costs nothing beyond the decompression itself. A `scale` of 10.0
+/// gives positions in Ångström rather than nanometers.
+///
+/// See [`read_compressed_positions`].
+#[allow(clippy::too_many_arguments)]
+pub fn read_compressed_positions_scaled<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
+    file: &'r mut R,
+    header_natoms: usize,
+    positions: &mut [f32],
+    precision: f32,
+    scale: f32,
+    scratch: &'s mut Vec<u8>,
+    atom_selection: &AtomSelection,
+    magic: Magic,
+) -> io::Result<usize> {
+    decode_compressed_positions::<B, R>(
+        file,
+        header_natoms,
+        positions,
+        precision,
+        scale,
+        scratch,
+        atom_selection,
+        magic,
+    )
+    .map(|stats| stats.used)
+}
+
+/// The decompression routine behind [`read_compressed_positions_scaled`], which returns the
+/// [`BufferStats`] of the read.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn decode_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
+    file: &'r mut R,
+    header_natoms: usize,
+    positions: &mut [f32],
+    precision: f32,
+    scale: f32,
+    scratch: &'s mut Vec<u8>,
+    atom_selection: &AtomSelection,
+    magic: Magic,
+) -> io::Result<BufferStats> {
+    validate_precision(precision)?;
+    let factor = precision.recip() * scale;
+    let prelude = Prelude::read(file)?;
+    decode_positions::<B, R, f32>(
+        file,
+        header_natoms,
+        positions,
+        prelude,
+        scratch,
+        atom_selection,
+        magic,
+        |coord| coord.map(|v| v as f32 * factor),
+    )
+}
+
+/// The decompression routine for quantized positions.
+///
+/// Rather than converting the decoded integer positions to floats, they are stored relative to
+/// the smallest integer position in the frame. If successful, returns that smallest position,
+/// which is to be added back before dividing by the precision to recover the positions.
+///
+/// # Errors
+///
+/// If the integer positions in the frame span a range that does not fit in an `i16`, an error of
+/// the kind [`io::ErrorKind::InvalidData`] is returned.
+pub fn read_quantized_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
+    file: &'r mut R,
+    header_natoms: usize,
+    positions: &mut [i16],
+    scratch: &'s mut Vec<u8>,
+    atom_selection: &AtomSelection,
+    magic: Magic,
+) -> io::Result<[i32; 3]> {
+    let prelude = Prelude::read(file)?;
+    let minint = prelude.minint;
+    let range = (0..3)
+        .map(|i| prelude.maxint[i] as i64 - minint[i] as i64)
+        .max()
+        .unwrap_or_default();
+    if range > i16::MAX as i64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the positions span {range} grid points, which is more than can be quantized to \
+                an i16 ({})",
+                i16::MAX
+            ),
+        ));
+    }
+    decode_positions::<B, R, i16>(
+        file,
+        header_natoms,
+        positions,
+        prelude,
+        scratch,
+        atom_selection,
+        magic,
+        |coord| [0, 1, 2].map(|i| (coord[i] - minint[i]) as i16),
+    )?;
+    Ok(minint)
+}
+
+/// Checks that a `precision` read from a frame is finite and positive.
+///
+/// The positions are divided by the precision, so any other value, such as the zero of a
+/// corrupted frame, would silently turn them into infinities or NaNs.
+pub(crate) fn validate_precision(precision: f32) -> io::Result<()> {
+    if precision.is_finite() && precision > 0.0 {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid precision {precision}, which must be finite and positive"),
+    ))
+}
+
+/// The values that precede the compressed positions.
+pub(crate) struct Prelude {
+    pub(crate) minint: [i32; 3],
+    pub(crate) maxint: [i32; 3],
+    pub(crate) smallidx: u32,
+}
+
+impl Prelude {
+    pub(crate) fn read<R: Read>(file: &mut R) -> io::Result<Self> {
+        // The elements of an array expression are evaluated from left to right, so the values are
+        // read in order. This stands in for the unstable `array_try_map`, without allocating.
+        let minint = [read_i32(file)?, read_i32(file)?, read_i32(file)?];
+        let maxint = [read_i32(file)?, read_i32(file)?, read_i32(file)?];
+        let smallidx = read_u32(file)?;
+        assert_eq!(
+            std::mem::size_of_val(&minint)
+                + std::mem::size_of_val(&maxint)
+                + std::mem::size_of_val(&smallidx),
+            NBYTES_POSITIONS_PRELUDE
+        );
+        Ok(Self {
+            minint,
+            maxint,
+            smallidx,
+        })
+    }
+}
+
+/// Decodes the compressed positions after the prelude, storing each decoded integer position
+/// into `positions` through `convert`.
+///
+/// If successful, returns the [`BufferStats`] of the read, of which `used` is the number of
+/// compressed bytes that were read during decompression.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+fn decode_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read, T: Copy>(
+    file: &'r mut R,
+    header_natoms: usize,
+    positions: &mut [T],
+    prelude: Prelude,
+    scratch: &'s mut Vec<u8>,
+    atom_selection: &AtomSelection,
+    magic: Magic,
+    convert: impl Fn([i32; 3]) -> [T; 3],
+) -> io::Result<BufferStats> {
     let natoms_out = {
         let n = positions.len();
         assert_eq!(n % 3, 0, "the length of `positions` must be divisible by 3");
@@ -59,28 +255,11 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
         )
     }
 
-    let invprecision = precision.recip();
-
-    // TODO: Once `array_try_map` is stable, both of these inits can be cleaned up significantly.
-    let minint = [0; 3]
-        .map(|_| read_i32(file))
-        .into_iter()
-        .collect::<io::Result<Vec<_>>>()?
-        .try_into()
-        .unwrap();
-    let maxint = [0; 3]
-        .map(|_| read_i32(file))
-        .into_iter()
-        .collect::<io::Result<Vec<_>>>()?
-        .try_into()
-        .unwrap();
-    let smallidx = read_u32(file)?;
-    assert_eq!(
-        std::mem::size_of_val(&minint)
-            + std::mem::size_of_val(&maxint)
-            + std::mem::size_of_val(&smallidx),
-        NBYTES_POSITIONS_PRELUDE
-    );
+    let Prelude {
+        minint,
+        maxint,
+        smallidx,
+    } = prelude;
     let mut smallidx = smallidx as usize;
     assert!(smallidx < MAGICINTS.len());
 
@@ -114,7 +293,7 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
         let Some(mut position) = positions
             .chunks_exact_mut(3)
             .nth(write_idx)
-            .map(|pos| -> &mut [f32; 3] { pos.try_into().unwrap() })
+            .map(|pos| -> &mut [T; 3] { pos.try_into().unwrap() })
         else {
             break 'decompress;
         };
@@ -139,7 +318,7 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
                     None => break 'decompress,
                     Some(false) => {}
                     Some(true) => {
-                        *$position = $coord.map(|v| v as f32 * invprecision);
+                        *$position = convert($coord);
                         $write_idx += 1;
                     }
                 }
@@ -153,6 +332,14 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
             };
         }
 
+        // A set flag announces a new run length. Otherwise, the previous run length is reused.
+        //
+        // The run length and the change in `smallidx` are packed together into a 5-bit value
+        // `v = run + is_smaller + 1`. Since `run` is a multiple of 3 and `is_smaller` is one of -1,
+        // 0, or 1, the remainder `v % 3` recovers `is_smaller + 1`. This leaves `run` as a
+        // non-negative multiple of 3 of at most 30, which is the number of coordinate values in
+        // the run. Encoders (including ours) write at most 8 atoms per run, but up to 10 atoms can
+        // be represented and are accepted here.
         let flag: bool = decodebits::<u8, R>(&mut buffer, &mut state, 1) > 0;
         let mut is_smaller = 0;
         if flag {
@@ -161,12 +348,15 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
             run -= is_smaller;
             is_smaller -= 1;
         }
+        debug_assert!(
+            (0..=30).contains(&run) && run % 3 == 0,
+            "invalid run length {run}"
+        );
+        debug_assert!((-1..=1).contains(&is_smaller));
         if run > 0 {
-            // TODO: Investigate whether this is something we can just remove. I believe it may be.
-            // if write_idx * 3 + run as usize > n {
-            //     eprintln!("may attempt to write a run beyond the positions buffer");
-            //     dbg!(write_idx, run, n, write_idx * 3 + run as usize);
-            // }
+            // A run may extend beyond the end of the positions buffer or the selection. That is
+            // fine, since writing a position checks both and ends the decompression when either
+            // is exhausted.
 
             // Let's read the next coordinate.
             coord.fill(0);
@@ -238,11 +428,10 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
         )
     }
 
-    // The number of bytes that were read during decompression.
-    let nbytes = buffer.tell();
+    let stats = buffer.stats();
     buffer.finish()?;
 
-    Ok(nbytes)
+    Ok(stats)
 }
 
 #[inline]
@@ -264,40 +453,94 @@ pub(crate) fn read_f32s<R: Read>(file: &mut R, buf: &mut [f32]) -> io::Result<()
     Ok(())
 }
 
-// FIXME: These read_* functions are prime targets for a macro tbh.
-pub(crate) fn read_f32<R: Read>(file: &mut R) -> io::Result<f32> {
-    let mut buf: [u8; 4] = Default::default();
-    file.read_exact(&mut buf)?;
-    Ok(f32::from_be_bytes(buf))
-}
-
-pub(crate) fn read_i32<R: Read>(file: &mut R) -> io::Result<i32> {
-    let mut buf: [u8; 4] = Default::default();
-    file.read_exact(&mut buf)?;
-    Ok(i32::from_be_bytes(buf))
-}
-
-pub(crate) fn read_u32<R: Read>(file: &mut R) -> io::Result<u32> {
-    let mut buf: [u8; 4] = Default::default();
-    file.read_exact(&mut buf)?;
-    Ok(u32::from_be_bytes(buf))
+/// Generates a function that reads a big-endian scalar of some type.
+macro_rules! read_be {
+    ($name:ident, $t:ty) => {
+        #[doc = concat!("Read a big-endian `", stringify!($t), "`.")]
+        ///
+        /// # Errors
+        ///
+        /// This function will pass through any reader errors.
+        pub fn $name<R: Read>(file: &mut R) -> io::Result<$t> {
+            let mut buf = [0; std::mem::size_of::<$t>()];
+            file.read_exact(&mut buf)?;
+            Ok(<$t>::from_be_bytes(buf))
+        }
+    };
 }
 
-pub(crate) fn read_u64<R: Read>(file: &mut R) -> io::Result<u64> {
-    let mut buf: [u8; 8] = Default::default();
-    file.read_exact(&mut buf)?;
-    Ok(u64::from_be_bytes(buf))
-}
+read_be!(read_f32, f32);
+read_be!(read_i32, i32);
+read_be!(read_u32, u32);
+read_be!(read_u64, u64);
 
+/// Read the number of bytes in the upcoming compressed data stream.
+///
+/// The width of this value depends on the `magic` number. For [`Magic::Xtc1995`] it is a `u32`,
+/// and for [`Magic::Xtc2023`] it is a `u64`, which allows for frames larger than 4 GiB.
+///
+/// # Errors
+///
+/// If the number of bytes cannot be represented as a `usize` on this platform, an error of the
+/// kind [`io::ErrorKind::InvalidData`] is returned. Any reader errors are passed through.
 pub fn read_nbytes<R: Read>(reader: &mut R, magic: Magic) -> io::Result<usize> {
     let nbytes = match magic {
-        Magic::Xtc1995 => read_u32(reader)? as usize,
-        Magic::Xtc2023 => read_u64(reader)? as usize,
+        Magic::Xtc1995 => read_u32(reader)? as u64,
+        Magic::Xtc2023 => read_u64(reader)?,
     };
-    Ok(nbytes)
+    usize::try_from(nbytes).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("could not read the number of compressed bytes ({nbytes}): {err}"),
+        )
+    })
 }
 
-fn calc_sizeint(
+/// Read an xdr opaque block into `buf`.
+///
+/// An opaque block consists of a byte count, followed by that number of bytes. The count is stored
+/// as a `u32` or `u64`, depending on the `magic`. The bytes are padded to a multiple of 4 bytes.
+///
+/// After reading, `buf` contains the bytes of the block followed by its padding, which is read
+/// from the reader as-is, and the reader is positioned right after the block. If successful,
+/// returns the byte count, excluding padding.
+///
+/// The byte count is not trusted for allocation. The buffer only grows with the bytes that are
+/// actually present in the reader, such that a corrupt count cannot cause an enormous allocation.
+///
+/// # Errors
+///
+/// If the reader ends before the end of the block, an error of the kind
+/// [`io::ErrorKind::UnexpectedEof`] is returned. Any other reader errors are passed through.
+pub fn read_opaque<R: Read>(reader: &mut R, magic: Magic, buf: &mut Vec<u8>) -> io::Result<usize> {
+    /// The largest number of bytes that is allocated up front, before they have been read.
+    const PREALLOC: usize = 1 << 24;
+
+    let count = read_nbytes(reader, magic)?;
+    let len = count.saturating_add(padding(count));
+    buf.clear();
+    buf.reserve(usize::min(len, PREALLOC));
+    let read = reader.by_ref().take(len as u64).read_to_end(buf)?;
+    if read < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("the opaque block spans {len} bytes, but only {read} bytes could be read"),
+        ));
+    }
+    Ok(count)
+}
+
+/// Determine the sizes and number of bits needed to store integer positions within the bounds
+/// of `minint` and `maxint`.
+///
+/// This is used for the atoms in the compressed positions that are stored by their full value,
+/// rather than relative to a neighbour. The range of each dimension is written into `sizeint`.
+///
+/// If all three ranges are small enough, they are packed together, and the total number of bits
+/// for the three packed values is returned (see [`sizeofints`]). Otherwise, the number of bits for
+/// each dimension separately is written into `bitsizeint` (see [`sizeofint`]), and 0 is returned
+/// to flag that the values are stored separately.
+pub fn calc_sizeint(
     minint: [i32; 3],
     maxint: [i32; 3],
     sizeint: &mut [u32; 3],
@@ -320,8 +563,9 @@ fn calc_sizeint(
     sizeofints(*sizeint)
 }
 
+/// Returns the number of bits needed to represent `size`.
 #[inline]
-const fn sizeofint(size: u32) -> u32 {
+pub const fn sizeofint(size: u32) -> u32 {
     let mut n = 1;
     let mut nbits = 0;
 
@@ -333,7 +577,13 @@ const fn sizeofint(size: u32) -> u32 {
     nbits
 }
 
-fn sizeofints(sizes: [u32; 3]) -> u32 {
+/// Returns the number of bits needed to represent the product of `sizes`.
+///
+/// This is the number of bits used to store three values packed together into a single large
+/// integer as `(x * sizes[1] + y) * sizes[2] + z`, where each value is smaller than its
+/// corresponding entry in `sizes`. Packing the values like this takes fewer bits than storing
+/// each of them separately. The product may exceed the range of a `u32`.
+pub fn sizeofints(sizes: [u32; 3]) -> u32 {
     let mut nbytes = 1;
     let mut bytes = [0u8; 32];
     bytes[0] = 1;
@@ -780,4 +1030,58 @@ mod tests {
             Ok(())
         }
     }
+
+    #[test]
+    fn sizeofint_values() {
+        assert_eq!(sizeofint(0), 0);
+        assert_eq!(sizeofint(1), 1);
+        assert_eq!(sizeofint(7), 3);
+        assert_eq!(sizeofint(8), 4);
+        assert_eq!(sizeofint(255), 8);
+        assert_eq!(sizeofint(256), 9);
+        assert_eq!(sizeofint(u32::MAX), 32);
+    }
+
+    #[test]
+    fn sizeofints_matches_product() {
+        for sizes in [
+            [1, 1, 1],
+            [2, 2, 2],
+            [3, 5, 7],
+            [1000, 1000, 1000],
+            [255, 256, 257],
+        ] {
+            let product = sizes.iter().product();
+            assert_eq!(sizeofints(sizes), sizeofint(product), "{sizes:?}");
+        }
+        // The product of these sizes exceeds a u32.
+        assert_eq!(sizeofints([0xffffff, 0xffffff, 0xffffff]), 72);
+    }
+
+    #[test]
+    fn calc_sizeint_small_and_large() {
+        let mut sizeint = [0; 3];
+        let mut bitsizeint = [0; 3];
+        let bitsize = calc_sizeint([0, -10, 5], [999, 989, 1004], &mut sizeint, &mut bitsizeint);
+        assert_eq!(sizeint, [1000, 1000, 1000]);
+        assert_eq!(bitsize, 30);
+        assert_eq!(bitsizeint, [0, 0, 0]);
+
+        let bitsize = calc_sizeint([0, 0, 0], [0x1000000, 9, 99], &mut sizeint, &mut bitsizeint);
+        assert_eq!(sizeint, [0x1000001, 10, 100]);
+        assert_eq!(bitsize, 0, "large sizes are flagged by a bitsize of 0");
+        assert_eq!(bitsizeint, [25, 4, 7]);
+    }
+
+    #[test]
+    fn read_nbytes_width() -> io::Result<()> {
+        let bytes = [0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02];
+        assert_eq!(read_nbytes(&mut bytes.as_slice(), Magic::Xtc1995)?, 1);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            read_nbytes(&mut bytes.as_slice(), Magic::Xtc2023)?,
+            0x0000_0001_0000_0002
+        );
+        Ok(())
+    }
 }
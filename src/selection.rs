@@ -1,5 +1,20 @@
 use std::collections::BTreeSet;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU32, NonZeroU64};
+
+/// How an [`AtomSelection`] is applied when reading a frame.
+///
+/// See [`XTCReader::read_frame_with_strategy`](crate::XTCReader::read_frame_with_strategy).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Skip the unselected atoms while decoding, and stop decoding after the last selected atom.
+    #[default]
+    DuringDecode,
+    /// Decode all atoms in the frame, and gather the selected atoms afterwards.
+    ///
+    /// This was not faster than [`SelectionStrategy::DuringDecode`] for any selection we have
+    /// measured. It serves as a straightforward reference to check the selection logic against.
+    AfterDecode,
+}
 
 // Invariant: The selection is only valid if the frame it reads them into is appropriately sized.
 // It is assumed that the frame is correctly sized, i.e.,
@@ -23,6 +38,22 @@ pub enum AtomSelection {
     /// If the value of the mask at an index `n` is `true`, the position at that same index `n` is
     /// included in the selection.
     Mask(Vec<bool>), // TODO: Bitmap optimization?
+    /// A sorted list of the unique indices of the positions to include in the selection.
+    ///
+    /// This is useful for a few atoms with high indices, for which a [`AtomSelection::Mask`] would
+    /// be needlessly large. See [`AtomSelection::from_index_list`].
+    ///
+    /// # Invariant
+    ///
+    /// The indices must be strictly increasing. Lookups search the list, and its last value is
+    /// taken as the bound of the selection, so unsorted or duplicate indices give wrong selections.
+    /// [`AtomSelection::from_index_list`] sorts and deduplicates any list of indices. In debug
+    /// builds, the invariant is checked when the size of the selection is determined.
+    IndexList(Vec<u32>),
+    /// Every `step`th position, starting at the index `start`, up to the end of the frame.
+    ///
+    /// This selects a regular subsample of the atoms without building a mask for them.
+    Stride { start: u32, step: NonZeroU32 },
     /// Index of the position right after the last position to be included in the selection.
     ///
     /// This is an exclusive stop value, such that a value of 8 will mean that a total of 7 atoms
@@ -31,12 +62,28 @@ pub enum AtomSelection {
 }
 
 impl AtomSelection {
-    /// Create a boolean mask from a list of indices.
+    /// The factor by which the largest index must exceed the number of indices before
+    /// [`AtomSelection::from_index_list`] stores an [`AtomSelection::IndexList`] rather than a
+    /// [`AtomSelection::Mask`].
+    const SPARSE_FACTOR: usize = 64;
+
+    /// Create a selection from a list of indices.
+    ///
+    /// The indices may be unsorted and contain duplicates. Usually, a boolean mask is created.
+    /// If the indices are sparse with respect to the largest index, a sorted
+    /// [`AtomSelection::IndexList`] is created instead, to avoid allocating a large mask for a few
+    /// atoms.
     pub fn from_index_list(indices: &[u32]) -> Self {
         let max = match indices.iter().max() {
             Some(&max) => max as usize + 1,
             None => return Self::Mask(Vec::new()),
         };
+        if max > indices.len() * Self::SPARSE_FACTOR {
+            let mut list = indices.to_vec();
+            list.sort_unstable();
+            list.dedup();
+            return Self::IndexList(list);
+        }
         let mut mask = Vec::with_capacity(max);
         mask.resize(max, false);
 
@@ -47,6 +94,28 @@ impl AtomSelection {
         Self::Mask(mask)
     }
 
+    /// Create a selection of the atoms in some ranges of residues.
+    ///
+    /// The `residue_to_atoms` map holds the range of atom indices for each residue, such that the
+    /// atoms of residue `i` are `residue_to_atoms[i]`. The atoms of all `residues` are combined
+    /// into a selection through [`AtomSelection::from_index_list`], so the residue ranges may
+    /// overlap and need not be sorted.
+    ///
+    /// # Panics
+    ///
+    /// If a residue range extends beyond the end of the `residue_to_atoms` map.
+    pub fn from_residue_ranges(
+        residues: &[std::ops::Range<u32>],
+        residue_to_atoms: &[std::ops::Range<u32>],
+    ) -> Self {
+        let indices: Vec<u32> = residues
+            .iter()
+            .flat_map(|residues| &residue_to_atoms[residues.start as usize..residues.end as usize])
+            .flat_map(|atoms| atoms.clone())
+            .collect();
+        Self::from_index_list(&indices)
+    }
+
     /// Determine whether some index `idx` is included in this [`AtomSelection`].
     ///
     /// Will return [`None`] once the index is beyond the scope of this `AtomSelection`.
@@ -54,6 +123,19 @@ impl AtomSelection {
         match self {
             AtomSelection::All => Some(true),
             AtomSelection::Mask(mask) => mask.get(idx).copied(),
+            AtomSelection::IndexList(list) => {
+                // This is called for every atom, so the invariant is only checked once per frame,
+                // through `natoms_selected` and `last`.
+                if *list.last()? as usize >= idx {
+                    Some(list.binary_search(&(idx as u32)).is_ok())
+                } else {
+                    None
+                }
+            }
+            AtomSelection::Stride { start, step } => {
+                let start = *start as usize;
+                Some(idx >= start && (idx - start) % step.get() as usize == 0)
+            }
             AtomSelection::Until(until) => {
                 if idx <= *until as usize {
                     Some(true)
@@ -72,11 +154,15 @@ impl AtomSelection {
     /// account.
     pub fn last(&self) -> Option<usize> {
         match self {
-            AtomSelection::All => None,
+            AtomSelection::All | AtomSelection::Stride { .. } => None,
             AtomSelection::Mask(mask) => match mask.iter().rposition(|&entry| entry) {
                 Some(n) => Some(n + 1),
                 None => Some(0),
             },
+            AtomSelection::IndexList(list) => {
+                debug_assert_index_list(list);
+                Some(list.last().map_or(0, |&n| n as usize + 1))
+            }
             AtomSelection::Until(until) => Some(*until as usize),
         }
     }
@@ -84,7 +170,7 @@ impl AtomSelection {
     /// The number of positions selected by this [`AtomSelection`].
     ///
     /// This function will return at most `frame_natoms`.
-    pub(crate) fn natoms_selected(&self, frame_natoms: usize) -> usize {
+    pub fn natoms_selected(&self, frame_natoms: usize) -> usize {
         match self {
             AtomSelection::All => frame_natoms,
             AtomSelection::Mask(mask) => mask
@@ -92,6 +178,18 @@ impl AtomSelection {
                 .take(frame_natoms)
                 .filter(|&&include| include)
                 .count(),
+            AtomSelection::IndexList(list) => {
+                debug_assert_index_list(list);
+                list.partition_point(|&idx| (idx as usize) < frame_natoms)
+            }
+            AtomSelection::Stride { start, step } => {
+                let start = *start as usize;
+                if frame_natoms > start {
+                    (frame_natoms - start - 1) / step.get() as usize + 1
+                } else {
+                    0
+                }
+            }
             AtomSelection::Until(until) => usize::min(*until as usize, frame_natoms),
         }
     }
@@ -101,7 +199,8 @@ impl AtomSelection {
     /// This function will return at most `frame_natoms`.
     ///
     /// Note that the return value for this function will only differ from
-    /// [`AtomSelection::natoms_selected`] for the `AtomSelection::Mask` variant.
+    /// [`AtomSelection::natoms_selected`] for the `AtomSelection::Mask` and
+    /// `AtomSelection::IndexList` variants.
     pub(crate) fn reading_limit(&self, frame_natoms: usize) -> usize {
         // TODO: Verify that the natoms used here is well-conceived: it needs to be the number of
         // atoms that reside in the total compressed frame, but not the natoms we eventually want
@@ -112,6 +211,15 @@ impl AtomSelection {
     }
 }
 
+/// Check that the indices of an [`AtomSelection::IndexList`] are strictly increasing.
+fn debug_assert_index_list(list: &[u32]) {
+    debug_assert!(
+        list.windows(2).all(|pair| pair[0] < pair[1]),
+        "the indices of an index list must be sorted and unique, use \
+        `AtomSelection::from_index_list` to create one from any indices"
+    );
+}
+
 /// A selection of [`Frame`]s.
 #[derive(Debug, Default, Clone)]
 pub enum FrameSelection {
@@ -165,6 +273,29 @@ impl FrameSelection {
             }
         }
     }
+
+    /// Returns the number of frames this [`FrameSelection`] yields from a trajectory of
+    /// `total_frames` frames.
+    ///
+    /// Indices beyond the end of the trajectory are not counted, so this is exactly the number of
+    /// frames a read of this selection produces. This can be used to allocate an output buffer of
+    /// the right size up front. Note that `total_frames` may also be the number of offsets found
+    /// with [`FrameSelection::until`], since no selected frames lie beyond it.
+    pub fn estimated_count(&self, total_frames: usize) -> usize {
+        match self {
+            FrameSelection::All => total_frames,
+            FrameSelection::Range(range) => {
+                let total = total_frames as u64;
+                let stop = range.end.map_or(total, |end| u64::min(end, total));
+                if stop > range.start {
+                    ((stop - range.start - 1) / range.step + 1) as usize
+                } else {
+                    0
+                }
+            }
+            FrameSelection::FrameList(list) => list.range(..total_frames).count(),
+        }
+    }
 }
 
 /// A selection of [`Frame`](super::Frame)s to be read from an [`XTCReader`](super::XTCReader).
@@ -181,7 +312,7 @@ impl FrameSelection {
 ///
 /// An instance where `start` > `end` is a valid `Selection`, but it will not make much sense,
 /// since the `Selection` will be understood to produce zero steps. This case will trigger a
-/// `debug_assert`.
+/// `debug_assert`. Use [`Range::try_new`] to reject it instead.
 #[derive(Debug, Clone, Copy)]
 pub struct Range {
     /// The `start` of a [`Selection`] is always bounded, and is zero by default.
@@ -190,7 +321,8 @@ pub struct Range {
     ///
     /// In case the end is unbounded ([`None`]), a `Selection` instructs the `XTCReader` to just
     /// read up to and including the last frame. If it is bounded by [`Some`] value, the frames up
-    /// to that index will be read. So, when `end` is bounded, it is an exclusive bound.
+    /// to that index will be read. So, when `end` is bounded, it is an exclusive bound, just like
+    /// the `stop` of a Python slice.
     pub end: Option<u64>,
     /// The `step` describes the number of frames that passed in each stride.
     ///
@@ -199,6 +331,26 @@ pub struct Range {
     pub step: NonZeroU64,
 }
 
+/// The error for a [`Range`] whose `start` exceeds its `end`, as returned by [`Range::try_new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl std::fmt::Display for InvalidRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { start, end } = self;
+        write!(
+            f,
+            "the start of a range ({start}) may not exceed its end ({end}), since it would select \
+            no frames"
+        )
+    }
+}
+
+impl std::error::Error for InvalidRange {}
+
 impl Range {
     pub fn new(start: Option<u64>, end: Option<u64>, step: Option<NonZeroU64>) -> Self {
         let mut sel = Self {
@@ -223,6 +375,42 @@ impl Range {
         sel
     }
 
+    /// Create a new [`Range`], rejecting a `start` that exceeds a bounded `end`.
+    ///
+    /// Such a range would select no frames at all, which is more likely a mistake than an
+    /// intention. Otherwise, this is the same as [`Range::new`].
+    ///
+    /// # Errors
+    ///
+    /// If `start` > `end`, an [`InvalidRange`] is returned.
+    pub fn try_new(
+        start: Option<u64>,
+        end: Option<u64>,
+        step: Option<NonZeroU64>,
+    ) -> Result<Self, InvalidRange> {
+        let start_value = start.unwrap_or_default();
+        match end {
+            Some(end) if start_value > end => Err(InvalidRange {
+                start: start_value,
+                end,
+            }),
+            _ => Ok(Self::new(start, end, step)),
+        }
+    }
+
+    /// Create a new [`Range`] where a bounded `end` is _inclusive_, like `start..=end`.
+    ///
+    /// This is convenient when translating from tools that treat the last index of a range as
+    /// part of it. Internally, the inclusive `end` is stored as the equivalent exclusive bound, so
+    /// `Range::new_inclusive(Some(3), Some(13), None)` is the same as
+    /// `Range::new(Some(3), Some(14), None)`.
+    pub fn new_inclusive(start: Option<u64>, end: Option<u64>, step: Option<NonZeroU64>) -> Self {
+        // An inclusive end at u64::MAX cannot be represented as an exclusive bound, but it is
+        // unbounded for all intents and purposes.
+        let end = end.and_then(|end| end.checked_add(1));
+        Self::new(start, end, step)
+    }
+
     /// If known, return whether some index is included in this range.
     pub fn is_included(&self, idx: u64) -> Option<bool> {
         if let Some(end) = self.end {
@@ -271,7 +459,7 @@ mod tests {
     mod frame {
         use std::num::NonZeroU64;
 
-        use super::{FrameSelection, Range};
+        use super::{FrameSelection, InvalidRange, Range};
 
         #[test]
         fn zero_selection() {
@@ -343,6 +531,104 @@ mod tests {
             }
         }
 
+        #[test]
+        fn range_try_new() {
+            let range = Range::try_new(Some(3), Some(14), NonZeroU64::new(2)).unwrap();
+            let expected = Range::new(Some(3), Some(14), NonZeroU64::new(2));
+            for idx in 0..100 {
+                assert_eq!(range.is_included(idx), expected.is_included(idx));
+            }
+
+            // Empty and open-ended ranges are fine.
+            assert!(Range::try_new(Some(5), Some(5), None).is_ok());
+            assert!(Range::try_new(Some(500), None, None).is_ok());
+            assert!(Range::try_new(None, Some(0), None).is_ok());
+
+            let err = Range::try_new(Some(100), Some(50), None).unwrap_err();
+            assert_eq!(
+                err,
+                InvalidRange {
+                    start: 100,
+                    end: 50
+                }
+            );
+        }
+
+        #[test]
+        fn estimated_count() {
+            let count = |s: &FrameSelection, total: usize| {
+                (0..total)
+                    .map_while(|idx| s.is_included(idx))
+                    .filter(|&included| included)
+                    .count()
+            };
+            let selections = [
+                FrameSelection::All,
+                FrameSelection::Range(Range::new(None, None, None)),
+                FrameSelection::Range(Range::new(Some(3), Some(14), NonZeroU64::new(4))),
+                FrameSelection::Range(Range::new(Some(3), None, NonZeroU64::new(5))),
+                FrameSelection::Range(Range::new(Some(50), None, None)),
+                FrameSelection::Range(Range::new(None, Some(0), None)),
+                FrameSelection::framelist_from_iter([1, 7, 8, 30]),
+                FrameSelection::FrameList(Default::default()),
+            ];
+            for s in &selections {
+                for total in [0, 1, 7, 8, 13, 14, 15, 31, 100] {
+                    assert_eq!(s.estimated_count(total), count(s, total), "{s:?}, {total}");
+                }
+            }
+        }
+
+        #[test]
+        fn range_inclusive() {
+            let exclusive = Range::new(Some(3), Some(14), None);
+            let inclusive = Range::new_inclusive(Some(3), Some(13), None);
+            for idx in 0..100 {
+                assert_eq!(inclusive.is_included(idx), exclusive.is_included(idx));
+            }
+            assert_eq!(inclusive.is_included(13), Some(true));
+            assert!(inclusive.is_included(14).is_none());
+
+            let single = Range::new_inclusive(Some(5), Some(5), None);
+            let included: Vec<_> = (0..10)
+                .filter(|&i| single.is_included(i) == Some(true))
+                .collect();
+            assert_eq!(included, [5]);
+
+            let stepped = Range::new_inclusive(None, Some(12), NonZeroU64::new(4));
+            let included: Vec<_> = (0..20)
+                .filter(|&i| stepped.is_included(i) == Some(true))
+                .collect();
+            assert_eq!(included, [0, 4, 8, 12]);
+
+            let max = Range::new_inclusive(None, Some(u64::MAX), None);
+            assert!(max.end.is_none());
+            assert_eq!(max.is_included(u64::MAX), Some(true));
+        }
+
+        /// Stepping counts from `start`, also when `start` is not a multiple of `step`.
+        #[test]
+        fn range_unaligned_start_step() {
+            for (start, step) in [(501, 5), (1, 2), (7, 3), (13, 17), (99, 100)] {
+                let range = Range::new(Some(start), Some(1000), NonZeroU64::new(step));
+                for idx in 0..1000 {
+                    let expected = idx >= start && (idx - start) % step == 0;
+                    assert_eq!(
+                        range.is_included(idx),
+                        Some(expected),
+                        "index {idx} for start {start} and step {step}"
+                    );
+                }
+                assert!(range.is_included(1000).is_none());
+            }
+
+            let s = Range::new(Some(501), None, NonZeroU64::new(5));
+            let included: Vec<_> = (0..530)
+                .filter(|&i| s.is_included(i) == Some(true))
+                .collect();
+            assert_eq!(included, [501, 506, 511, 516, 521, 526]);
+        }
+
         #[test]
         fn until() {
             let n = 100;
@@ -370,6 +656,38 @@ mod tests {
 
     mod atom {
         use super::AtomSelection;
+        use std::num::NonZeroU32;
+
+        #[test]
+        fn residue_ranges() {
+            // Ten residues of three atoms each, followed by a residue of a single atom.
+            let residue_to_atoms: Vec<_> = (0..10)
+                .map(|r| r * 3..r * 3 + 3)
+                .chain(std::iter::once(30..31))
+                .collect();
+
+            let selection = AtomSelection::from_residue_ranges(&[1..3, 10..11], &residue_to_atoms);
+            let selected: Vec<_> = (0..40)
+                .filter(|&idx| selection.is_included(idx).unwrap_or_default())
+                .collect();
+            assert_eq!(selected, [3, 4, 5, 6, 7, 8, 30]);
+            assert_eq!(selection.natoms_selected(31), 7);
+
+            // Overlapping and unsorted ranges select each atom once.
+            let overlapping = AtomSelection::from_residue_ranges(&[2..4, 0..3], &residue_to_atoms);
+            assert_eq!(overlapping.natoms_selected(31), 12);
+            assert_eq!(overlapping.last(), Some(12));
+
+            // Empty residue ranges select no atoms.
+            let empty = AtomSelection::from_residue_ranges(&[5..5, 8..8], &residue_to_atoms);
+            assert_eq!(empty.natoms_selected(31), 0);
+        }
+
+        #[test]
+        #[should_panic]
+        fn residue_ranges_out_of_bounds() {
+            AtomSelection::from_residue_ranges(&[0..1, 1..3], &[0..3, 3..6]);
+        }
 
         #[test]
         fn zero_selection() {
@@ -476,5 +794,67 @@ mod tests {
             let limit = steps.reading_limit(n);
             assert_eq!(limit, 91);
         }
+
+        #[test]
+        fn stride() {
+            let n = 100;
+            let stride = AtomSelection::Stride {
+                start: 3,
+                step: NonZeroU32::new(10).unwrap(),
+            };
+            let list =
+                AtomSelection::from_index_list(&(3..n as u32).step_by(10).collect::<Vec<_>>());
+            for idx in 0..n {
+                assert_eq!(
+                    stride.is_included(idx),
+                    list.is_included(idx).or(Some(false))
+                );
+            }
+            // A stride runs up to the end of the frame.
+            assert_eq!(stride.is_included(1003), Some(true));
+            assert_eq!(stride.is_included(1004), Some(false));
+            assert_eq!(stride.natoms_selected(n), 10);
+            assert_eq!(stride.natoms_selected(93), 9);
+            assert_eq!(stride.natoms_selected(94), 10);
+            assert_eq!(stride.natoms_selected(3), 0);
+            assert_eq!(stride.natoms_selected(4), 1);
+            assert_eq!(stride.reading_limit(n), n);
+        }
+
+        #[test]
+        fn sparse_index_list() {
+            let n = 2_000_000;
+            let sparse = AtomSelection::from_index_list(&[1_000_000, 5, 5, 2, 1_500_000]);
+            let AtomSelection::IndexList(list) = &sparse else {
+                panic!("a sparse selection should be stored as an index list, not {sparse:?}");
+            };
+            assert_eq!(list, &[2, 5, 1_000_000, 1_500_000]);
+
+            for idx in 0..=1_500_000 {
+                let expected = matches!(idx, 2 | 5 | 1_000_000 | 1_500_000);
+                assert_eq!(sparse.is_included(idx), Some(expected));
+            }
+            assert_eq!(sparse.is_included(1_500_001), None);
+            assert_eq!(sparse.natoms_selected(n), 4);
+            assert_eq!(sparse.natoms_selected(1_000_000), 2);
+            assert_eq!(sparse.reading_limit(n), 1_500_001);
+            assert_eq!(sparse.reading_limit(100), 100);
+
+            // Dense selections are still stored as a mask.
+            let dense = AtomSelection::from_index_list(&[5, 5, 2]);
+            assert!(matches!(dense, AtomSelection::Mask(_)));
+            for idx in 0..10 {
+                let mask = dense.is_included(idx);
+                let list = AtomSelection::IndexList(vec![2, 5]).is_included(idx);
+                assert_eq!(mask, list);
+            }
+        }
+
+        #[test]
+        #[cfg(debug_assertions)]
+        #[should_panic(expected = "must be sorted and unique")]
+        fn unsorted_index_list() {
+            AtomSelection::IndexList(vec![5, 1, 5]).natoms_selected(10);
+        }
     }
 }
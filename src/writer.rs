@@ -0,0 +1,921 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::reader::{calc_sizeint, FIRSTIDX, MAGICINTS};
+use crate::{padding, BoxVec, Frame, Header, Magic, Precision, XTCReader, SMALL_NATOMS_THRESHOLD};
+
+/// Generates a function that writes a big-endian scalar of some type.
+macro_rules! write_be {
+    ($name:ident, $t:ty) => {
+        #[doc = concat!("Write a big-endian `", stringify!($t), "`.")]
+        ///
+        /// # Errors
+        ///
+        /// This function will pass through any writer errors.
+        pub fn $name<W: Write>(writer: &mut W, value: $t) -> io::Result<()> {
+            writer.write_all(&value.to_be_bytes())
+        }
+    };
+}
+
+write_be!(write_f32, f32);
+write_be!(write_i32, i32);
+write_be!(write_u32, u32);
+write_be!(write_u64, u64);
+
+/// The largest absolute value a scaled coordinate may take before it can no longer be
+/// represented as an integer.
+const MAXABS: f64 = (i32::MAX - 2) as f64;
+
+/// The index of the largest entry in [`MAGICINTS`].
+const LASTIDX: usize = MAGICINTS.len() - 1;
+
+/// Bit-level writer for the compressed data stream.
+///
+/// This is the counterpart of the decoding state that is used when reading.
+#[derive(Default)]
+struct Encoder {
+    bytes: Vec<u8>,
+    lastbits: u32,
+    lastbyte: u32,
+}
+
+impl Encoder {
+    /// Append the lowest `nbits` bits of `num` to the stream.
+    fn sendbits(&mut self, mut nbits: u32, num: u32) {
+        while nbits >= 8 {
+            self.lastbyte = (self.lastbyte << 8) | ((num >> (nbits - 8)) & 0xff);
+            self.bytes.push((self.lastbyte >> self.lastbits) as u8);
+            nbits -= 8;
+        }
+        if nbits > 0 {
+            let mask = (1 << nbits) - 1; // A string of ones that is nbits long.
+            self.lastbyte = (self.lastbyte << nbits) | (num & mask);
+            self.lastbits += nbits;
+            if self.lastbits >= 8 {
+                self.lastbits -= 8;
+                self.bytes.push((self.lastbyte >> self.lastbits) as u8);
+            }
+        }
+    }
+
+    /// Pack three integers into a single number of `nbits` bits and append it to the stream.
+    ///
+    /// Each value in `nums` must be smaller than its corresponding value in `sizes`.
+    fn sendints(&mut self, nbits: u32, sizes: [u32; 3], nums: [u32; 3]) {
+        let mut bytes = [0u32; 32];
+        let mut nbytes = 0;
+
+        let mut tmp = nums[0];
+        loop {
+            bytes[nbytes] = tmp & 0xff;
+            nbytes += 1;
+            tmp >>= 8;
+            if tmp == 0 {
+                break;
+            }
+        }
+
+        for (&size, &num) in sizes.iter().zip(&nums).skip(1) {
+            debug_assert!(num < size, "the value {num} does not fit in size {size}");
+            // Multiply the bytes we have so far by the size, and add the next number.
+            let mut tmp = num as u64;
+            let mut bytecount = 0;
+            while bytecount < nbytes {
+                tmp += bytes[bytecount] as u64 * size as u64;
+                bytes[bytecount] = (tmp & 0xff) as u32;
+                tmp >>= 8;
+                bytecount += 1;
+            }
+            while tmp != 0 {
+                bytes[bytecount] = (tmp & 0xff) as u32;
+                bytecount += 1;
+                tmp >>= 8;
+            }
+            nbytes = bytecount;
+        }
+
+        let nbytes_bits = nbytes as u32 * 8;
+        if nbits >= nbytes_bits {
+            for &byte in &bytes[..nbytes] {
+                self.sendbits(8, byte);
+            }
+            self.sendbits(nbits - nbytes_bits, 0);
+        } else {
+            for &byte in &bytes[..nbytes - 1] {
+                self.sendbits(8, byte);
+            }
+            self.sendbits(nbits - (nbytes_bits - 8), bytes[nbytes - 1]);
+        }
+    }
+
+    /// Flush the remaining bits and return the encoded bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if self.lastbits > 0 {
+            self.bytes
+                .push((self.lastbyte << (8 - self.lastbits)) as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Returns whether each of the components of `a` and `b` differ by less than `limit`.
+fn is_within(a: [i32; 3], b: [i32; 3], limit: i32) -> bool {
+    (0..3).all(|d| (a[d] as i64 - b[d] as i64).abs() < limit as i64)
+}
+
+/// The low-level compression routine.
+///
+/// Writes the prelude of `minint`, `maxint`, and `smallidx`, followed by the number of compressed
+/// bytes and the padded compressed data stream. The precision is not written by this function.
+///
+/// If successful, returns the number of compressed bytes that were written, excluding padding.
+///
+/// # Errors
+///
+/// If a position is not finite, or cannot be represented as an integer at the given `precision`,
+/// an error of the kind [`io::ErrorKind::InvalidInput`] is returned. So is a compressed block of
+/// 4 GiB or more with [`Magic::Xtc1995`], see [`write_opaque`]. Any writer errors are passed
+/// through.
+///
+/// # Panics
+///
+/// The number of positions must be divisible by 3, and describe more than 9 atoms. Frames with 9
+/// atoms or less are stored as uncompressed floats.
+pub fn write_compressed_positions<W: Write>(
+    writer: &mut W,
+    positions: &[f32],
+    precision: f32,
+    magic: Magic,
+) -> io::Result<usize> {
+    let natoms = {
+        let n = positions.len();
+        assert_eq!(n % 3, 0, "the length of `positions` must be divisible by 3");
+        n / 3
+    };
+    assert!(
+        natoms > SMALL_NATOMS_THRESHOLD,
+        "only compress positions when the number of atoms is greater than {SMALL_NATOMS_THRESHOLD}"
+    );
+
+    // Scale the positions to integers, and find their bounds.
+    let mut ints = Vec::with_capacity(natoms);
+    let mut minint = [i32::MAX; 3];
+    let mut maxint = [i32::MIN; 3];
+    let mut mindiff = i64::MAX;
+    let mut prevcoord = [0i32; 3];
+    for (idx, position) in positions.chunks_exact(3).enumerate() {
+        let mut coord = [0i32; 3];
+        for d in 0..3 {
+            let value = position[d];
+            // Round to the nearest integer.
+            let scaled = if value >= 0.0 {
+                value * precision + 0.5
+            } else {
+                value * precision - 0.5
+            };
+            if !scaled.is_finite() || scaled.abs() as f64 > MAXABS {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "cannot compress position {idx} ({position:?}) with precision {precision}"
+                    ),
+                ));
+            }
+            coord[d] = scaled as i32;
+            minint[d] = minint[d].min(coord[d]);
+            maxint[d] = maxint[d].max(coord[d]);
+        }
+        let diff: i64 = (0..3)
+            .map(|d| (prevcoord[d] as i64 - coord[d] as i64).abs())
+            .sum();
+        if idx > 0 && diff < mindiff {
+            mindiff = diff;
+        }
+        prevcoord = coord;
+        ints.push(coord);
+    }
+
+    if (0..3).any(|d| maxint[d] as f64 - minint[d] as f64 >= MAXABS) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot compress positions spanning from {minint:?} to {maxint:?}"),
+        ));
+    }
+
+    let mut sizeint = [0u32; 3];
+    let mut bitsizeint = [0u32; 3];
+    let bitsize = calc_sizeint(minint, maxint, &mut sizeint, &mut bitsizeint);
+
+    let mut smallidx = FIRSTIDX;
+    while smallidx < LASTIDX && (MAGICINTS[smallidx] as i64) < mindiff {
+        smallidx += 1;
+    }
+
+    for &value in minint.iter().chain(&maxint) {
+        write_i32(writer, value)?;
+    }
+    write_u32(writer, smallidx as u32)?;
+
+    let maxidx = usize::min(LASTIDX, smallidx + 8);
+    let minidx = maxidx - 8; // Often, this is equal to smallidx.
+    let mut smaller = MAGICINTS[usize::max(FIRSTIDX, smallidx - 1)] / 2;
+    let mut smallnum = MAGICINTS[smallidx] / 2;
+    let mut sizesmall = [MAGICINTS[smallidx] as u32; 3];
+    let larger = MAGICINTS[maxidx] / 2;
+
+    let mut encoder = Encoder::default();
+    let mut prevrun = -1;
+    let mut idx = 0;
+    while idx < natoms {
+        let mut is_smaller =
+            if smallidx < maxidx && idx >= 1 && is_within(ints[idx], prevcoord, larger) {
+                1
+            } else if smallidx > minidx {
+                -1
+            } else {
+                0
+            };
+
+        let mut is_small = false;
+        if idx + 1 < natoms && is_within(ints[idx], ints[idx + 1], smallnum) {
+            // Swap the first and second atom. This is done to achieve better compression for
+            // water atoms. The decoder swaps them back again.
+            ints.swap(idx, idx + 1);
+            is_small = true;
+        }
+
+        let thiscoord = ints[idx];
+        let tmpcoord = [0, 1, 2].map(|d| (thiscoord[d] - minint[d]) as u32);
+        if bitsize == 0 {
+            encoder.sendbits(bitsizeint[0], tmpcoord[0]);
+            encoder.sendbits(bitsizeint[1], tmpcoord[1]);
+            encoder.sendbits(bitsizeint[2], tmpcoord[2]);
+        } else {
+            encoder.sendints(bitsize, sizeint, tmpcoord);
+        }
+        prevcoord = thiscoord;
+        idx += 1;
+
+        // Collect the run of small differences that follows.
+        let mut run: i32 = 0;
+        let mut runcoords = [0u32; 8 * 3];
+        if !is_small && is_smaller == -1 {
+            is_smaller = 0;
+        }
+        while is_small && run < 8 * 3 {
+            let thiscoord = ints[idx];
+            let sqdist: i64 = (0..3)
+                .map(|d| (thiscoord[d] as i64 - prevcoord[d] as i64).pow(2))
+                .sum();
+            if is_smaller == -1 && sqdist >= smaller as i64 * smaller as i64 {
+                is_smaller = 0;
+            }
+
+            for d in 0..3 {
+                runcoords[run as usize + d] = (thiscoord[d] - prevcoord[d] + smallnum) as u32;
+            }
+            run += 3;
+            prevcoord = thiscoord;
+            idx += 1;
+
+            is_small = idx < natoms && is_within(ints[idx], prevcoord, smallnum);
+        }
+
+        if run != prevrun || is_smaller != 0 {
+            // Flag the change in run length.
+            prevrun = run;
+            encoder.sendbits(1, 1);
+            encoder.sendbits(5, (run + is_smaller + 1) as u32);
+        } else {
+            // Flag that the run length did not change.
+            encoder.sendbits(1, 0);
+        }
+        for coord in runcoords[..run as usize].chunks_exact(3) {
+            encoder.sendints(smallidx as u32, sizesmall, coord.try_into().unwrap());
+        }
+
+        match is_smaller.cmp(&0) {
+            std::cmp::Ordering::Less => {
+                smallidx -= 1;
+                smallnum = smaller;
+                if smallidx > FIRSTIDX {
+                    smaller = MAGICINTS[smallidx - 1] / 2;
+                } else {
+                    smaller = 0;
+                }
+            }
+            std::cmp::Ordering::Greater => {
+                smallidx += 1;
+                smaller = smallnum;
+                smallnum = MAGICINTS[smallidx] / 2;
+            }
+            std::cmp::Ordering::Equal => {}
+        }
+        sizesmall.fill(MAGICINTS[smallidx] as u32);
+    }
+
+    let bytes = encoder.finish();
+    write_opaque(writer, magic, &bytes)?;
+
+    Ok(bytes.len())
+}
+
+/// Write a [`Frame`] in the xtc format, with the provided `magic` number.
+///
+/// If the frame holds more than 9 atoms, its positions are compressed according to its
+/// `precision`. Otherwise, they are written as uncompressed floats.
+///
+/// # Errors
+///
+/// If the frame's `step` does not fit in the 32-bit step of an xtc header, an error of kind
+/// [`io::ErrorKind::InvalidInput`] is returned. Otherwise, see [`write_compressed_positions`].
+/// Any writer errors are passed through.
+pub fn write_frame<W: Write>(writer: &mut W, frame: &Frame, magic: Magic) -> io::Result<()> {
+    write_frame_with_box(writer, frame, &frame.boxvec, magic)
+}
+
+/// Write a [`Frame`] like [`write_frame`], but with `boxvec` in place of the box of the frame.
+fn write_frame_with_box<W: Write>(
+    writer: &mut W,
+    frame: &Frame,
+    boxvec: &BoxVec,
+    magic: Magic,
+) -> io::Result<()> {
+    if frame.step > u32::MAX as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the step of the frame ({}) does not fit in the 32-bit step of an xtc header",
+                frame.step
+            ),
+        ));
+    }
+    let natoms = frame.natoms();
+    let header = Header {
+        magic,
+        natoms,
+        step: frame.step,
+        time: frame.time,
+        boxvec: *boxvec,
+        natoms_repeated: natoms,
+    };
+    let precision = match frame.precision {
+        _ if !header.is_compressed() => None,
+        Precision::Compressed(precision) => Some(precision),
+        Precision::Uncompressed => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot compress the positions of a frame with {natoms} atoms without a \
+                    precision"
+                ),
+            ))
+        }
+    };
+    writer.write_all(&header.to_be_bytes()?)?;
+
+    if let Some(precision) = precision {
+        write_f32(writer, precision)?;
+        write_compressed_positions(writer, &frame.positions, precision, magic)?;
+    } else {
+        for &value in &frame.positions {
+            write_f32(writer, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `boxvec` is in the canonical form that Gromacs expects of a periodic box.
+///
+/// With the box vectors `a`, `b`, and `c` (see [`BoxVec`]), this form requires that `a` lies
+/// along the x axis and `b` in the xy plane, such that `a.y`, `a.z`, and `b.z` are zero. The
+/// diagonal elements `a.x`, `b.y`, and `c.z` must be positive, and the box may not be skewed by
+/// more than half a box vector: `|b.x|` and `|c.x|` are at most `a.x / 2`, and `|c.y|` is at most
+/// `b.y / 2`. Like Gromacs, a small margin is allowed on the skew. A zero box, which marks a
+/// frame without a box, is accepted as well.
+///
+/// # Errors
+///
+/// If the box is not in canonical form, an error of kind [`io::ErrorKind::InvalidInput`] is
+/// returned that describes the first violated condition.
+pub fn validate_box(boxvec: &BoxVec) -> io::Result<()> {
+    // The relative margin on the skew that Gromacs allows, see BOX_MARGIN in its pbc.h.
+    const MARGIN: f32 = 1.0010;
+
+    if *boxvec == BoxVec::ZERO {
+        return Ok(());
+    }
+    let (a, b, c) = (boxvec.x_axis, boxvec.y_axis, boxvec.z_axis);
+    let invalid = |reason: &str| {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the box is not in the canonical Gromacs form: {reason}"),
+        ))
+    };
+    if a.y != 0.0 || a.z != 0.0 || b.z != 0.0 {
+        return invalid("the box vector a must lie along x, and b in the xy plane");
+    }
+    if !(a.x > 0.0 && b.y > 0.0 && c.z > 0.0) {
+        return invalid("the diagonal elements must be positive");
+    }
+    if b.x.abs() > MARGIN * a.x / 2.0 || c.x.abs() > MARGIN * a.x / 2.0 {
+        return invalid("the x components of b and c may be at most half of a");
+    }
+    if c.y.abs() > MARGIN * b.y / 2.0 {
+        return invalid("the y component of c may be at most half of b");
+    }
+    Ok(())
+}
+
+/// Writes frames to an xtc trajectory.
+///
+/// This is a convenience around [`write_frame`] that holds the settings shared by all frames of
+/// a trajectory.
+#[derive(Debug)]
+pub struct XTCWriter<W> {
+    pub file: W,
+    /// The magic number that each frame is written with.
+    magic: Magic,
+    /// The box that is written for each frame in place of its own, if set.
+    boxvec: Option<BoxVec>,
+    /// The number of atoms each frame must have, if it is fixed by existing frames.
+    natoms: Option<usize>,
+}
+
+impl XTCWriter<BufWriter<File>> {
+    /// Create a file at `path` to write an xtc trajectory to, truncating it if it exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Open the xtc trajectory at `path` to append frames to it, creating it if it does not exist.
+    ///
+    /// This is the natural way to extend a trajectory, for instance when a simulation is
+    /// restarted. The headers of the existing frames are read to check that the trajectory is
+    /// intact up to its end. The appended frames are written with the magic number of the
+    /// existing frames, and must have the same number of atoms as the first of them.
+    ///
+    /// # Errors
+    ///
+    /// If the trajectory ends in an incomplete frame, as it may after a crash, an error of kind
+    /// [`io::ErrorKind::InvalidData`] is returned, since appending to it would leave a corrupt
+    /// trajectory. Any reader errors are passed through.
+    pub fn append<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut reader = XTCReader::new(&file);
+        let metas = reader.scan(None)?;
+        let len = file.metadata()?.len();
+        let end = metas.last().map_or(0, |meta| meta.offset + meta.size);
+        if end != len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the trajectory ends in an incomplete frame: its {} complete frames end at \
+                    byte {end}, but the file is {len} bytes long",
+                    metas.len()
+                ),
+            ));
+        }
+
+        // The scan leaves the reader at the start, at the first header.
+        let first = match metas.is_empty() {
+            true => None,
+            false => Some(reader.read_header()?),
+        };
+
+        let mut writer = Self::new(BufWriter::new(file));
+        if let Some(first) = first {
+            writer.magic = first.magic;
+            writer.natoms = Some(first.natoms);
+        }
+        Ok(writer)
+    }
+}
+
+impl<W: Write> XTCWriter<W> {
+    /// Create a new [`XTCWriter`] that writes frames with the [`Magic::Xtc1995`] magic number.
+    pub fn new(writer: W) -> Self {
+        Self {
+            file: writer,
+            magic: Magic::Xtc1995,
+            boxvec: None,
+            natoms: None,
+        }
+    }
+
+    /// Set the magic number that the frames are written with.
+    pub fn set_magic(&mut self, magic: Magic) {
+        self.magic = magic;
+    }
+
+    /// Set a box that is written for every frame instead of the box of the frame itself, or
+    /// remove it by passing [`None`].
+    ///
+    /// This is useful when a trajectory is re-boxed, such that the periodic cell changes for all
+    /// frames.
+    ///
+    /// # Errors
+    ///
+    /// If the box is not in the canonical Gromacs form, an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned and the previous setting is kept. See
+    /// [`validate_box`].
+    pub fn set_box(&mut self, boxvec: Option<BoxVec>) -> io::Result<()> {
+        if let Some(boxvec) = &boxvec {
+            validate_box(boxvec)?;
+        }
+        self.boxvec = boxvec;
+        Ok(())
+    }
+
+    /// Write a [`Frame`].
+    ///
+    /// # Errors
+    ///
+    /// For a writer opened with [`XTCWriter::append`], a frame with a number of atoms other than
+    /// that of the existing frames results in an error of kind [`io::ErrorKind::InvalidInput`].
+    /// See [`write_frame`] for the other errors.
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        if let Some(natoms) = self.natoms {
+            if frame.natoms() != natoms {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "cannot append a frame with {} atoms to a trajectory with {natoms} atoms",
+                        frame.natoms()
+                    ),
+                ));
+            }
+        }
+        let boxvec = self.boxvec.as_ref().unwrap_or(&frame.boxvec);
+        write_frame_with_box(&mut self.file, frame, boxvec, self.magic)
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any writer errors.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Write `bytes` as an xdr opaque block.
+///
+/// This is the counterpart of [`read_opaque`](crate::reader::read_opaque). The byte count is
+/// written as a `u32` or `u64`, depending on the `magic`, followed by the bytes and the zeroed
+/// padding up to a multiple of 4 bytes.
+///
+/// # Errors
+///
+/// With [`Magic::Xtc1995`], a block of 4 GiB or more cannot be described by its `u32` byte count,
+/// and an error of the kind [`io::ErrorKind::InvalidInput`] is returned before anything is
+/// written. Such blocks need [`Magic::Xtc2023`]. Any writer errors are passed through.
+pub fn write_opaque<W: Write>(writer: &mut W, magic: Magic, bytes: &[u8]) -> io::Result<()> {
+    let nbytes = bytes.len();
+    match magic {
+        Magic::Xtc1995 => {
+            let nbytes = u32::try_from(nbytes).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "cannot write a block of {nbytes} bytes with magic number {magic}, since \
+                        its byte count is a 32-bit integer; use magic number {} instead",
+                        Magic::Xtc2023
+                    ),
+                )
+            })?;
+            write_u32(writer, nbytes)?
+        }
+        Magic::Xtc2023 => write_u64(writer, nbytes as u64)?,
+    }
+    writer.write_all(bytes)?;
+    // Note that we are dealing with xdr padding, here! (32-bit blocks.)
+    writer.write_all(&[0; 4][..padding(nbytes)])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::buffer::UnBuffered;
+    use crate::reader::{read_compressed_positions, read_opaque};
+    use crate::selection::AtomSelection;
+
+    const HEADER_BYTES: usize = 60;
+    const N_ATOMS: usize = 125;
+    const PRECISION: f32 = 1000.0;
+
+    fn decode(bytes: &[u8], natoms: usize, magic: Magic) -> io::Result<Vec<f32>> {
+        let mut positions = vec![f32::NAN; natoms * 3];
+        read_compressed_positions::<UnBuffered, _>(
+            &mut BufReader::new(bytes),
+            natoms,
+            &mut positions,
+            PRECISION,
+            &mut Vec::new(),
+            &AtomSelection::All,
+            magic,
+        )?;
+        Ok(positions)
+    }
+
+    /// Positions that were read from a file must survive an encode and decode unchanged.
+    #[test]
+    fn write_compressed_delinyah_tiny() -> io::Result<()> {
+        let bytes = include_bytes!("../tests/trajectories/delinyah_tiny.xtc");
+        let position_bytes = &bytes[HEADER_BYTES..]; // Skip the header.
+        let positions = decode(position_bytes, N_ATOMS, Magic::Xtc1995)?;
+
+        for magic in [Magic::Xtc1995, Magic::Xtc2023] {
+            let mut encoded = Vec::new();
+            let nbytes = write_compressed_positions(&mut encoded, &positions, PRECISION, magic)?;
+            let nbytes_size = magic.nbytes_width();
+            assert_eq!(encoded.len(), 28 + nbytes_size + nbytes + padding(nbytes));
+            assert_eq!(decode(&encoded, N_ATOMS, magic)?, positions);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_compressed_round_trip() -> io::Result<()> {
+        // A somewhat irregular set of positions, with some close pairs to trigger runs.
+        let positions: Vec<f32> = (0..1000)
+            .flat_map(|i| {
+                let i = i as f32;
+                let x = (i * 0.37).sin() * 3.0 + 4.0;
+                let y = (i * 0.11).cos() * 2.0 - 1.0;
+                let z = (i % 7.0) * 0.01 + i * 0.002;
+                [x, y, z]
+            })
+            .collect();
+        let natoms = positions.len() / 3;
+
+        for magic in [Magic::Xtc1995, Magic::Xtc2023] {
+            let mut encoded = Vec::new();
+            write_compressed_positions(&mut encoded, &positions, PRECISION, magic)?;
+            assert_eq!(encoded.len() % 4, 0, "the encoded bytes must be padded");
+            let decoded = decode(&encoded, natoms, magic)?;
+            for (a, b) in positions.iter().zip(&decoded) {
+                assert!((a - b).abs() <= 0.5 / PRECISION + 1e-6, "{a} != {b}");
+            }
+
+            // Encoding the decoded positions again must give the same positions.
+            let mut reencoded = Vec::new();
+            write_compressed_positions(&mut reencoded, &decoded, PRECISION, magic)?;
+            assert_eq!(encoded, reencoded);
+        }
+
+        Ok(())
+    }
+
+    /// Compress the positions of `ngroups` groups of a large atom followed by a run of the
+    /// maximal length of 10 small atoms, which is longer than our own encoder will write.
+    ///
+    /// Returns the encoded bytes and the expected positions.
+    fn maximal_runs(ngroups: usize) -> (Vec<u8>, Vec<f32>) {
+        const SMALLIDX: usize = FIRSTIDX + 3; // A magic int of 16.
+        let smallnum = MAGICINTS[SMALLIDX] / 2;
+        let sizesmall = [MAGICINTS[SMALLIDX] as u32; 3];
+        let (minint, maxint) = ([0; 3], [999; 3]);
+        let mut sizeint = [0u32; 3];
+        let mut bitsizeint = [0u32; 3];
+        let bitsize = calc_sizeint(minint, maxint, &mut sizeint, &mut bitsizeint);
+
+        let mut encoder = Encoder::default();
+        let mut ints = Vec::new();
+        for group in 0..ngroups {
+            let large = [100 + 300 * group as i32, 500, 900];
+            encoder.sendints(bitsize, sizeint, large.map(|v| v as u32));
+            if group == 0 {
+                // Flag a run of 30 coordinate values, without a change in the small size.
+                encoder.sendbits(1, 1);
+                encoder.sendbits(5, 30 + 1);
+            } else {
+                // Flag that the run length did not change.
+                encoder.sendbits(1, 0);
+            }
+            let mut prev = large;
+            let mut run = Vec::new();
+            for k in 0..10 {
+                let delta = [k % 3 + 1, -(k % 5), 7 - k];
+                let small = [0, 1, 2].map(|d| prev[d] + delta[d]);
+                encoder.sendints(
+                    SMALLIDX as u32,
+                    sizesmall,
+                    delta.map(|d| (d + smallnum) as u32),
+                );
+                run.push(small);
+                prev = small;
+            }
+            // The decoder swaps the large atom with the first small atom.
+            ints.push(run[0]);
+            ints.push(large);
+            ints.extend_from_slice(&run[1..]);
+        }
+
+        let mut bytes = Vec::new();
+        for value in minint.into_iter().chain(maxint) {
+            write_i32(&mut bytes, value).unwrap();
+        }
+        write_u32(&mut bytes, SMALLIDX as u32).unwrap();
+        write_opaque(&mut bytes, Magic::Xtc1995, &encoder.finish()).unwrap();
+
+        let positions = ints
+            .into_iter()
+            .flatten()
+            .map(|v| v as f32 * PRECISION.recip())
+            .collect();
+        (bytes, positions)
+    }
+
+    #[test]
+    fn read_maximal_runs() -> io::Result<()> {
+        let (bytes, positions) = maximal_runs(3);
+        let natoms = positions.len() / 3;
+        assert_eq!(natoms, 3 * 11);
+        assert_eq!(decode(&bytes, natoms, Magic::Xtc1995)?, positions);
+
+        // A selection that ends anywhere within a run stops cleanly at its boundary.
+        for until in 1..natoms {
+            let mut decoded = vec![f32::NAN; until * 3];
+            read_compressed_positions::<UnBuffered, _>(
+                &mut BufReader::new(bytes.as_slice()),
+                natoms,
+                &mut decoded,
+                PRECISION,
+                &mut Vec::new(),
+                &AtomSelection::Until(until as u32),
+                Magic::Xtc1995,
+            )?;
+            assert_eq!(decoded, positions[..until * 3], "until {until}");
+        }
+
+        // Re-encoding yields the same positions, even though the runs are split up.
+        let mut reencoded = Vec::new();
+        write_compressed_positions(&mut reencoded, &positions, PRECISION, Magic::Xtc1995)?;
+        assert_eq!(decode(&reencoded, natoms, Magic::Xtc1995)?, positions);
+
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_round_trip() -> io::Result<()> {
+        use crate::reader::{read_f32, read_i32, read_u32, read_u64};
+
+        let mut bytes = Vec::new();
+        for value in [0.0, -1.5, f32::MAX, f32::MIN_POSITIVE, f32::INFINITY] {
+            write_f32(&mut bytes, value)?;
+        }
+        for value in [0, -1, i32::MIN, i32::MAX, 1995] {
+            write_i32(&mut bytes, value)?;
+        }
+        for value in [0, 1, u32::MAX, 2023] {
+            write_u32(&mut bytes, value)?;
+        }
+        for value in [0, 1, u64::MAX, 1 << 40] {
+            write_u64(&mut bytes, value)?;
+        }
+        assert_eq!(bytes.len(), 5 * 4 + 5 * 4 + 4 * 4 + 4 * 8);
+
+        let mut reader = bytes.as_slice();
+        for value in [0.0, -1.5, f32::MAX, f32::MIN_POSITIVE, f32::INFINITY] {
+            assert_eq!(read_f32(&mut reader)?, value);
+        }
+        for value in [0, -1, i32::MIN, i32::MAX, 1995] {
+            assert_eq!(read_i32(&mut reader)?, value);
+        }
+        for value in [0, 1, u32::MAX, 2023] {
+            assert_eq!(read_u32(&mut reader)?, value);
+        }
+        for value in [0, 1, u64::MAX, 1 << 40] {
+            assert_eq!(read_u64(&mut reader)?, value);
+        }
+        assert!(reader.is_empty());
+
+        Ok(())
+    }
+
+    /// The scalars are stored as big-endian, as xdr prescribes.
+    #[test]
+    fn scalar_big_endian() -> io::Result<()> {
+        let mut bytes = Vec::new();
+        write_i32(&mut bytes, 1995)?;
+        write_u64(&mut bytes, 2023)?;
+        write_f32(&mut bytes, 1.0)?;
+        assert_eq!(
+            bytes,
+            [0, 0, 7, 203, 0, 0, 0, 0, 0, 0, 7, 231, 0x3f, 0x80, 0, 0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn opaque_round_trip() -> io::Result<()> {
+        for magic in [Magic::Xtc1995, Magic::Xtc2023] {
+            for n in 0..=9 {
+                let bytes: Vec<u8> = (1..=n).collect();
+                let mut encoded = Vec::new();
+                write_opaque(&mut encoded, magic, &bytes)?;
+                let nbytes_size = magic.nbytes_width();
+                assert_eq!(
+                    encoded.len(),
+                    nbytes_size + n as usize + padding(n as usize)
+                );
+
+                let mut reader = encoded.as_slice();
+                let mut buf = Vec::new();
+                let count = read_opaque(&mut reader, magic, &mut buf)?;
+                assert_eq!(count, n as usize);
+                assert_eq!(buf[..count], bytes);
+                assert!(
+                    buf[count..].iter().all(|&b| b == 0),
+                    "padding must be zeroed"
+                );
+                assert!(reader.is_empty(), "the whole block must be consumed");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_opaque_truncated() {
+        let mut encoded = Vec::new();
+        write_opaque(&mut encoded, Magic::Xtc1995, &[1, 2, 3, 4, 5]).unwrap();
+        encoded.truncate(encoded.len() - 4);
+        let err =
+            read_opaque(&mut encoded.as_slice(), Magic::Xtc1995, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    /// Frames that are written must be read back identically.
+    #[test]
+    fn write_frame_round_trip() -> io::Result<()> {
+        let path = "tests/trajectories/adk_oplsaa.xtc";
+        let frames = crate::XTCReader::open(path)?.read_all_frames()?;
+
+        for magic in [Magic::Xtc1995, Magic::Xtc2023] {
+            let mut bytes = Vec::new();
+            for frame in frames.iter() {
+                write_frame(&mut bytes, frame, magic)?;
+            }
+            let mut reader = crate::XTCReader::new(bytes.as_slice());
+            assert_eq!(reader.read_all_frames()?, frames);
+        }
+
+        Ok(())
+    }
+
+    /// Frames that are written must be byte-for-byte identical to those written by GROMACS.
+    #[test]
+    fn write_frame_identical_bytes() -> io::Result<()> {
+        for path in [
+            "tests/trajectories/adk_oplsaa.xtc",
+            "tests/trajectories/delinyah_smaller.xtc",
+            "tests/trajectories/xtc_test_only_10_frame_10_atoms.xtc",
+        ] {
+            let original = std::fs::read(path)?;
+            let mut reader = crate::XTCReader::open(path)?;
+            let metas = reader.scan(None)?;
+            let frames = reader.read_all_frames()?;
+
+            for (meta, frame) in metas.iter().zip(frames.iter()) {
+                let start = meta.offset as usize;
+                let bytes = &original[start..start + meta.size as usize];
+                let mut written = Vec::new();
+                write_frame(&mut written, frame, Magic::Xtc1995)?;
+                assert!(written == bytes, "frame at {start} in {path} differs");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_compressed_invalid() {
+        let mut positions = vec![1.0; 10 * 3];
+        positions[4] = f32::NAN;
+        let err =
+            write_compressed_positions(&mut Vec::new(), &positions, PRECISION, Magic::Xtc1995)
+                .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        positions[4] = 1e10;
+        let err =
+            write_compressed_positions(&mut Vec::new(), &positions, PRECISION, Magic::Xtc1995)
+                .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::Cursor;
+
+use molly::writer::{write_compressed_positions, write_f32};
+use molly::{BoxVec, Frame, Header, Magic, XTCReader};
+
+const PRECISION: f32 = 1000.0;
+
+/// Construct a trajectory of a small and a compressed frame with the provided box.
+fn trajectory(boxvec: BoxVec) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for (step, natoms) in [3, 12].into_iter().enumerate() {
+        let header = Header {
+            magic: Magic::Xtc1995,
+            natoms,
+            step: step as u64,
+            time: step as f32,
+            boxvec,
+            natoms_repeated: natoms,
+        };
+        bytes.extend(header.to_be_bytes()?);
+
+        let positions: Vec<f32> = (0..natoms * 3).map(|i| i as f32 * 0.125).collect();
+        if header.is_compressed() {
+            write_f32(&mut bytes, PRECISION)?;
+            write_compressed_positions(&mut bytes, &positions, PRECISION, header.magic)?;
+        } else {
+            for &value in &positions {
+                write_f32(&mut bytes, value)?;
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+fn read_frames(bytes: Vec<u8>) -> std::io::Result<Vec<Frame>> {
+    let mut reader = XTCReader::new(Cursor::new(bytes));
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while reader.read_frame(&mut frame).is_ok() {
+        frames.push(frame.clone());
+    }
+    Ok(frames)
+}
+
+#[test]
+fn zero_box() -> std::io::Result<()> {
+    let frames = read_frames(trajectory(BoxVec::ZERO)?)?;
+    assert_eq!(frames.len(), 2);
+    for frame in &frames {
+        assert!(!frame.has_box());
+        assert_eq!(frame.volume(), None);
+        assert!(frame.positions.iter().all(|v| v.is_finite()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn with_box() -> std::io::Result<()> {
+    let boxvec = BoxVec::from_cols_array(&[2.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 4.0]);
+    let frames = read_frames(trajectory(boxvec)?)?;
+    assert_eq!(frames.len(), 2);
+    for frame in &frames {
+        assert!(frame.has_box());
+        assert_eq!(frame.volume(), Some(24.0));
+    }
+
+    Ok(())
+}
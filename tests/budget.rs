@@ -0,0 +1,56 @@
+use molly::selection::{AtomSelection, FrameSelection, Range};
+use molly::{Budgeted, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn budgeted_materialized() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::COB)?.read_all_frames()?;
+    let mut reader = XTCReader::open(trajectories::COB)?;
+    let (fs, als) = (FrameSelection::All, AtomSelection::All);
+    let size = reader.estimate_decoded_size(&fs, &als)?;
+    let Budgeted::Materialized(read) = reader.read_budgeted(&fs, &als, size)? else {
+        panic!("frames that fit within the budget should be materialized");
+    };
+    assert_eq!(&read[..], &frames[..]);
+    assert_eq!(read[1].index, Some(1));
+
+    Ok(())
+}
+
+#[test]
+fn budgeted_streaming() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::COB)?.read_all_frames()?;
+    let mut reader = XTCReader::open(trajectories::COB)?;
+    let (fs, als) = (FrameSelection::All, AtomSelection::All);
+    let size = reader.estimate_decoded_size(&fs, &als)?;
+    let Budgeted::Streaming(iter) = reader.read_budgeted(&fs, &als, size - 1)? else {
+        panic!("frames that exceed the budget should be streamed");
+    };
+    let read = iter.collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(&read[..], &frames[..]);
+
+    Ok(())
+}
+
+#[test]
+fn budgeted_selection() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let fs = FrameSelection::Range(Range::new(Some(1), Some(4), None));
+    let als = AtomSelection::Until(100);
+    // Three frames of 100 atoms take up 3600 bytes.
+    let Budgeted::Materialized(read) = reader.read_budgeted(&fs, &als, 3600)? else {
+        panic!("frames that fit within the budget should be materialized");
+    };
+    assert_eq!(read.len(), 3);
+    assert!(read.iter().all(|frame| frame.natoms() == 100));
+    assert_eq!(read[0].index, Some(1));
+
+    assert!(matches!(
+        reader.read_budgeted(&fs, &als, 3599)?,
+        Budgeted::Streaming(_)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::io::Cursor;
+
+use molly::buffer::BufferStats;
+use molly::selection::AtomSelection;
+use molly::writer::XTCWriter;
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn stats_match_frames() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut expected = XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    let mut expected_frame = Frame::default();
+    let selection = AtomSelection::Until(100);
+    while expected
+        .read_frame_with_selection(&mut expected_frame, &selection)
+        .is_ok()
+    {
+        let stats = reader.read_frame_with_stats(&mut frame, &selection)?;
+        assert_eq!(frame, expected_frame);
+        let stats = stats.expect("the frames are compressed");
+        assert!(
+            stats.used <= stats.read && stats.read <= stats.size,
+            "{stats:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stats_selection() -> std::io::Result<()> {
+    let mut frame = Frame::default();
+    let mut few = BufferStats::default();
+    let mut reader = XTCReader::open(trajectories::DELINYAH)?;
+    for _ in 0..3 {
+        few += reader
+            .read_frame_with_stats(&mut frame, &AtomSelection::Until(10))?
+            .unwrap();
+    }
+    let mut all = BufferStats::default();
+    let mut reader = XTCReader::open(trajectories::DELINYAH)?;
+    for _ in 0..3 {
+        all += reader
+            .read_frame_with_stats(&mut frame, &AtomSelection::All)?
+            .unwrap();
+    }
+
+    assert_eq!(few.size, all.size);
+    // Only the start of each frame is needed for the small selection, while nearly all of it is
+    // needed for all atoms. The frames of this trajectory are small enough to be read at once.
+    assert!(few.used < all.used, "{few:?} {all:?}");
+    assert!(all.size - all.used < 3 * 4, "{all:?}");
+    assert_eq!(few.read_fraction(), 1.0);
+
+    Ok(())
+}
+
+#[test]
+fn stats_uncompressed() -> std::io::Result<()> {
+    let small = Frame {
+        positions: vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+        ..Default::default()
+    };
+    let mut writer = XTCWriter::new(Vec::new());
+    writer.write_frame(&small)?;
+
+    let mut reader = XTCReader::new(Cursor::new(writer.file));
+    let mut frame = Frame::default();
+    let stats = reader.read_frame_with_stats(&mut frame, &AtomSelection::All)?;
+    assert_eq!(stats, None);
+    assert_eq!(frame.positions, small.positions);
+
+    Ok(())
+}
+
+#[test]
+fn stats_large_frame() -> std::io::Result<()> {
+    // A frame of scattered atoms, such that its compressed data spans several blocks.
+    let mut state = 0x2545f491u32;
+    let positions = (0..3 * 200_000)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32 * 50.0
+        })
+        .collect();
+    let large = Frame {
+        positions,
+        precision: molly::Precision::Compressed(1000.0),
+        ..Default::default()
+    };
+    let mut writer = XTCWriter::new(Vec::new());
+    writer.write_frame(&large)?;
+    let bytes = writer.file;
+
+    let mut frame = Frame::default();
+    let mut reader = XTCReader::new(Cursor::new(&bytes));
+    let few = reader
+        .read_frame_with_stats(&mut frame, &AtomSelection::Until(10))?
+        .unwrap();
+    assert_eq!(frame.natoms(), 10);
+    // Only the first block was read.
+    assert!(few.read < few.size, "{few:?}");
+    assert!(few.read_fraction() < 0.5, "{few:?}");
+
+    let mut reader = XTCReader::new(Cursor::new(&bytes));
+    let all = reader
+        .read_frame_with_stats(&mut frame, &AtomSelection::All)?
+        .unwrap();
+    assert_eq!(frame.natoms(), 200_000);
+    assert_eq!(all.size, few.size);
+    assert!(all.read > few.read, "{few:?} {all:?}");
+
+    Ok(())
+}
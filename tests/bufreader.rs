@@ -0,0 +1,54 @@
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+fn bufreader(path: &str) -> std::io::Result<()> {
+    let expected = XTCReader::open(path)?.read_all_frames()?;
+
+    let mut reader = XTCReader::open_buffered(path)?;
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    // Seeking back home and scanning works through the buffer.
+    reader.home()?;
+    let offsets = XTCReader::open(path)?.determine_offsets(None)?;
+    assert_eq!(reader.determine_offsets(None)?, offsets);
+    assert_eq!(reader.frame_count()?, expected.len());
+
+    // Reading at an offset, both in an unbuffered and a buffered manner.
+    let mut frame = Frame::default();
+    for (idx, &offset) in offsets.iter().enumerate().rev() {
+        reader.read_frame_at_offset::<false>(&mut frame, offset, &AtomSelection::All)?;
+        assert_eq!(frame, expected[idx]);
+        reader.read_frame_at_offset::<true>(&mut frame, offset, &AtomSelection::All)?;
+        assert_eq!(frame, expected[idx]);
+    }
+
+    // Reading a selection of frames.
+    reader.home()?;
+    let selection = FrameSelection::framelist_from_iter([0, 2, 3]);
+    let mut frames = Vec::new();
+    reader.read_frames::<true>(&mut frames, &selection, &AtomSelection::All)?;
+    assert_eq!(
+        frames,
+        [&expected[0], &expected[2], &expected[3]].map(Clone::clone)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn bufreader_adk() -> std::io::Result<()> {
+    bufreader(trajectories::ADK)
+}
+
+#[test]
+fn bufreader_smol() -> std::io::Result<()> {
+    bufreader(trajectories::SMOL)
+}
+
+#[test]
+fn bufreader_ten() -> std::io::Result<()> {
+    bufreader(trajectories::TEN)
+}
@@ -0,0 +1,88 @@
+use molly::Frame;
+
+mod common;
+use common::{frame, trajectories};
+
+#[test]
+fn center_of_mass_weighted() {
+    let frame = frame(&[[0.0, 0.0, 0.0], [4.0, 2.0, -8.0]]);
+    let com = frame.center_of_mass(&[3.0, 1.0]);
+    assert_eq!(com.to_array(), [1.0, 0.5, -2.0]);
+}
+
+#[test]
+fn center_of_mass_equal_masses() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+
+    // With equal masses, the center of mass is the center of geometry.
+    let natoms = frame.natoms();
+    let com = frame.center_of_mass(&vec![12.011; natoms]);
+    let mut cog = [0.0; 3];
+    for pos in frame.positions.chunks_exact(3) {
+        for (c, &v) in cog.iter_mut().zip(pos) {
+            *c += v as f64 / natoms as f64;
+        }
+    }
+    let cog = glam::Vec3::from_array(cog.map(|c| c as f32));
+    assert!((com - cog).length() < 1e-4, "{com:?} != {cog:?}");
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "the number of masses must be equal to the number of atoms")]
+fn center_of_mass_mismatch() {
+    let frame = frame(&[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+    frame.center_of_mass(&[1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn radius_of_gyration_geometric() {
+    // The center is at the origin, and each atom lies at a distance of 1 or 3 from it.
+    let frame = frame(&[
+        [1.0, 0.0, 0.0],
+        [-1.0, 0.0, 0.0],
+        [0.0, 3.0, 0.0],
+        [0.0, -3.0, 0.0],
+    ]);
+    let rg = frame.radius_of_gyration(None);
+    assert!((rg - 5.0f32.sqrt()).abs() < 1e-6, "{rg}");
+}
+
+#[test]
+fn radius_of_gyration_weighted() {
+    // The center of mass is at the origin, with the atoms at distances 1 and 3.
+    // Rg² = (3 · 1² + 1 · 3²) / 4 = 3.
+    let frame = frame(&[[0.0, 1.0, 0.0], [0.0, -3.0, 0.0]]);
+    let rg = frame.radius_of_gyration(Some(&[3.0, 1.0]));
+    assert!((rg - 3.0f32.sqrt()).abs() < 1e-6, "{rg}");
+}
+
+#[test]
+fn radius_of_gyration_degenerate() {
+    assert_eq!(frame(&[[1.0, 2.0, 3.0]]).radius_of_gyration(None), 0.0);
+    assert_eq!(Frame::default().radius_of_gyration(None), 0.0);
+    let pair = frame(&[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+    assert_eq!(pair.radius_of_gyration(Some(&[0.0, 0.0])), 0.0);
+}
+
+#[test]
+fn radius_of_gyration_inertia() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+
+    // The trace of the inertia tensor is twice the sum of the squared distances to the center.
+    let rg = frame.radius_of_gyration(None);
+    let tensor = frame.inertia_tensor(None);
+    let trace = tensor.x_axis.x + tensor.y_axis.y + tensor.z_axis.z;
+    let expected = (trace / 2.0 / frame.natoms() as f32).sqrt();
+    assert!(
+        (rg - expected).abs() < 1e-3 * expected,
+        "{rg} != {expected}"
+    );
+
+    Ok(())
+}
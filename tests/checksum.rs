@@ -0,0 +1,100 @@
+use molly::checksum::{self, sidecar_path};
+
+mod common;
+use common::trajectories;
+
+/// Returns the path to a fresh copy of a trajectory, such that it can be modified.
+fn copy(name: &str) -> std::io::Result<std::path::PathBuf> {
+    let copy = std::env::temp_dir().join(format!("molly_checksum_{name}.xtc"));
+    std::fs::copy(trajectories::ADK, &copy)?;
+    Ok(copy)
+}
+
+fn cleanup(path: &std::path::Path) -> std::io::Result<()> {
+    std::fs::remove_file(sidecar_path(path))?;
+    std::fs::remove_file(path)
+}
+
+#[test]
+fn intact() -> std::io::Result<()> {
+    let path = copy("intact")?;
+    checksum::stamp(&path)?;
+
+    let recorded = checksum::read_sidecar(sidecar_path(&path))?;
+    let offsets = molly::XTCReader::open(&path)?.determine_offsets(None)?;
+    assert_eq!(recorded.len(), offsets.len());
+    assert!(recorded
+        .iter()
+        .zip(offsets.iter())
+        .all(|(c, &o)| c.offset == o));
+    assert!(checksum::verify(&path)?.is_empty());
+
+    // The sidecar does not affect reading the trajectory.
+    let frames = molly::XTCReader::open(&path)?.read_all_frames()?;
+    assert_eq!(
+        frames,
+        molly::XTCReader::open(trajectories::ADK)?.read_all_frames()?
+    );
+
+    cleanup(&path)
+}
+
+#[test]
+fn bit_rot() -> std::io::Result<()> {
+    let path = copy("bit_rot")?;
+    checksum::stamp(&path)?;
+
+    // Flip a bit near the end of the third frame, within its compressed positions.
+    let ends = molly::XTCReader::open(&path)?.determine_offsets_exclusive(None)?;
+    let mut bytes = std::fs::read(&path)?;
+    bytes[ends[2] as usize - 8] ^= 0b100;
+    std::fs::write(&path, &bytes)?;
+
+    assert_eq!(checksum::verify(&path)?, [2]);
+
+    cleanup(&path)
+}
+
+#[test]
+fn truncated() -> std::io::Result<()> {
+    let path = copy("truncated")?;
+    checksum::stamp(&path)?;
+
+    // Drop the last frame, such that the trajectory no longer lines up with its sidecar.
+    let ends = molly::XTCReader::open(&path)?.determine_offsets_exclusive(None)?;
+    let bytes = std::fs::read(&path)?;
+    std::fs::write(&path, &bytes[..ends[ends.len() - 2] as usize])?;
+
+    let err = checksum::verify(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    cleanup(&path)
+}
+
+#[test]
+fn missing_sidecar() -> std::io::Result<()> {
+    let err = checksum::verify(trajectories::TEN).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    Ok(())
+}
+
+/// A corrupt frame that claims an enormous number of compressed bytes is reported, without
+/// allocating for it.
+#[test]
+fn corrupt_nbytes() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let mut header = molly::XTCReader::new(bytes.as_slice()).read_header()?;
+    header.magic = molly::Magic::Xtc2023;
+
+    let mut corrupt = header.to_be_bytes()?.to_vec();
+    corrupt.extend(&bytes[molly::Header::SIZE..molly::Header::SIZE + 4 * 8]); // Up to smallidx.
+    molly::writer::write_u64(&mut corrupt, 1 << 60)?;
+    corrupt.extend([0; 64]);
+
+    let mut reader = molly::XTCReader::new(std::io::Cursor::new(corrupt));
+    let err = checksum::frame_checksums(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use molly::Frame;
+
+mod common;
+use common::trajectories;
+
+/// A frame with the given positions, and a step and time to check that they carry over.
+fn frame(positions: &[[f32; 3]]) -> Frame {
+    Frame {
+        step: 12,
+        time: 3.5,
+        ..common::frame(positions)
+    }
+}
+
+#[test]
+fn coarse_grain_geometric() {
+    let frame = frame(&[
+        [0.0, 0.0, 0.0],
+        [2.0, 0.0, 0.0],
+        [1.0, 3.0, 0.0],
+        [5.0, 5.0, 5.0],
+        [1.0, 1.0, 1.0],
+        [3.0, 3.0, 3.0],
+    ]);
+    let cg = frame.coarse_grain(&[3, 1, 2], None);
+    assert_eq!(cg.natoms(), 3);
+    assert_eq!(cg.positions, [1.0, 1.0, 0.0, 5.0, 5.0, 5.0, 2.0, 2.0, 2.0]);
+    assert_eq!(
+        (cg.step, cg.time, cg.boxvec),
+        (frame.step, frame.time, frame.boxvec)
+    );
+}
+
+#[test]
+fn coarse_grain_weighted() {
+    let frame = frame(&[
+        [0.0, 0.0, 0.0],
+        [4.0, 2.0, -8.0],
+        [1.0, 1.0, 1.0],
+        [3.0, 3.0, 3.0],
+    ]);
+    let cg = frame.coarse_grain(&[2, 2], Some(&[3.0, 1.0, 0.0, 0.0]));
+    // The second group has no mass, so its bead is at its center of geometry.
+    assert_eq!(cg.positions, [1.0, 0.5, -2.0, 2.0, 2.0, 2.0]);
+}
+
+#[test]
+fn coarse_grain_matches_center_of_mass() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+
+    // A single group holding all atoms has its bead at the center of mass.
+    let natoms = frame.natoms();
+    let masses: Vec<f32> = (0..natoms).map(|i| 1.0 + (i % 4) as f32).collect();
+    let cg = frame.coarse_grain(&[natoms], Some(&masses));
+    let com = frame.center_of_mass(&masses);
+    assert_eq!(cg.positions, com.to_array());
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "the group sizes must sum to the number of atoms in the frame")]
+fn coarse_grain_mismatch() {
+    let frame = frame(&[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+    frame.coarse_grain(&[1, 2], None);
+}
+
+#[test]
+#[should_panic(expected = "the group sizes must all be greater than zero")]
+fn coarse_grain_empty_group() {
+    let frame = frame(&[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+    frame.coarse_grain(&[1, 0, 1], None);
+}
@@ -9,3 +9,55 @@ pub mod trajectories {
     pub const BAD: &str = "tests/trajectories/bad.xtc";
     pub const DELINYAH: &str = "tests/trajectories/delinyah_smaller.xtc";
 }
+
+/// A frame with the given positions, and all else at its default.
+#[allow(dead_code)]
+pub fn frame(positions: &[[f32; 3]]) -> molly::Frame {
+    molly::Frame {
+        positions: positions.iter().flatten().copied().collect(),
+        ..Default::default()
+    }
+}
+
+/// A frame with the given positions and box.
+#[allow(dead_code)]
+pub fn frame_with_box(positions: &[[f32; 3]], boxvec: glam::Mat3) -> molly::Frame {
+    molly::Frame {
+        boxvec,
+        ..frame(positions)
+    }
+}
+
+/// Assert that the values are equal in number, and each within `tolerance` of its expected value.
+#[allow(dead_code)]
+pub fn assert_close(found: &[f32], expected: &[f32], tolerance: f32) {
+    assert_eq!(found.len(), expected.len(), "{found:?} != {expected:?}");
+    for (f, e) in found.iter().zip(expected) {
+        assert!((f - e).abs() < tolerance, "{found:?} != {expected:?}");
+    }
+}
+
+/// A path in the temporary directory with a name unique to this process, which is removed when
+/// dropped.
+#[allow(dead_code)]
+pub struct TempPath(std::path::PathBuf);
+
+#[allow(dead_code)]
+impl TempPath {
+    pub fn new(name: &str) -> Self {
+        let name = format!("molly_{name}_{}.xtc", std::process::id());
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl AsRef<std::path::Path> for TempPath {
+    fn as_ref(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
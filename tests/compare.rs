@@ -46,6 +46,12 @@ fn compare(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
             .read(&mut xdr_frame)
             .expect("couldn't read xdrfile frame");
 
+        assert_eq!(
+            molly_frame.box_rows(),
+            xdr_frame.box_vector,
+            "box vectors for molly and xdrfile do not match"
+        );
+
         let molly_positions = molly_frame
             .coords()
             .map(|c| c.to_array())
@@ -0,0 +1,99 @@
+use std::io::Seek;
+use std::num::NonZeroU32;
+
+use molly::selection::AtomSelection as AS;
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+/// The atom selections that are decoded by both readers.
+///
+/// The small [`AS::Until`] selections are where the buffered reader stops reading a frame early,
+/// and must reposition itself at the start of the next frame.
+fn selections() -> Vec<AS> {
+    vec![
+        AS::All,
+        AS::Until(0),
+        AS::Until(1),
+        AS::Until(2),
+        AS::Until(9),
+        AS::Until(10),
+        AS::Until(100),
+        AS::Until(1000),
+        AS::Mask(vec![true; 5]),
+        AS::Mask([false, true].repeat(40)),
+        AS::from_index_list(&[0, 3, 7, 500]),
+        AS::IndexList(vec![1, 2, 3, 2000]),
+        AS::Stride {
+            start: 1,
+            step: NonZeroU32::new(3).unwrap(),
+        },
+    ]
+}
+
+/// Read all frames of the trajectory at `path` with both the unbuffered and buffered readers,
+/// and assert that every decoded frame, as well as the position after it, is identical.
+fn consistent(path: &str, atom_selection: &AS) -> std::io::Result<()> {
+    let mut unbuffered_reader = XTCReader::open(path)?;
+    let mut buffered_reader = XTCReader::open(path)?;
+    let mut unbuffered = molly::Frame::default();
+    let mut buffered = molly::Frame::default();
+
+    for idx in 0.. {
+        let unbuffered_result =
+            unbuffered_reader.read_frame_with_selection(&mut unbuffered, atom_selection);
+        let buffered_result =
+            buffered_reader.read_frame_with_selection_buffered(&mut buffered, atom_selection);
+        match (unbuffered_result, buffered_result) {
+            (Ok(()), Ok(())) => {}
+            // Both readers must stop at the same frame, for the same reason.
+            (Err(unbuffered_err), Err(buffered_err)) => {
+                assert_eq!(
+                    unbuffered_err.kind(),
+                    buffered_err.kind(),
+                    "{path}: frame {idx} with {atom_selection:?} fails differently"
+                );
+                break;
+            }
+            (unbuffered_result, buffered_result) => panic!(
+                "{path}: frame {idx} with {atom_selection:?} gives {unbuffered_result:?} \
+                unbuffered, but {buffered_result:?} buffered"
+            ),
+        }
+
+        assert_eq!(
+            unbuffered, buffered,
+            "{path}: frame {idx} with {atom_selection:?} is decoded differently"
+        );
+        assert_eq!(
+            unbuffered_reader.file.stream_position()?,
+            buffered_reader.file.stream_position()?,
+            "{path}: the readers end up at different positions after frame {idx} with \
+            {atom_selection:?}"
+        );
+    }
+
+    Ok(())
+}
+
+macro_rules! consistency_test {
+    ($name:ident, $path:expr) => {
+        #[test]
+        fn $name() -> std::io::Result<()> {
+            for atom_selection in selections() {
+                consistent($path, &atom_selection)?;
+            }
+            Ok(())
+        }
+    };
+}
+
+consistency_test!(consistent_adk, trajectories::ADK);
+consistency_test!(consistent_aux, trajectories::AUX);
+consistency_test!(consistent_cob, trajectories::COB);
+consistency_test!(consistent_smol, trajectories::SMOL);
+consistency_test!(consistent_ten, trajectories::TEN);
+consistency_test!(consistent_xyz, trajectories::XYZ);
+consistency_test!(consistent_bad, trajectories::BAD);
+consistency_test!(consistent_delinyah, trajectories::DELINYAH);
@@ -0,0 +1,82 @@
+use std::num::NonZeroU64;
+
+use molly::selection::{AtomSelection, FrameSelection, Range};
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+fn contiguous(
+    path: &str,
+    frame_selection: FrameSelection,
+    atom_selection: AtomSelection,
+) -> std::io::Result<()> {
+    let mut reader = XTCReader::open(path)?;
+    let mut frames = Vec::new();
+    reader.read_frames::<false>(&mut frames, &frame_selection, &atom_selection)?;
+
+    reader.home()?;
+    let (positions, shape) = reader.read_contiguous(&frame_selection, &atom_selection)?;
+    let natoms = frames.first().map_or(0, |frame| frame.natoms());
+    assert_eq!(shape, [frames.len(), natoms, 3]);
+    assert_eq!(positions.len(), shape.iter().product::<usize>());
+    let expected: Vec<f32> = frames
+        .iter()
+        .flat_map(|frame| frame.positions.iter().copied())
+        .collect();
+    assert_eq!(positions, expected);
+
+    Ok(())
+}
+
+#[test]
+fn contiguous_all() -> std::io::Result<()> {
+    contiguous(trajectories::ADK, FrameSelection::All, AtomSelection::All)
+}
+
+#[test]
+fn contiguous_selections() -> std::io::Result<()> {
+    let frame_selection = FrameSelection::Range(Range::new(Some(1), Some(8), NonZeroU64::new(3)));
+    contiguous(
+        trajectories::ADK,
+        frame_selection.clone(),
+        AtomSelection::Until(500),
+    )?;
+    contiguous(
+        trajectories::SMOL,
+        frame_selection,
+        AtomSelection::from_index_list(&[3, 30, 300, 3000]),
+    )
+}
+
+#[test]
+fn contiguous_empty() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let selection = FrameSelection::framelist_from_iter([]);
+    let (positions, shape) = reader.read_contiguous(&selection, &AtomSelection::All)?;
+    assert!(positions.is_empty());
+    assert_eq!(shape, [0, 0, 3]);
+    Ok(())
+}
+
+#[test]
+fn contiguous_ragged() -> std::io::Result<()> {
+    // Concatenate two trajectories with a different number of atoms.
+    let bytes = [
+        std::fs::read(trajectories::TEN)?,
+        std::fs::read(trajectories::XYZ)?,
+    ]
+    .concat();
+
+    let mut reader = XTCReader::new(std::io::Cursor::new(bytes));
+    let err = reader
+        .read_contiguous(&FrameSelection::All, &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Selecting the same number of atoms from each frame makes them stackable again.
+    reader.home()?;
+    let (_, shape) = reader.read_contiguous(&FrameSelection::All, &AtomSelection::Until(5))?;
+    assert_eq!(shape[1..], [5, 3]);
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use molly::selection::AtomSelection;
+use molly::writer::{write_i32, write_u32};
+use molly::{Frame, Header, QuantizedFrame};
+
+mod common;
+use common::trajectories;
+
+/// Write a copy of the first frame of a trajectory with a corrupt number of compressed bytes.
+fn corrupt_nbytes(name: &str, nbytes: u32) -> std::io::Result<std::path::PathBuf> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let mut reader = molly::XTCReader::new(bytes.as_slice());
+    let header = reader.read_header()?;
+
+    let mut corrupt = header.to_be_bytes()?.to_vec();
+    corrupt.extend(&bytes[Header::SIZE..Header::SIZE + 4]); // Precision.
+    for value in [0, 0, 0, 1000, 1000, 1000] {
+        write_i32(&mut corrupt, value)?;
+    }
+    write_u32(&mut corrupt, 20)?; // Smallidx.
+    write_u32(&mut corrupt, nbytes)?;
+    corrupt.extend([0; 64]);
+
+    let path = std::env::temp_dir().join(format!("molly_corrupt_{name}.xtc"));
+    std::fs::write(&path, corrupt)?;
+    Ok(path)
+}
+
+/// A huge number of compressed bytes must be rejected without allocating for it.
+#[test]
+fn corrupt_nbytes_buffered() -> std::io::Result<()> {
+    let path = corrupt_nbytes("buffered", u32::MAX)?;
+    let mut reader = molly::XTCReader::open(&path)?;
+    let err = reader
+        .read_frame_with_selection_buffered(&mut Frame::default(), &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+/// The same holds for unbuffered reads, which read all compressed bytes at once.
+#[test]
+fn corrupt_nbytes_unbuffered() -> std::io::Result<()> {
+    let path = corrupt_nbytes("unbuffered", u32::MAX)?;
+    let mut reader = molly::XTCReader::open(&path)?;
+    let err = reader
+        .read_frame_with_selection(&mut Frame::default(), &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+/// Returns the bytes of the first frame of a trajectory, with its precision set to `precision`.
+fn corrupt_precision(precision: f32) -> std::io::Result<Vec<u8>> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let frame = reader.read_raw_frames(0, 1)?;
+    let mut corrupt = frame[..Header::SIZE].to_vec();
+    corrupt.extend(precision.to_be_bytes());
+    corrupt.extend(&frame[Header::SIZE + 4..]);
+    Ok(corrupt)
+}
+
+/// A frame with a precision of zero would decode to infinite positions, so it is rejected.
+#[test]
+fn corrupt_precision_zero() -> std::io::Result<()> {
+    for precision in [0.0, -1000.0, f32::NAN, f32::INFINITY] {
+        let bytes = corrupt_precision(precision)?;
+        let err = molly::XTCReader::new(bytes.as_slice())
+            .read_frame(&mut Frame::default())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{precision}");
+        assert!(err.to_string().contains("precision"), "{err}");
+
+        let err = Frame::decode_from_bytes(&bytes).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{precision}");
+
+        let err = molly::XTCReader::new(bytes.as_slice())
+            .read_frame_quantized(&mut QuantizedFrame::default(), &AtomSelection::All)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{precision}");
+    }
+
+    // The intact frame is read without trouble.
+    let bytes = corrupt_precision(1000.0)?;
+    molly::XTCReader::new(bytes.as_slice()).read_frame(&mut Frame::default())?;
+
+    Ok(())
+}
+
+/// A header with a mismatched repeated number of atoms is rejected, unless reading leniently.
+#[test]
+fn natoms_mismatch() -> std::io::Result<()> {
+    let mut bytes = std::fs::read(trajectories::ADK)?;
+    let mut expected = Frame::default();
+    molly::XTCReader::new(bytes.as_slice()).read_frame(&mut expected)?;
+
+    let natoms = expected.natoms();
+    let repeated = natoms + 1;
+    bytes[Header::SIZE - 4..Header::SIZE].copy_from_slice(&(repeated as u32).to_be_bytes());
+
+    let mut reader = molly::XTCReader::new(bytes.as_slice());
+    let err = reader.read_frame(&mut Frame::default()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let mismatch = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<molly::NAtomsMismatch>())
+        .expect("the error should hold the mismatch");
+    assert_eq!(
+        *mismatch,
+        molly::NAtomsMismatch {
+            first: natoms,
+            repeated
+        }
+    );
+
+    let mut reader = molly::XTCReader::new(bytes.as_slice());
+    reader.set_lenient(true);
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame, expected);
+    assert_eq!(reader.natoms_mismatches(), 1);
+    let frames = reader.read_all_frames()?;
+    assert!(!frames.is_empty());
+    // Only the first frame was modified.
+    assert_eq!(reader.natoms_mismatches(), 1);
+
+    let header = Header::read_lenient(&mut bytes.as_slice())?;
+    assert_eq!(header.natoms_mismatch(), Some(*mismatch));
+
+    Ok(())
+}
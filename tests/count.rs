@@ -0,0 +1,35 @@
+mod common;
+use common::trajectories;
+
+fn count(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+
+    let n = reader.frame_count()?;
+    assert_eq!(n, frames.len());
+
+    // Counting the frames leaves the reader where it was.
+    let frames_with_capacity = reader.read_all_frames_with_capacity(n)?;
+    assert_eq!(frames_with_capacity, frames);
+
+    // From the end of the trajectory, there are no frames left to count.
+    assert_eq!(reader.frame_count()?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn count_adk() -> std::io::Result<()> {
+    count(trajectories::ADK)
+}
+
+#[test]
+fn count_smol() -> std::io::Result<()> {
+    count(trajectories::SMOL)
+}
+
+#[test]
+fn count_ten() -> std::io::Result<()> {
+    count(trajectories::TEN)
+}
@@ -0,0 +1,47 @@
+use molly::Frame;
+
+mod common;
+use common::trajectories;
+
+fn decode(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let bytes = std::fs::read(&path)?;
+    let mut reader = molly::XTCReader::open(&path)?;
+    let metas = reader.scan(None)?;
+    let frames = reader.read_all_frames()?;
+
+    for (meta, frame) in metas.iter().zip(frames.iter()) {
+        let start = meta.offset as usize;
+        let frame_bytes = &bytes[start..start + meta.size as usize];
+        assert_eq!(&Frame::decode_from_bytes(frame_bytes)?, frame);
+
+        // Trailing bytes are ignored.
+        assert_eq!(&Frame::decode_from_bytes(&bytes[start..])?, frame);
+
+        // But missing bytes are not.
+        let truncated = &frame_bytes[..frame_bytes.len() - 8];
+        assert!(Frame::decode_from_bytes(truncated).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn decode_adk() -> std::io::Result<()> {
+    decode(trajectories::ADK)
+}
+
+#[test]
+fn decode_cob() -> std::io::Result<()> {
+    decode(trajectories::COB)
+}
+
+#[test]
+fn decode_ten() -> std::io::Result<()> {
+    decode(trajectories::TEN)
+}
+
+#[test]
+fn decode_empty() {
+    let err = Frame::decode_from_bytes(&[]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,72 @@
+use molly::{BoxVec, Frame, Header, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn frame_display() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(
+        frame.to_string(),
+        "step 0, time 0 ps, 10 atoms, box 20.000 × 20.000 × 20.000 nm"
+    );
+
+    let boxless = Frame {
+        step: 7,
+        time: 1.5,
+        boxvec: BoxVec::ZERO,
+        ..Default::default()
+    };
+    assert_eq!(boxless.to_string(), "step 7, time 1.5 ps, 0 atoms, no box");
+
+    Ok(())
+}
+
+#[test]
+fn frame_debug_omits_positions() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    let debug = format!("{frame:?}");
+    assert!(debug.starts_with("Frame { step: "), "{debug}");
+    assert!(
+        debug.contains(&format!("natoms: {}", frame.natoms())),
+        "{debug}"
+    );
+    assert!(
+        debug.contains(&format!("npositions: {}", frame.positions.len())),
+        "{debug}"
+    );
+    // A handful of fields rather than tens of thousands of coordinates.
+    assert!(debug.len() < 600, "{debug}");
+
+    Ok(())
+}
+
+/// A frame whose positions do not come in threes can still be formatted.
+#[test]
+fn frame_malformed() {
+    let frame = Frame {
+        positions: vec![1.0, 2.0, 3.0, 4.0],
+        ..Default::default()
+    };
+    let debug = format!("{frame:?}");
+    assert!(debug.contains("natoms: 1, npositions: 4"), "{debug}");
+    assert!(debug.contains("head: [1.0, 2.0, 3.0, 4.0]"), "{debug}");
+    assert!(frame.to_string().contains("1 atoms"));
+}
+
+#[test]
+fn header_debug() -> std::io::Result<()> {
+    let mut file = std::fs::File::open(trajectories::TEN)?;
+    let header = Header::read(&mut file)?;
+    let debug = format!("{header:?}");
+    assert!(
+        debug.starts_with("Header { magic: Xtc1995, natoms: 10"),
+        "{debug}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use glam::{Mat3, Vec3};
+use molly::selection::AtomSelection;
+use molly::Frame;
+
+mod common;
+use common::{assert_close, frame_with_box, trajectories};
+
+#[test]
+fn distance_matrix_ordering() {
+    let frame = frame_with_box(
+        &[[0.0, 0.0, 0.0], [3.0, 0.0, 0.0], [0.0, 4.0, 0.0]],
+        Mat3::ZERO,
+    );
+    let distances = frame.distance_matrix(&AtomSelection::All, false);
+    // The pairs (0, 1), (0, 2), and (1, 2).
+    assert_close(&distances, &[3.0, 4.0, 5.0], 1e-5);
+}
+
+#[test]
+fn distance_matrix_selection() {
+    let frame = frame_with_box(
+        &[[0.0, 0.0, 0.0], [3.0, 0.0, 0.0], [0.0, 4.0, 0.0]],
+        Mat3::ZERO,
+    );
+    let distances = frame.distance_matrix(&AtomSelection::from_index_list(&[1, 2]), false);
+    assert_close(&distances, &[5.0], 1e-5);
+
+    let distances = frame.distance_matrix(&AtomSelection::from_index_list(&[2]), false);
+    assert!(distances.is_empty());
+
+    // An `Until` selection covers the atoms before its bound.
+    let distances = frame.distance_matrix(&AtomSelection::Until(2), false);
+    assert_close(&distances, &[3.0], 1e-5);
+    let pairs = frame.distances_within(&AtomSelection::Until(2), 10.0, false);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!((pairs[0].0, pairs[0].1), (0, 1));
+}
+
+#[test]
+fn distance_matrix_rectangular_pbc() {
+    let boxvec = Mat3::from_diagonal(Vec3::new(10.0, 10.0, 10.0));
+    let frame = frame_with_box(&[[1.0, 1.0, 1.0], [9.0, 1.0, 5.0]], boxvec);
+    assert_close(
+        &frame.distance_matrix(&AtomSelection::All, false),
+        &[(64.0f32 + 16.0).sqrt()],
+        1e-5,
+    );
+    // Across the boundary, the separation in x is 2 rather than 8.
+    assert_close(
+        &frame.distance_matrix(&AtomSelection::All, true),
+        &[(4.0f32 + 16.0).sqrt()],
+        1e-5,
+    );
+}
+
+#[test]
+fn minimum_image_triclinic() {
+    // A box in canonical form, with c tilted in x and y.
+    let boxvec = Mat3::from_cols(
+        Vec3::new(10.0, 0.0, 0.0),
+        Vec3::new(0.0, 10.0, 0.0),
+        Vec3::new(5.0, 5.0, 10.0),
+    );
+    let frame = frame_with_box(&[], boxvec);
+    // One c vector away from the origin, up to a small offset.
+    let d = frame.minimum_image(Vec3::new(5.5, 5.0, 10.0));
+    assert!((d - Vec3::new(0.5, 0.0, 0.0)).length() < 1e-6, "{d:?}");
+    let d = frame.minimum_image(Vec3::new(-9.0, 1.0, 0.0));
+    assert!((d - Vec3::new(1.0, 1.0, 0.0)).length() < 1e-6, "{d:?}");
+}
+
+#[test]
+fn minimum_image_without_box() {
+    let frame = frame_with_box(&[], Mat3::ZERO);
+    let d = Vec3::new(25.0, -30.0, 12.0);
+    assert_eq!(frame.minimum_image(d), d);
+}
+
+/// Dimensions with a box length of zero are not periodic, and never give NaN distances.
+#[test]
+fn minimum_image_zero_diagonal() {
+    let slab = Mat3::from_diagonal(Vec3::new(10.0, 10.0, 0.0));
+    let slab = frame_with_box(&[[1.0, 1.0, 1.0], [9.0, 1.0, 25.0]], slab);
+    let d = slab.minimum_image(Vec3::new(8.0, 0.0, 24.0));
+    assert_eq!(d, Vec3::new(-2.0, 0.0, 24.0));
+    assert_close(
+        &slab.distance_matrix(&AtomSelection::All, true),
+        &[(4.0f32 + 576.0).sqrt()],
+        1e-5,
+    );
+
+    // A partially written box with only an off-diagonal value.
+    let mut boxvec = Mat3::ZERO;
+    boxvec.z_axis.x = 3.0;
+    let partial = frame_with_box(&[[1.0, 1.0, 1.0], [9.0, 1.0, 5.0]], boxvec);
+    assert!(partial.has_box());
+    assert_close(
+        &partial.distance_matrix(&AtomSelection::All, true),
+        &[(64.0f32 + 16.0).sqrt()],
+        1e-5,
+    );
+}
+
+#[test]
+fn distances_within_cutoff() {
+    let boxvec = Mat3::from_diagonal(Vec3::new(10.0, 10.0, 10.0));
+    let frame = frame_with_box(
+        &[
+            [1.0, 1.0, 1.0],
+            [2.0, 1.0, 1.0],
+            [9.5, 1.0, 1.0],
+            [5.0, 5.0, 5.0],
+        ],
+        boxvec,
+    );
+
+    let pairs = frame.distances_within(&AtomSelection::All, 1.6, false);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!((pairs[0].0, pairs[0].1), (0, 1));
+
+    let pairs = frame.distances_within(&AtomSelection::All, 1.6, true);
+    let indices: Vec<_> = pairs.iter().map(|&(a, b, _)| (a, b)).collect();
+    assert_eq!(indices, [(0, 1), (0, 2)]);
+    assert!((pairs[1].2 - 1.5).abs() < 1e-6);
+
+    // The indices refer to the frame, not to the selection.
+    let pairs = frame.distances_within(&AtomSelection::from_index_list(&[1, 2]), 3.0, true);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!((pairs[0].0, pairs[0].1), (1, 2));
+    assert!((pairs[0].2 - 2.5).abs() < 1e-6);
+}
+
+#[test]
+fn distances_within_agrees_with_matrix() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+
+    let selection = AtomSelection::Until(200);
+    let cutoff = 0.5;
+    let matrix = frame.distance_matrix(&selection, true);
+    let close: Vec<f32> = matrix.into_iter().filter(|&d| d <= cutoff).collect();
+    let pairs = frame.distances_within(&selection, cutoff, true);
+    assert!(!pairs.is_empty());
+    assert_close(
+        &pairs.iter().map(|&(_, _, d)| d).collect::<Vec<_>>(),
+        &close,
+        1e-5,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use molly::selection::AtomSelection;
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+/// Reading past the last frame signals a clean end, rather than an error.
+#[test]
+fn clean_end() -> std::io::Result<()> {
+    let expected = XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frames = Vec::new();
+    while let Some(frame) = reader.read_next_frame(&AtomSelection::All)? {
+        frames.push(frame);
+    }
+    assert_eq!(frames.as_slice(), &*expected);
+    assert_eq!(reader.step, expected.len());
+
+    // Repeated reads at the end stay clean.
+    assert!(reader.read_next_frame(&AtomSelection::All)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn clean_end_empty() -> std::io::Result<()> {
+    let mut reader = XTCReader::new([].as_slice());
+    assert!(reader.read_next_frame(&AtomSelection::All)?.is_none());
+    assert!(reader.read_all_frames()?.is_empty());
+    Ok(())
+}
+
+/// A trajectory cut off in the middle of its last frame results in an error when reading frame by
+/// frame.
+#[test]
+fn truncated() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let metas = XTCReader::open(trajectories::ADK)?.scan(None)?;
+    let last = metas.last().unwrap().offset as usize;
+
+    // Cut off within the header and within the positions of the last frame.
+    for end in [last + 10, last + 100, bytes.len() - 1] {
+        let truncated = &bytes[..end];
+
+        let mut reader = XTCReader::new(truncated);
+        for _ in 0..metas.len() - 1 {
+            assert!(reader.read_next_frame(&AtomSelection::All)?.is_some());
+        }
+        let err = reader.read_next_frame(&AtomSelection::All).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        // Reading all frames salvages the complete frames before the truncated one.
+        let frames = XTCReader::new(truncated).read_all_frames()?;
+        assert_eq!(frames.len(), metas.len() - 1);
+        let n = XTCReader::new(truncated).fold_frames(0, |n, _| n + 1)?;
+        assert_eq!(n, metas.len() - 1);
+    }
+    Ok(())
+}
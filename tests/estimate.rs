@@ -0,0 +1,55 @@
+use std::num::NonZeroU64;
+
+use molly::selection::{AtomSelection, FrameSelection, Range};
+
+mod common;
+use common::trajectories;
+
+/// The estimate must be equal to the size of the positions that are actually read.
+fn estimate(
+    path: impl AsRef<std::path::Path>,
+    frame_selection: FrameSelection,
+    atom_selection: AtomSelection,
+) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let estimate = reader.estimate_decoded_size(&frame_selection, &atom_selection)?;
+
+    let mut frames = Vec::new();
+    reader.read_frames::<false>(&mut frames, &frame_selection, &atom_selection)?;
+    let size: usize = frames
+        .iter()
+        .map(|frame| frame.positions.len() * std::mem::size_of::<f32>())
+        .sum();
+    assert_eq!(estimate, size as u64);
+
+    Ok(())
+}
+
+#[test]
+fn estimate_all() -> std::io::Result<()> {
+    estimate(trajectories::ADK, FrameSelection::All, AtomSelection::All)
+}
+
+#[test]
+fn estimate_selections() -> std::io::Result<()> {
+    let frame_selection = FrameSelection::Range(Range::new(Some(2), Some(9), NonZeroU64::new(3)));
+    estimate(
+        trajectories::ADK,
+        frame_selection.clone(),
+        AtomSelection::Until(1000),
+    )?;
+    estimate(
+        trajectories::ADK,
+        frame_selection,
+        AtomSelection::from_index_list(&[1, 10, 100, 1000]),
+    )
+}
+
+#[test]
+fn estimate_smol() -> std::io::Result<()> {
+    estimate(
+        trajectories::SMOL,
+        FrameSelection::framelist_from_iter([0, 500, 1000, 5000]),
+        AtomSelection::All,
+    )
+}
@@ -0,0 +1,28 @@
+#![cfg(unix)]
+
+use std::os::unix::io::IntoRawFd;
+
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn from_raw_fd() -> std::io::Result<()> {
+    let expected = XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+
+    let fd = std::fs::File::open(trajectories::ADK)?.into_raw_fd();
+    // Safety: The descriptor was just opened and its ownership released to us.
+    let mut reader = unsafe { XTCReader::from_raw_fd(fd) };
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    // Going home returns to the start of the file.
+    reader.home()?;
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    // Without a path, the reader cannot be reopened.
+    let err = reader.reopen().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+
+    Ok(())
+}
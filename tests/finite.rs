@@ -0,0 +1,80 @@
+use std::io::Cursor;
+
+use glam::{Mat3, Vec3};
+use molly::{Frame, Magic, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn finite_frames() -> std::io::Result<()> {
+    for path in [trajectories::ADK, trajectories::COB, trajectories::TEN] {
+        let frames = XTCReader::open(path)?.read_all_frames()?;
+        assert!(frames.iter().all(Frame::is_finite), "{path}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn non_finite() {
+    let frame = Frame {
+        positions: vec![1.0; 3 * 12],
+        boxvec: Mat3::from_diagonal(Vec3::splat(3.0)),
+        ..Default::default()
+    };
+    assert!(frame.is_finite());
+
+    let mut nan = frame.clone();
+    nan.positions[7] = f32::NAN;
+    assert!(!nan.is_finite());
+
+    let infinite_box = Frame {
+        boxvec: Mat3::from_diagonal(Vec3::new(3.0, f32::INFINITY, 3.0)),
+        ..frame
+    };
+    assert!(!infinite_box.is_finite());
+}
+
+/// In strict mode, a frame with an infinite box is rejected, and the frames after it can still be
+/// read.
+#[test]
+fn strict_finite() -> std::io::Result<()> {
+    let frames: Vec<_> = (0..3)
+        .map(|step| Frame {
+            step,
+            positions: vec![1.0; 3 * 12],
+            precision: molly::Precision::Compressed(1000.0),
+            boxvec: Mat3::from_diagonal(Vec3::splat(3.0)),
+            ..Default::default()
+        })
+        .collect();
+    let mut bytes = Vec::new();
+    for (idx, frame) in frames.iter().enumerate() {
+        let frame = match idx {
+            1 => Frame {
+                boxvec: Mat3::from_diagonal(Vec3::splat(f32::INFINITY)),
+                ..frame.clone()
+            },
+            _ => frame.clone(),
+        };
+        molly::writer::write_frame(&mut bytes, &frame, Magic::Xtc1995)?;
+    }
+
+    // By default, the infinite box is read like any other.
+    let read = XTCReader::new(Cursor::new(&bytes)).read_all_frames()?;
+    assert_eq!(read.len(), 3);
+    assert!(!read[1].is_finite());
+
+    let mut reader = XTCReader::new(Cursor::new(&bytes));
+    reader.set_strict_finite(true);
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame, frames[0]);
+    let err = reader.read_frame(&mut frame).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame, frames[2]);
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+mod common;
+use common::trajectories;
+
+fn fold(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+
+    // Count the frames and sum the first coordinate in a single streaming pass.
+    let (n, sum) = reader.fold_frames((0, 0.0), |(n, sum), frame| {
+        (
+            n + 1,
+            sum + frame.positions.first().copied().unwrap_or_default(),
+        )
+    })?;
+
+    assert_eq!(n, frames.len(), "the fold should visit every frame");
+    let expected: f32 = frames
+        .iter()
+        .map(|frame| frame.positions.first().copied().unwrap_or_default())
+        .sum();
+    assert_eq!(sum, expected);
+
+    // After folding, the reader is exhausted.
+    assert_eq!(reader.fold_frames(0, |n, _| n + 1)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn fold_adk() -> std::io::Result<()> {
+    fold(trajectories::ADK)
+}
+
+#[test]
+fn fold_smol() -> std::io::Result<()> {
+    fold(trajectories::SMOL)
+}
+
+#[test]
+fn fold_ten() -> std::io::Result<()> {
+    fold(trajectories::TEN)
+}
@@ -0,0 +1,68 @@
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn framelist() -> std::io::Result<()> {
+    let all = XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+    let indices = [0, 3, 4, 9, 1000];
+    let selection = FrameSelection::framelist_from_iter(indices);
+
+    for buffered in [false, true] {
+        let mut reader = XTCReader::open(trajectories::ADK)?;
+        let mut frames = Vec::new();
+        let n = match buffered {
+            false => reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?,
+            true => reader.read_frames::<true>(&mut frames, &selection, &AtomSelection::All)?,
+        };
+        // The index beyond the end of the trajectory is skipped.
+        assert_eq!(n, 4);
+        assert_eq!(frames.len(), n);
+        for (frame, idx) in frames.iter().zip(indices) {
+            assert_eq!(frame.index, Some(idx));
+            assert_eq!(frame, &all[idx]);
+        }
+    }
+
+    Ok(())
+}
+
+/// The frames after the last frame in a list are never visited, not even their headers.
+#[test]
+fn framelist_bounded() -> std::io::Result<()> {
+    let mut bytes = std::fs::read(trajectories::ADK)?;
+    let metas = XTCReader::open(trajectories::ADK)?.scan(None)?;
+    // Cut off the trajectory after its sixth frame, and follow it by garbage.
+    bytes.truncate(metas[6].offset as usize);
+    bytes.extend([0xff; 256]);
+
+    let mut reader = XTCReader::new(std::io::Cursor::new(&bytes));
+    let mut frames = Vec::new();
+    let selection = FrameSelection::framelist_from_iter([1, 5]);
+    assert_eq!(
+        reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?,
+        2
+    );
+
+    // Reading all frames runs into the garbage.
+    reader.home()?;
+    assert!(reader
+        .read_frames::<false>(&mut frames, &FrameSelection::All, &AtomSelection::All)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn framelist_empty() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frames = Vec::new();
+    let selection = FrameSelection::FrameList(Default::default());
+    let n = reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?;
+    assert_eq!(n, 0);
+    assert!(frames.is_empty());
+
+    Ok(())
+}
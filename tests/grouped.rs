@@ -0,0 +1,97 @@
+use glam::Vec3;
+
+mod common;
+use common::trajectories;
+
+fn first_frame() -> std::io::Result<molly::Frame> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let mut frame = molly::Frame::default();
+    reader.read_frame(&mut frame)?;
+    Ok(frame)
+}
+
+#[test]
+fn grouped() -> std::io::Result<()> {
+    let frame = first_frame()?;
+    let natoms = frame.natoms();
+    let coords: Vec<Vec3> = frame.coords().collect();
+
+    for group_size in [1, 2, natoms] {
+        if natoms % group_size != 0 {
+            continue;
+        }
+        let groups: Vec<&[Vec3]> = frame.coords_grouped(group_size).collect();
+        assert_eq!(groups.len(), natoms / group_size);
+        assert!(groups.iter().all(|group| group.len() == group_size));
+        assert_eq!(groups.concat(), coords);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn grouped_center() {
+    // Two molecules of three atoms each.
+    let frame = molly::Frame {
+        positions: vec![
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0, //
+            0.0, 3.0, 0.0, 0.0, 3.0, 3.0, 0.0, 3.0, 6.0,
+        ],
+        ..Default::default()
+    };
+    let centers: Vec<Vec3> = frame
+        .coords_grouped(3)
+        .map(|group| group.iter().sum::<Vec3>() / group.len() as f32)
+        .collect();
+    assert_eq!(
+        centers,
+        [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 3.0, 3.0)]
+    );
+}
+
+#[test]
+#[should_panic(expected = "must divide the number of atoms")]
+fn grouped_indivisible() {
+    let frame = molly::Frame {
+        positions: vec![0.0; 4 * 3],
+        ..Default::default()
+    };
+    let _ = frame.coords_grouped(3).count();
+}
+
+#[test]
+#[should_panic(expected = "must divide the number of atoms")]
+fn grouped_zero() {
+    let frame = molly::Frame {
+        positions: vec![0.0; 4 * 3],
+        ..Default::default()
+    };
+    let _ = frame.coords_grouped(0).count();
+}
+
+#[test]
+fn atom() -> std::io::Result<()> {
+    let frame = first_frame()?;
+    let natoms = frame.natoms();
+    let coords: Vec<Vec3> = frame.coords().collect();
+
+    for i in [0, 1, natoms / 2, natoms - 1] {
+        assert_eq!(frame.atom(i), Some(coords[i]));
+    }
+    assert_eq!(frame.atom(natoms), None);
+    assert_eq!(frame.atom(usize::MAX), None);
+
+    Ok(())
+}
+
+#[test]
+fn atom_slice() -> std::io::Result<()> {
+    let frame = first_frame()?;
+    let natoms = frame.natoms();
+
+    assert_eq!(frame.atom_slice(0..natoms), frame.positions.as_slice());
+    assert_eq!(frame.atom_slice(2..5), &frame.positions[6..15]);
+    assert!(frame.atom_slice(3..3).is_empty());
+
+    Ok(())
+}
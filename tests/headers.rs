@@ -0,0 +1,92 @@
+use molly::{Frame, Header, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn headers_match_scan() -> std::io::Result<()> {
+    for path in [
+        trajectories::ADK,
+        trajectories::COB,
+        trajectories::SMOL,
+        trajectories::TEN,
+        trajectories::XYZ,
+    ] {
+        let mut reader = XTCReader::open(path)?;
+        let metas = reader.scan(None)?;
+        let headers = reader.headers().collect::<std::io::Result<Vec<_>>>()?;
+        assert_eq!(headers.len(), metas.len(), "{path}");
+        for (header, meta) in headers.iter().zip(&metas) {
+            assert_eq!(header.step, meta.step);
+            assert_eq!(header.time, meta.time);
+            assert_eq!(header.natoms, meta.natoms);
+            assert_eq!(header.boxvec, meta.boxvec);
+        }
+        assert_eq!(reader.step, metas.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn headers_lazy() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let times = reader
+        .headers()
+        .take(3)
+        .map(|header| header.map(|header| header.time))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(times.len(), 3);
+
+    // The reader is left right after the headers that were taken.
+    let metas = {
+        let mut reader = XTCReader::open(trajectories::ADK)?;
+        reader.scan(None)?
+    };
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame.time, metas[3].time);
+
+    Ok(())
+}
+
+#[test]
+fn headers_error() -> std::io::Result<()> {
+    // A trajectory that is cut off in the middle of its third header.
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let metas = XTCReader::new(std::io::Cursor::new(&bytes)).scan(None)?;
+    let cut = metas[2].offset as usize + 20;
+    let mut reader = XTCReader::new(std::io::Cursor::new(&bytes[..cut]));
+    let results: Vec<_> = reader.headers().collect();
+    // The iterator stops after yielding the error.
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok() && results[1].is_ok());
+    let err = results[2].as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
+
+/// A header with values that do not fit in its 32-bit fields cannot be written.
+#[test]
+fn header_to_be_bytes_out_of_range() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let header = Header::read(&mut &bytes[..])?;
+    assert_eq!(header.to_be_bytes()?, bytes[..Header::SIZE]);
+
+    let remapped = Header {
+        step: u32::MAX as u64 + 1,
+        ..Header::read(&mut &bytes[..])?
+    };
+    let err = remapped.to_be_bytes().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let mismatch = Header {
+        natoms_repeated: header.natoms + 1,
+        ..header
+    };
+    let err = mismatch.to_be_bytes().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::io::Cursor;
+
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn indexed() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let expected = reader.determine_offsets(None)?;
+
+    let mut indexed = XTCReader::open_indexed(trajectories::ADK)?;
+    assert_eq!(indexed.step, 0);
+    assert_eq!(indexed.determine_offsets(None)?, expected);
+
+    let selection = FrameSelection::framelist_from_iter([2, 5, 7]);
+    let mut frames = Vec::new();
+    indexed.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?;
+    let mut expected_frames = Vec::new();
+    reader.read_frames::<false>(&mut expected_frames, &selection, &AtomSelection::All)?;
+    assert_eq!(frames, expected_frames);
+
+    Ok(())
+}
+
+/// Indexing leaves the position of the reader unchanged, and works for any seekable reader.
+#[test]
+fn index_in_memory() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::COB)?;
+    let mut reader = XTCReader::new(Cursor::new(&bytes));
+    let first = reader.read_header()?;
+    let pos = reader.file.position();
+
+    reader.index()?;
+    assert_eq!(reader.file.position(), pos);
+    reader.home()?;
+    let offsets = reader.determine_offsets(None)?;
+    assert_eq!(
+        offsets,
+        XTCReader::open(trajectories::COB)?.determine_offsets(None)?
+    );
+    assert_eq!(reader.read_header()?.step, first.step);
+
+    Ok(())
+}
+
+/// A trajectory of which the last frame is cut off is rejected up front.
+#[test]
+fn indexed_truncated() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let mut reader = XTCReader::new(Cursor::new(&bytes[..bytes.len() - 100]));
+    let err = reader.index().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
+
+/// A trajectory with a corrupt header is rejected up front.
+#[test]
+fn indexed_corrupt() -> std::io::Result<()> {
+    let mut bytes = std::fs::read(trajectories::ADK)?;
+    let metas = XTCReader::open(trajectories::ADK)?.scan(None)?;
+    let offset = metas[3].offset as usize;
+    bytes[offset..offset + 4].copy_from_slice(&1234_i32.to_be_bytes()); // Magic number.
+
+    let err = XTCReader::new(Cursor::new(&bytes)).index().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn indexed_empty() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(Cursor::new(Vec::new()));
+    reader.index()?;
+    assert!(reader.determine_offsets(None)?.is_empty());
+
+    Ok(())
+}
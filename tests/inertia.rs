@@ -0,0 +1,83 @@
+use glam::{Mat3, Vec3};
+use molly::Frame;
+
+mod common;
+use common::{assert_close, frame, trajectories};
+
+fn assert_rotation(axes: Mat3) {
+    assert!((axes.transpose() * axes).abs_diff_eq(Mat3::IDENTITY, 1e-4));
+    assert!((axes.determinant() - 1.0).abs() < 1e-4);
+}
+
+/// Two unit masses at (±1, 0, 0), a rod along the x axis.
+#[test]
+fn rod() {
+    let frame = frame(&[[-1.0, 2.0, 3.0], [1.0, 2.0, 3.0]]);
+    let tensor = frame.inertia_tensor(None);
+    assert_eq!(tensor, Mat3::from_diagonal(Vec3::new(0.0, 2.0, 2.0)));
+
+    // The atoms are collinear, so the two largest moments are degenerate.
+    let (moments, axes) = frame.principal_axes(None);
+    assert_close(moments.as_ref(), Vec3::new(0.0, 2.0, 2.0).as_ref(), 1e-4);
+    assert_close(axes.x_axis.abs().as_ref(), Vec3::X.as_ref(), 1e-4);
+    assert_rotation(axes);
+}
+
+#[test]
+fn weighted() {
+    // The center of mass is at the origin. Each atom is at distance 1 and 3 along y.
+    let frame = frame(&[[0.0, 1.0, 0.0], [0.0, -3.0, 0.0]]);
+    let tensor = frame.inertia_tensor(Some(&[3.0, 1.0]));
+    assert_eq!(tensor, Mat3::from_diagonal(Vec3::new(12.0, 0.0, 12.0)));
+
+    let (moments, axes) = frame.principal_axes(Some(&[3.0, 1.0]));
+    assert_close(moments.as_ref(), Vec3::new(0.0, 12.0, 12.0).as_ref(), 1e-4);
+    assert_close(axes.x_axis.abs().as_ref(), Vec3::Y.as_ref(), 1e-4);
+}
+
+#[test]
+fn single_atom() {
+    let frame = frame(&[[1.0, 2.0, 3.0]]);
+    assert_eq!(frame.inertia_tensor(None), Mat3::ZERO);
+
+    let (moments, axes) = frame.principal_axes(None);
+    assert_eq!(moments, Vec3::ZERO);
+    assert_eq!(axes, Mat3::IDENTITY);
+}
+
+#[test]
+fn empty() {
+    let frame = Frame::default();
+    assert_eq!(frame.inertia_tensor(None), Mat3::ZERO);
+    let (moments, axes) = frame.principal_axes(None);
+    assert!(moments.is_finite() && axes.is_finite());
+}
+
+#[test]
+fn principal_axes_adk() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+
+    let tensor = frame.inertia_tensor(None);
+    let (moments, axes) = frame.principal_axes(None);
+    assert!(moments.x <= moments.y && moments.y <= moments.z);
+    assert_rotation(axes);
+
+    // The axes diagonalize the tensor.
+    let diagonal = axes.transpose() * tensor * axes;
+    let scale = moments.z;
+    assert!(
+        diagonal.abs_diff_eq(Mat3::from_diagonal(moments), scale * 1e-5),
+        "{diagonal:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "the number of masses must be equal to the number of atoms")]
+fn inertia_tensor_mismatch() {
+    let frame = frame(&[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+    frame.inertia_tensor(Some(&[1.0]));
+}
@@ -0,0 +1,74 @@
+use molly::selection::AtomSelection;
+use molly::Frame;
+
+mod common;
+use common::trajectories;
+
+fn into(
+    path: impl AsRef<std::path::Path>,
+    positions: &mut Vec<f32>,
+    atom_selection: AtomSelection,
+) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while reader
+        .read_frame_with_selection(&mut frame, &atom_selection)
+        .is_ok()
+    {
+        frames.push(frame.clone());
+    }
+    reader.home()?;
+
+    for frame in &frames {
+        let header = reader.read_frame_into(positions, &atom_selection)?;
+        assert_eq!(header.step, frame.step);
+        assert_eq!(header.time, frame.time);
+        assert_eq!(header.boxvec, frame.boxvec);
+        assert_eq!(positions, &frame.positions);
+    }
+    assert_eq!(reader.step, frames.len());
+    assert!(reader.read_frame_into(positions, &atom_selection).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn into_adk() -> std::io::Result<()> {
+    into(trajectories::ADK, &mut Vec::new(), AtomSelection::All)
+}
+
+#[test]
+fn into_adk_until() -> std::io::Result<()> {
+    into(
+        trajectories::ADK,
+        &mut Vec::new(),
+        AtomSelection::Until(100),
+    )
+}
+
+#[test]
+fn into_adk_mask() -> std::io::Result<()> {
+    into(
+        trajectories::ADK,
+        &mut Vec::new(),
+        AtomSelection::from_index_list(&[1, 5, 8, 13, 400]),
+    )
+}
+
+#[test]
+fn into_xyz() -> std::io::Result<()> {
+    into(trajectories::XYZ, &mut Vec::new(), AtomSelection::Until(3))
+}
+
+/// A single buffer can be reused across files without being reallocated.
+#[test]
+fn into_pooled() -> std::io::Result<()> {
+    let mut positions = Vec::with_capacity(1 << 20);
+    let ptr = positions.as_ptr();
+    for path in [trajectories::ADK, trajectories::TEN, trajectories::SMOL] {
+        into(path, &mut positions, AtomSelection::All)?;
+    }
+    assert_eq!(positions.as_ptr(), ptr);
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use molly::selection::AtomSelection;
+use molly::Frame;
+
+mod common;
+use common::trajectories;
+
+fn last(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let frames = reader.read_all_frames()?;
+    let expected = frames.last().expect("the trajectory should contain frames");
+
+    let mut frame = Frame::default();
+    reader.read_last_frame(&mut frame, &AtomSelection::All)?;
+    assert_eq!(&frame, expected);
+    assert_eq!(reader.step, frames.len());
+
+    // The reader is at the end of the trajectory, now.
+    assert!(reader.read_frame(&mut frame).is_err());
+
+    // With an atom selection.
+    let selection = AtomSelection::Until(4);
+    reader.read_last_frame(&mut frame, &selection)?;
+    let natoms = usize::min(expected.natoms(), 4);
+    assert_eq!(frame.positions, expected.positions[..natoms * 3]);
+    assert_eq!(frame.step, expected.step);
+    assert_eq!(frame.time, expected.time);
+
+    Ok(())
+}
+
+#[test]
+fn last_adk() -> std::io::Result<()> {
+    last(trajectories::ADK)
+}
+
+#[test]
+fn last_smol() -> std::io::Result<()> {
+    last(trajectories::SMOL)
+}
+
+#[test]
+fn last_ten() -> std::io::Result<()> {
+    last(trajectories::TEN)
+}
+
+#[test]
+fn last_empty() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::new(std::io::Cursor::new(Vec::new()));
+    let err = reader
+        .read_last_frame(&mut Frame::default(), &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    Ok(())
+}
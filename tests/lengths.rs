@@ -0,0 +1,57 @@
+use glam::{Mat3, Vec3};
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::{assert_close, trajectories};
+
+fn lengths_and_angles(boxvec: Mat3) -> ([f32; 3], [f32; 3]) {
+    Frame {
+        boxvec,
+        ..Frame::default()
+    }
+    .box_lengths_and_angles()
+}
+
+#[test]
+fn rectangular() {
+    let (lengths, angles) = lengths_and_angles(Mat3::from_diagonal(Vec3::new(3.0, 4.0, 5.0)));
+    assert_eq!(lengths, [3.0, 4.0, 5.0]);
+    assert_eq!(angles, [90.0, 90.0, 90.0]);
+}
+
+#[test]
+fn rhombic_dodecahedron() {
+    // The xy-square rhombic dodecahedron, as written by `gmx editconf -bt dodecahedron`.
+    let d = 6.0;
+    let boxvec = Mat3::from_cols(
+        Vec3::new(d, 0.0, 0.0),
+        Vec3::new(0.0, d, 0.0),
+        Vec3::new(d / 2.0, d / 2.0, d * std::f32::consts::FRAC_1_SQRT_2),
+    );
+    let (lengths, angles) = lengths_and_angles(boxvec);
+    assert_close(&lengths, &[d, d, d], 1e-4);
+    assert_close(&angles, &[60.0, 60.0, 90.0], 1e-4);
+}
+
+#[test]
+fn boxless() {
+    let (lengths, angles) = lengths_and_angles(Mat3::ZERO);
+    assert_eq!(lengths, [0.0; 3]);
+    assert_eq!(angles, [90.0; 3]);
+}
+
+#[test]
+fn from_file() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    let (lengths, angles) = frame.box_lengths_and_angles();
+    let rows = frame.box_rows();
+    for (length, row) in lengths.iter().zip(rows) {
+        let expected = row.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((length - expected).abs() < 1e-5);
+    }
+    assert!(angles.iter().all(|&angle| angle > 0.0 && angle < 180.0));
+
+    Ok(())
+}
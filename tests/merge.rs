@@ -0,0 +1,76 @@
+use molly::selection::AtomSelection;
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+fn read_selection(path: &str, atom_selection: &AtomSelection) -> std::io::Result<Frame> {
+    let mut reader = XTCReader::open(path)?;
+    let mut frame = Frame::default();
+    reader.read_frame_with_selection(&mut frame, atom_selection)?;
+    Ok(frame)
+}
+
+#[test]
+fn merge_selections() -> std::io::Result<()> {
+    let path = trajectories::ADK;
+    let all = read_selection(path, &AtomSelection::All)?;
+    let natoms = all.natoms() as u32;
+
+    let head = read_selection(path, &AtomSelection::Until(100))?;
+    let tail = read_selection(
+        path,
+        &AtomSelection::from_index_list(&Vec::from_iter(100..natoms)),
+    )?;
+    let merged = Frame::merge(&[head, tail])?;
+    assert_eq!(merged, all);
+
+    Ok(())
+}
+
+#[test]
+fn merge_order() -> std::io::Result<()> {
+    let a = read_selection(trajectories::ADK, &AtomSelection::Until(10))?;
+    let b = read_selection(
+        trajectories::ADK,
+        &AtomSelection::from_index_list(&[20, 21, 22]),
+    )?;
+    let merged = Frame::merge(&[b.clone(), a.clone()])?;
+    assert_eq!(merged.natoms(), 13);
+    assert_eq!(&merged.positions[..9], &b.positions[..]);
+    assert_eq!(&merged.positions[9..], &a.positions[..]);
+    assert_eq!(merged.step, a.step);
+
+    Ok(())
+}
+
+#[test]
+fn merge_empty() -> std::io::Result<()> {
+    assert_eq!(Frame::merge(&[])?, Frame::default());
+
+    let frame = read_selection(trajectories::COB, &AtomSelection::Until(10))?;
+    assert_eq!(Frame::merge(std::slice::from_ref(&frame))?, frame);
+
+    Ok(())
+}
+
+#[test]
+fn merge_mismatch() -> std::io::Result<()> {
+    let frame = read_selection(trajectories::ADK, &AtomSelection::Until(10))?;
+
+    let mut later = frame.clone();
+    later.step += 1;
+    let err = Frame::merge(&[frame.clone(), later]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("step"), "{err}");
+
+    let mut later = frame.clone();
+    later.time += 1.0;
+    assert!(Frame::merge(&[frame.clone(), later]).is_err());
+
+    let mut scaled = frame.clone();
+    scaled.boxvec *= 2.0;
+    assert!(Frame::merge(&[frame.clone(), frame.clone(), scaled]).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::io::Cursor;
+
+mod common;
+use common::trajectories;
+
+fn read_frame_meta(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+    let metas = reader.scan(None)?;
+
+    for (idx, (frame, meta)) in frames.iter().zip(metas).enumerate() {
+        let summary = reader.read_frame_meta()?;
+        assert_eq!(reader.step, idx + 1);
+        assert_eq!(summary.meta, meta);
+        assert_eq!(summary.precision, frame.precision);
+
+        // The extent bounds the integer positions of all atoms.
+        match (summary.precision, summary.extent) {
+            (molly::Precision::Compressed(precision), Some((minint, maxint))) => {
+                for coord in frame.coords() {
+                    for ((v, min), max) in coord.to_array().into_iter().zip(minint).zip(maxint) {
+                        let v = (v * precision).round() as i32;
+                        assert!((min..=max).contains(&v), "{v} lies outside {min}..={max}");
+                    }
+                }
+            }
+            (molly::Precision::Uncompressed, None) => assert!(frame.natoms() <= 9),
+            (precision, extent) => panic!("unexpected {precision:?} with {extent:?}"),
+        }
+    }
+
+    // The reader is left at the end, like it is after reading all frames.
+    assert!(reader.read_frame_meta().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn read_frame_meta_adk() -> std::io::Result<()> {
+    read_frame_meta(trajectories::ADK)
+}
+
+#[test]
+fn read_frame_meta_cob() -> std::io::Result<()> {
+    read_frame_meta(trajectories::COB)
+}
+
+#[test]
+fn read_frame_meta_smol() -> std::io::Result<()> {
+    read_frame_meta(trajectories::SMOL)
+}
+
+#[test]
+fn read_frame_meta_ten() -> std::io::Result<()> {
+    read_frame_meta(trajectories::TEN)
+}
+
+/// Reading the metadata of a frame leaves the reader at the start of the next frame.
+#[test]
+fn interleaved() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::COB)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+
+    let mut frame = molly::Frame::default();
+    assert_eq!(reader.read_frame_meta()?.meta.step, frames[0].step);
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame, frames[1]);
+    assert_eq!(reader.read_frame_meta()?.meta.step, frames[2].step);
+
+    Ok(())
+}
+
+/// Frames of a few atoms store their positions uncompressed, without a precision or extent.
+#[test]
+fn uncompressed() -> std::io::Result<()> {
+    let frame = molly::Frame {
+        positions: vec![0.5; 3 * 3],
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    molly::writer::write_frame(&mut bytes, &frame, molly::Magic::Xtc1995)?;
+
+    let summary = molly::XTCReader::new(Cursor::new(&bytes)).read_frame_meta()?;
+    assert_eq!(summary.precision, molly::Precision::Uncompressed);
+    assert_eq!(summary.extent, None);
+    assert_eq!(summary.meta.natoms, 3);
+    assert_eq!(summary.meta.size, bytes.len() as u64);
+
+    Ok(())
+}
+
+/// A frame that is cut short is reported, rather than summarized with a size beyond the end.
+#[test]
+fn truncated() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::COB)?;
+    let bytes = reader.read_raw_frames(0, 2)?;
+    for len in [bytes.len() - 1, bytes.len() - 100] {
+        let mut reader = molly::XTCReader::new(Cursor::new(&bytes[..len]));
+        reader.read_frame_meta()?;
+        let err = reader.read_frame_meta().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    // The same holds for a frame of uncompressed positions.
+    let frame = molly::Frame {
+        positions: vec![0.5; 3 * 3],
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    molly::writer::write_frame(&mut bytes, &frame, molly::Magic::Xtc1995)?;
+    let mut reader = molly::XTCReader::new(Cursor::new(&bytes[..bytes.len() - 1]));
+    let err = reader.read_frame_meta().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
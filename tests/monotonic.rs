@@ -0,0 +1,110 @@
+use std::io::Cursor;
+
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::writer::XTCWriter;
+use molly::{Frame, NonMonotonicTime, XTCReader};
+
+mod common;
+use common::trajectories;
+
+/// Returns the frames of a trajectory in which time goes backward at frame 6.
+fn backward() -> std::io::Result<Cursor<Vec<u8>>> {
+    let mut frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    frames[6].time = frames[4].time;
+    let mut writer = XTCWriter::new(Vec::new());
+    for frame in &frames {
+        writer.write_frame(frame)?;
+    }
+    Ok(Cursor::new(writer.file))
+}
+
+fn assert_non_monotonic(err: std::io::Error) {
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let err = err
+        .get_ref()
+        .and_then(|err| err.downcast_ref::<NonMonotonicTime>())
+        .expect("the error should carry a NonMonotonicTime");
+    assert_eq!(err.frame, 6);
+    assert!(err.cur < err.prev);
+}
+
+#[test]
+fn lax_by_default() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(backward()?);
+    assert_eq!(reader.read_all_frames()?.len(), 10);
+    reader.home()?;
+    assert_eq!(reader.scan(None)?.len(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn strict_scan() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(backward()?);
+    reader.set_strict_time(true);
+    assert_non_monotonic(reader.scan(None).unwrap_err());
+    // The position of the reader is restored.
+    assert_eq!(reader.scan(Some(6))?.len(), 6);
+
+    let mut frames = Vec::new();
+    let err = reader
+        .read_frames::<false>(&mut frames, &FrameSelection::All, &AtomSelection::All)
+        .unwrap_err();
+    assert_non_monotonic(err);
+
+    Ok(())
+}
+
+#[test]
+fn strict_sequential() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(backward()?);
+    reader.set_strict_time(true);
+    let mut frame = Frame::default();
+    for _ in 0..6 {
+        reader.read_frame(&mut frame)?;
+    }
+    assert_non_monotonic(reader.read_frame(&mut frame).unwrap_err());
+
+    // After going home, the frames are checked anew.
+    reader.home()?;
+    reader.read_frame(&mut frame)?;
+
+    Ok(())
+}
+
+#[test]
+fn strict_intact() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    reader.set_strict_time(true);
+    assert_eq!(reader.read_all_frames()?.len(), 10);
+    reader.home()?;
+    assert_eq!(reader.scan(None)?.len(), 10);
+    // Frames read in reverse are not compared to each other.
+    reader.home()?;
+    assert_eq!(
+        reader
+            .frames_rev()
+            .collect::<std::io::Result<Vec<_>>>()?
+            .len(),
+        10
+    );
+
+    Ok(())
+}
+
+/// Probing the time bounds does not take part in the strict time check of sequential reads.
+#[test]
+fn strict_time_bounds() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(backward()?);
+    reader.set_strict_time(true);
+    let mut frame = Frame::default();
+    for _ in 0..6 {
+        reader.read_frame(&mut frame)?;
+    }
+    let (first, _) = reader.time_bounds()?;
+    assert!(first < frame.time);
+    assert_eq!(reader.step, 6);
+    assert_non_monotonic(reader.read_frame(&mut frame).unwrap_err());
+
+    Ok(())
+}
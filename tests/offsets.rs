@@ -0,0 +1,127 @@
+use std::io::Write;
+
+mod common;
+use common::trajectories;
+
+/// Returns the path to a fresh copy of a trajectory, such that it can be modified.
+fn copy(path: &str, name: &str) -> std::io::Result<std::path::PathBuf> {
+    let copy = std::env::temp_dir().join(format!("molly_offsets_{name}.xtc"));
+    std::fs::copy(path, &copy)?;
+    Ok(copy)
+}
+
+#[test]
+fn offsets_cached() -> std::io::Result<()> {
+    let path = copy(trajectories::TEN, "cached")?;
+    let mut reader = molly::XTCReader::open(&path)?;
+    let offsets = reader.determine_offsets_exclusive(None)?;
+    assert_eq!(offsets.len(), 10);
+
+    // A limited number of offsets is taken from the cache as well.
+    assert_eq!(*reader.determine_offsets_exclusive(Some(4))?, offsets[..4]);
+    assert_eq!(reader.determine_offsets_exclusive(Some(100))?, offsets);
+    assert_eq!(reader.determine_offsets(None)?.len(), 10);
+
+    // Let the file grow by appending the same frames again.
+    let bytes = std::fs::read(&path)?;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)?
+        .write_all(&bytes)?;
+
+    // The cached offsets are still used, even after returning home.
+    reader.home()?;
+    assert_eq!(reader.determine_offsets_exclusive(None)?, offsets);
+
+    // Until they are invalidated.
+    reader.invalidate_offsets();
+    assert_eq!(reader.determine_offsets_exclusive(None)?.len(), 20);
+    assert_eq!(reader.read_all_frames()?.len(), 20);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+/// Offsets from a position other than the start are not taken from the cache.
+#[test]
+fn offsets_not_from_start() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::TEN)?;
+    let offsets = reader.determine_offsets_exclusive(None)?;
+
+    reader.read_frame(&mut molly::Frame::default())?;
+    assert_eq!(*reader.determine_offsets_exclusive(None)?, offsets[1..]);
+
+    Ok(())
+}
+
+/// The inclusive offsets, exclusive offsets, and frame sizes must describe the same frames.
+#[test]
+fn offsets_consistent() -> std::io::Result<()> {
+    for path in [
+        trajectories::ADK,
+        trajectories::AUX,
+        trajectories::COB,
+        trajectories::SMOL,
+        trajectories::TEN,
+        trajectories::XYZ,
+        trajectories::DELINYAH,
+    ] {
+        let mut reader = molly::XTCReader::open(path)?;
+        let offsets = reader.determine_offsets(None)?;
+        let exclusive = reader.determine_offsets_exclusive(None)?;
+        let sizes = reader.determine_frame_sizes(None)?;
+        assert_eq!(offsets.len(), exclusive.len(), "{path}");
+        assert_eq!(sizes.len(), exclusive.len(), "{path}");
+        for i in 0..offsets.len() {
+            assert_eq!(offsets[i] + sizes[i], exclusive[i], "{path}: frame {i}");
+        }
+        // The frames are contiguous, ending at the end of the file.
+        assert_eq!(offsets[1..], exclusive[..exclusive.len() - 1], "{path}");
+        assert_eq!(
+            exclusive.last(),
+            Some(&std::fs::metadata(path)?.len()),
+            "{path}"
+        );
+
+        // The same holds for a limited number of frames.
+        let until = Some(2);
+        let offsets = reader.determine_offsets(until)?;
+        let exclusive = reader.determine_offsets_exclusive(until)?;
+        let sizes = reader.determine_frame_sizes(until)?;
+        assert_eq!(offsets.len(), exclusive.len(), "{path}");
+        assert_eq!(sizes.len(), exclusive.len(), "{path}");
+        for i in 0..offsets.len() {
+            assert_eq!(offsets[i] + sizes[i], exclusive[i], "{path}: frame {i}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Offsets from a position other than the start begin at that position.
+#[test]
+fn offsets_consistent_not_from_start() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(trajectories::TEN)?;
+    let position = reader.determine_offsets_exclusive(Some(1))?;
+    reader.read_frame(&mut molly::Frame::default())?;
+    let offsets = reader.determine_offsets(None)?;
+    let exclusive = reader.determine_offsets_exclusive(None)?;
+    let sizes = reader.determine_frame_sizes(None)?;
+    assert_eq!(offsets.len(), 9);
+    assert_eq!(offsets[0], position[0]);
+    for i in 0..offsets.len() {
+        assert_eq!(offsets[i] + sizes[i], exclusive[i]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn offsets_empty() -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::new(std::io::Cursor::new(Vec::new()));
+    assert!(reader.determine_offsets(None)?.is_empty());
+    assert!(reader.determine_offsets_exclusive(None)?.is_empty());
+    assert!(reader.determine_frame_sizes(None)?.is_empty());
+
+    Ok(())
+}
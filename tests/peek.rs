@@ -0,0 +1,56 @@
+use molly::selection::AtomSelection;
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+/// Peeking at frames does not disturb the frames that are read sequentially.
+#[test]
+fn peek_interleaved() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::COB)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+    let offsets = reader.determine_offsets(None)?;
+
+    let mut frame = Frame::default();
+    let mut peeked = Frame::default();
+    for (idx, expected) in frames.iter().enumerate() {
+        let last = offsets[offsets.len() - 1];
+        reader.peek_frame_at_offset::<false>(&mut peeked, last, &AtomSelection::All)?;
+        assert_eq!(&peeked, frames.last().unwrap());
+        assert_eq!(peeked.offset, Some(last));
+        assert_eq!(reader.step, idx);
+
+        reader.peek_frame_at_offset::<true>(&mut peeked, offsets[0], &AtomSelection::Until(10))?;
+        assert_eq!(
+            peeked.positions,
+            frames[0].positions[..peeked.positions.len()]
+        );
+        assert_eq!(reader.step, idx);
+
+        reader.read_frame(&mut frame)?;
+        assert_eq!(&frame, expected);
+    }
+
+    Ok(())
+}
+
+/// A failed peek also leaves the reader where it was.
+#[test]
+fn peek_error() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::COB)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    let len = std::fs::metadata(trajectories::COB)?.len();
+    assert!(reader
+        .peek_frame_at_offset::<false>(&mut frame, len - 4, &AtomSelection::All)
+        .is_err());
+    assert_eq!(reader.step, 1);
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame, frames[1]);
+
+    Ok(())
+}
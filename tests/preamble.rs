@@ -0,0 +1,64 @@
+mod common;
+use common::trajectories;
+
+const PREAMBLE: &[u8] = b"# not an xtc frame, but some metadata in front of one\n";
+
+/// Returns the path to a copy of a trajectory that is preceded by a preamble.
+fn with_preamble(name: &str) -> std::io::Result<std::path::PathBuf> {
+    let path = std::env::temp_dir().join(format!("molly_preamble_{name}.xtc"));
+    let bytes = std::fs::read(trajectories::TEN)?;
+    std::fs::write(&path, [PREAMBLE, bytes.as_slice()].concat())?;
+    Ok(path)
+}
+
+#[test]
+fn open_at() -> std::io::Result<()> {
+    let path = with_preamble("open_at")?;
+    let expected = molly::XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+
+    let mut reader = molly::XTCReader::open_at(&path, PREAMBLE.len() as u64)?;
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    // Going home returns to the start of the xtc data, not the start of the file.
+    reader.home()?;
+    assert_eq!(reader.frame_count()?, expected.len());
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    // The offsets are positions in the whole file.
+    reader.home()?;
+    let offsets = reader.determine_offsets(None)?;
+    let plain = molly::XTCReader::open(trajectories::TEN)?.determine_offsets(None)?;
+    assert_eq!(offsets.len(), plain.len());
+    for (offset, plain) in offsets.iter().zip(plain.iter()) {
+        assert_eq!(*offset, plain + PREAMBLE.len() as u64);
+    }
+
+    // Frames can be read by selection and from the end.
+    let mut frames = Vec::new();
+    reader.read_frames::<false>(
+        &mut frames,
+        &molly::selection::FrameSelection::FrameList([1, 7].into()),
+        &molly::selection::AtomSelection::All,
+    )?;
+    assert_eq!(frames, [expected[1].clone(), expected[7].clone()]);
+    let mut last = molly::Frame::default();
+    reader.read_last_frame(&mut last, &molly::selection::AtomSelection::All)?;
+    assert_eq!(&last, expected.last().unwrap());
+
+    // Reopening keeps the offset.
+    reader.reopen()?;
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn open_without_offset_fails() -> std::io::Result<()> {
+    let path = with_preamble("fails")?;
+    let mut reader = molly::XTCReader::open(&path)?;
+    assert!(reader.read_frame(&mut molly::Frame::default()).is_err());
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::io::Cursor;
+
+use molly::writer::write_frame;
+use molly::{Frame, Magic, Precision, XTCReader};
+
+mod common;
+use common::trajectories;
+
+fn frame(natoms: usize, precision: Precision) -> Frame {
+    Frame {
+        positions: (0..natoms * 3).map(|i| i as f32 * 0.125).collect(),
+        precision,
+        ..Frame::default()
+    }
+}
+
+fn roundtrip(frame: &Frame) -> std::io::Result<Frame> {
+    let mut bytes = Vec::new();
+    write_frame(&mut bytes, frame, Magic::Xtc1995)?;
+    let mut read = Frame::default();
+    XTCReader::new(Cursor::new(bytes)).read_frame(&mut read)?;
+    Ok(read)
+}
+
+#[test]
+fn compressed() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame.precision, Precision::Compressed(1000.0));
+    assert_eq!(frame.precision.value(), Some(1000.0));
+
+    Ok(())
+}
+
+#[test]
+fn uncompressed() -> std::io::Result<()> {
+    // A frame of 9 atoms or less has no precision, whatever it was written with.
+    let read = roundtrip(&frame(4, Precision::Compressed(1000.0)))?;
+    assert_eq!(read.precision, Precision::Uncompressed);
+    assert_eq!(read.precision.value(), None);
+
+    // Reading a small frame into a frame that held a compressed one clears its precision.
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut reused = Frame::default();
+    reader.read_frame(&mut reused)?;
+    let mut bytes = Vec::new();
+    write_frame(
+        &mut bytes,
+        &frame(4, Precision::Uncompressed),
+        Magic::Xtc1995,
+    )?;
+    XTCReader::new(Cursor::new(bytes)).read_frame(&mut reused)?;
+    assert_eq!(reused.precision, Precision::Uncompressed);
+
+    Ok(())
+}
+
+#[test]
+fn compress_without_precision() {
+    let mut bytes = Vec::new();
+    let err = write_frame(
+        &mut bytes,
+        &frame(12, Precision::Uncompressed),
+        Magic::Xtc1995,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,58 @@
+use molly::{probe, Magic};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn probe_valid() -> std::io::Result<()> {
+    for path in [
+        trajectories::ADK,
+        trajectories::COB,
+        trajectories::SMOL,
+        trajectories::TEN,
+        trajectories::XYZ,
+    ] {
+        let mut reader = molly::XTCReader::open(path)?;
+        let mut frame = molly::Frame::default();
+        reader.read_frame(&mut frame)?;
+
+        let probed = probe(path)?;
+        assert_eq!(probed.version, Magic::Xtc1995);
+        assert_eq!(probed.natoms, frame.natoms());
+        assert_eq!(probed.first_step, frame.step);
+        assert_eq!(probed.first_time, frame.time);
+    }
+    Ok(())
+}
+
+#[test]
+fn probe_invalid() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let dir = std::env::temp_dir();
+
+    let empty = dir.join("molly_probe_empty.xtc");
+    std::fs::write(&empty, [])?;
+    let err = probe(&empty).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    let short = dir.join("molly_probe_short.xtc");
+    std::fs::write(&short, &bytes[..20])?;
+    let err = probe(&short).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert!(err.to_string().contains("too short"));
+
+    let magic = dir.join("molly_probe_magic.xtc");
+    std::fs::write(&magic, [&[0, 0, 0, 42], &bytes[4..]].concat())?;
+    let err = probe(&magic).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("magic number"));
+
+    let missing = dir.join("molly_probe_does_not_exist.xtc");
+    let err = probe(missing).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    for path in [empty, short, magic] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn provenance_read_frames() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let metas = reader.scan(None)?;
+
+    let selection = FrameSelection::framelist_from_iter([1, 4, 7]);
+    let mut frames = Vec::new();
+    reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?;
+    let indices: Vec<_> = frames.iter().map(|frame| frame.index).collect();
+    assert_eq!(indices, [Some(1), Some(4), Some(7)]);
+    for frame in &frames {
+        let idx = frame.index.unwrap();
+        assert_eq!(frame.offset, Some(metas[idx].offset));
+        assert_eq!(frame.step, metas[idx].step);
+    }
+
+    // The same through the lazy iterator.
+    reader.home()?;
+    for result in reader.selected_frames(&selection, &AtomSelection::All) {
+        let (idx, frame) = result?;
+        assert_eq!(frame.index, Some(idx));
+        assert_eq!(frame.offset, Some(metas[idx].offset));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn provenance_sequential() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let metas = reader.scan(None)?;
+
+    // Reading at an offset records the offset, but not the index.
+    let mut frame = Frame::default();
+    reader.read_frame_at_offset::<false>(&mut frame, metas[3].offset, &AtomSelection::All)?;
+    assert_eq!(frame.offset, Some(metas[3].offset));
+    assert_eq!(frame.index, None);
+
+    // A sequential read into the same frame forgets where the previous frame came from.
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame.offset, None);
+    assert_eq!(frame.index, None);
+
+    reader.read_last_frame(&mut frame, &AtomSelection::All)?;
+    assert_eq!(frame.index, Some(metas.len() - 1));
+    assert_eq!(frame.offset, metas.last().map(|meta| meta.offset));
+
+    Ok(())
+}
+
+/// Provenance is not considered when comparing frames.
+#[test]
+fn provenance_equality() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut sequential = Frame::default();
+    reader.read_frame(&mut sequential)?;
+
+    reader.home()?;
+    let mut frames = Vec::new();
+    reader.read_frames::<false>(
+        &mut frames,
+        &FrameSelection::framelist_from_iter([0]),
+        &AtomSelection::All,
+    )?;
+    assert_eq!(frames[0].index, Some(0));
+    assert_eq!(frames[0], sequential);
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use molly::selection::AtomSelection;
+use molly::{Frame, Precision, QuantizedFrame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+fn quantized(path: &str, atom_selection: AtomSelection) -> std::io::Result<()> {
+    let mut reader = XTCReader::open(path)?;
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while reader
+        .read_frame_with_selection(&mut frame, &atom_selection)
+        .is_ok()
+    {
+        frames.push(frame.clone());
+    }
+    reader.home()?;
+
+    let mut quantized = QuantizedFrame::default();
+    for frame in &frames {
+        reader.read_frame_quantized(&mut quantized, &atom_selection)?;
+        assert_eq!(quantized.step, frame.step);
+        assert_eq!(quantized.time, frame.time);
+        assert_eq!(quantized.boxvec, frame.boxvec);
+        assert_eq!(Precision::Compressed(quantized.precision), frame.precision);
+        assert_eq!(quantized.natoms(), frame.natoms());
+        assert!(quantized.positions.iter().all(|&q| q >= 0));
+        // Lossless with respect to the stored precision.
+        assert_eq!(quantized.dequantize(), frame.positions);
+    }
+    assert_eq!(reader.step, frames.len());
+
+    Ok(())
+}
+
+#[test]
+fn quantized_adk() -> std::io::Result<()> {
+    quantized(trajectories::ADK, AtomSelection::All)
+}
+
+#[test]
+fn quantized_adk_selection() -> std::io::Result<()> {
+    quantized(trajectories::ADK, AtomSelection::Until(100))?;
+    quantized(
+        trajectories::ADK,
+        AtomSelection::from_index_list(&[1, 5, 8, 13, 400]),
+    )
+}
+
+#[test]
+fn quantized_uncompressed() -> std::io::Result<()> {
+    // Frames with 9 atoms or less store their positions as plain floats.
+    let frame = Frame {
+        positions: vec![0.5; 4 * 3],
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    molly::writer::write_frame(&mut bytes, &frame, molly::Magic::Xtc1995)?;
+
+    let mut reader = XTCReader::new(bytes.as_slice());
+    let err = reader
+        .read_frame_quantized(&mut QuantizedFrame::default(), &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    Ok(())
+}
+
+#[test]
+fn quantized_out_of_range() -> std::io::Result<()> {
+    // At a precision of 1000, a span of 100 nm covers more grid points than fit in an i16.
+    let mut positions = vec![0.0; 20 * 3];
+    positions[3] = 100.0;
+    let frame = Frame {
+        precision: Precision::Compressed(1000.0),
+        positions,
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    molly::writer::write_frame(&mut bytes, &frame, molly::Magic::Xtc1995)?;
+
+    let mut reader = XTCReader::new(bytes.as_slice());
+    let err = reader
+        .read_frame_quantized(&mut QuantizedFrame::default(), &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::io::Cursor;
+
+use molly::XTCReader;
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn raw_frames_decode() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::XYZ)?.read_all_frames()?;
+    let mut reader = XTCReader::open(trajectories::XYZ)?;
+    let n = frames.len();
+    for (start, count) in [(0, 1), (0, n), (2, 4), (n - 1, 1)] {
+        let bytes = reader.read_raw_frames(start, count)?;
+        let read = XTCReader::new(Cursor::new(bytes)).read_all_frames()?;
+        assert_eq!(&read[..], &frames[start..start + count]);
+    }
+    assert!(reader.read_raw_frames(3, 0)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn raw_frames_verbatim() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let metas = reader.scan(None)?;
+    let n = metas.len();
+    assert_eq!(reader.read_raw_frames(0, n)?, bytes);
+
+    let raw = reader.read_raw_frames(1, 2)?;
+    let from = metas[1].offset as usize;
+    let to = (metas[2].offset + metas[2].size) as usize;
+    assert_eq!(raw, &bytes[from..to]);
+
+    // Concatenating slices gives back the whole trajectory.
+    let mut joined = reader.read_raw_frames(0, 2)?;
+    joined.extend(reader.read_raw_frames(2, n - 2)?);
+    assert_eq!(joined, bytes);
+
+    Ok(())
+}
+
+#[test]
+fn raw_frames_out_of_range() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    let err = reader.read_raw_frames(8, 3).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    let err = reader.read_raw_frames(usize::MAX, 2).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    // The position of the reader is unchanged, so reading still starts at the first frame.
+    let mut frame = molly::Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(
+        frame.step,
+        XTCReader::open(trajectories::TEN)?.scan(None)?[0].step
+    );
+
+    Ok(())
+}
+
+/// A corrupt byte count is not trusted for allocation.
+#[test]
+fn raw_frames_corrupt_nbytes() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let mut header = XTCReader::new(bytes.as_slice()).read_header()?;
+    header.magic = molly::Magic::Xtc2023;
+
+    let mut corrupt = header.to_be_bytes()?.to_vec();
+    corrupt.extend(&bytes[molly::Header::SIZE..molly::Header::SIZE + 4 * 8]); // Up to smallidx.
+    molly::writer::write_u64(&mut corrupt, 1 << 60)?;
+    corrupt.extend([0; 64]);
+
+    let mut reader = XTCReader::new(Cursor::new(corrupt));
+    let err = reader.read_raw_frames(0, 1).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::{StepRemap, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn remap_offset() -> std::io::Result<()> {
+    let expected = XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    reader.set_step_remap(Some(StepRemap::offset(1000, 50.0)));
+    let frames = reader.read_all_frames()?;
+    assert_eq!(frames.len(), expected.len());
+    for (frame, expected) in frames.iter().zip(expected.iter()) {
+        assert_eq!(frame.step, expected.step + 1000);
+        assert_eq!(frame.time, expected.time + 50.0);
+        assert_eq!(frame.positions, expected.positions);
+    }
+
+    // Removing the remap restores the original values.
+    reader.home()?;
+    reader.set_step_remap(None);
+    assert_eq!(reader.read_all_frames()?, expected);
+
+    Ok(())
+}
+
+/// Concatenated copies of a trajectory get a monotonic timeline.
+#[test]
+fn remap_concatenated() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::TEN)?;
+    let concatenated = [bytes.as_slice(), bytes.as_slice()].concat();
+
+    let mut reader = XTCReader::new(std::io::Cursor::new(concatenated));
+    reader.set_step_remap(Some(StepRemap::new(|idx, _, _| {
+        (idx as u64 * 10, idx as f32 * 2.5)
+    })));
+
+    let frames = reader.read_all_frames()?;
+    assert_eq!(frames.len(), 20);
+    for (idx, frame) in frames.iter().enumerate() {
+        assert_eq!(frame.step, idx as u64 * 10);
+        assert_eq!(frame.time, idx as f32 * 2.5);
+    }
+
+    // Scanning the headers applies the same mapping.
+    reader.home()?;
+    let metas = reader.scan(None)?;
+    let steps: Vec<u64> = metas.iter().map(|meta| meta.step).collect();
+    let expected: Vec<u64> = frames.iter().map(|frame| frame.step).collect();
+    assert_eq!(steps, expected);
+
+    // With a monotonic timeline, we can now seek by time.
+    assert_eq!(reader.seek_to_time(25.0, molly::Rounding::Nearest)?, 10);
+
+    Ok(())
+}
+
+/// Frames that are read through their offsets are remapped with their index, like in a scan.
+#[test]
+fn remap_frame_list() -> std::io::Result<()> {
+    let remap = StepRemap::new(|idx, step, time| (step + idx as u64 * 1000, time + idx as f32));
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    reader.set_step_remap(Some(remap));
+    let metas = reader.scan(None)?;
+
+    let list = [1, 4, 7, 9];
+    let selection = FrameSelection::FrameList(list.into_iter().collect());
+    let mut frames = Vec::new();
+    reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?;
+    assert_eq!(frames.len(), list.len());
+    for (frame, idx) in frames.iter().zip(list) {
+        assert_eq!(frame.step, metas[idx].step);
+        assert_eq!(frame.time, metas[idx].time);
+    }
+
+    reader.home()?;
+    let shared = reader.read_frames_shared_box(&selection, &AtomSelection::All)?;
+    let steps: Vec<u64> = list.iter().map(|&idx| metas[idx].step).collect();
+    assert_eq!(shared.steps, steps);
+
+    Ok(())
+}
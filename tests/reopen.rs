@@ -0,0 +1,65 @@
+mod common;
+use common::trajectories;
+
+/// Returns the path to a fresh copy of a trajectory, such that it can be replaced.
+fn copy(name: &str) -> std::io::Result<std::path::PathBuf> {
+    let copy = std::env::temp_dir().join(format!("molly_reopen_{name}.xtc"));
+    std::fs::copy(trajectories::TEN, &copy)?;
+    Ok(copy)
+}
+
+/// Replace the file at `path` by a new file holding only its first `n` frames.
+fn replace_truncated(path: &std::path::Path, n: usize) -> std::io::Result<()> {
+    let offsets = molly::XTCReader::open(path)?.determine_offsets_exclusive(Some(n))?;
+    let bytes = std::fs::read(path)?;
+    let tmp = path.with_extension("xtc.tmp");
+    std::fs::write(&tmp, &bytes[..offsets[n - 1] as usize])?;
+    std::fs::rename(&tmp, path)
+}
+
+#[test]
+fn reopen() -> std::io::Result<()> {
+    let path = copy("file")?;
+    let mut reader = molly::XTCReader::open(&path)?;
+    assert_eq!(reader.frame_count()?, 10);
+    reader.read_frame(&mut molly::Frame::default())?;
+
+    replace_truncated(&path, 4)?;
+
+    // The old handle still sees the replaced file.
+    reader.home()?;
+    assert_eq!(reader.read_all_frames()?.len(), 10);
+
+    reader.reopen()?;
+    assert_eq!(reader.step, 0);
+    assert_eq!(reader.frame_count()?, 4);
+    assert_eq!(reader.read_all_frames()?.len(), 4);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn reopen_buffered() -> std::io::Result<()> {
+    let path = copy("buffered")?;
+    let mut reader = molly::XTCReader::open_buffered(&path)?;
+    assert_eq!(reader.read_all_frames()?.len(), 10);
+
+    replace_truncated(&path, 3)?;
+
+    reader.reopen()?;
+    assert_eq!(reader.read_all_frames()?.len(), 3);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn reopen_without_path() -> std::io::Result<()> {
+    let file = std::fs::File::open(trajectories::TEN)?;
+    let mut reader = molly::XTCReader::new(file);
+    let err = reader.reopen().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+
+    Ok(())
+}
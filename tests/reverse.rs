@@ -0,0 +1,35 @@
+mod common;
+use common::trajectories;
+
+fn reverse(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let mut frames = reader.read_all_frames()?.into_vec();
+    reader.home()?;
+
+    let reversed = reader.frames_rev().collect::<std::io::Result<Vec<_>>>()?;
+    frames.reverse();
+    assert_eq!(reversed, frames);
+
+    // Starting from the second frame, the first frame is not included.
+    reader.home()?;
+    reader.read_frame(&mut molly::Frame::default())?;
+    let reversed = reader.frames_rev().collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(reversed, frames[..frames.len() - 1]);
+
+    Ok(())
+}
+
+#[test]
+fn reverse_adk() -> std::io::Result<()> {
+    reverse(trajectories::ADK)
+}
+
+#[test]
+fn reverse_smol() -> std::io::Result<()> {
+    reverse(trajectories::SMOL)
+}
+
+#[test]
+fn reverse_ten() -> std::io::Result<()> {
+    reverse(trajectories::TEN)
+}
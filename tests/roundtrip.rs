@@ -0,0 +1,104 @@
+//! Write trajectories with molly and read them back with xdrfile.
+//!
+//! Reading the written frames with an independent implementation catches encoder bugs that
+//! would cancel out if molly were to read its own output.
+use std::io::BufWriter;
+
+use molly::writer::write_frame;
+use molly::Magic;
+use xdrfile::Trajectory;
+
+mod common;
+use common::trajectories;
+
+fn roundtrip(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut molly_reader = molly::XTCReader::open(&path)?;
+    let frames = molly_reader.read_all_frames()?;
+
+    // Write the frames to a temporary file.
+    let name = path.as_ref().file_name().unwrap().to_string_lossy();
+    let out = std::env::temp_dir().join(format!("molly_roundtrip_{name}"));
+    {
+        let mut writer = BufWriter::new(std::fs::File::create(&out)?);
+        for frame in frames.iter() {
+            // The 1995 magic number is the one xdrfile understands.
+            write_frame(&mut writer, frame, Magic::Xtc1995)?;
+        }
+    }
+
+    let mut xdr_reader =
+        xdrfile::XTCTrajectory::open_read(&out).expect("couldn't open file using xdrfile");
+    let num_atoms = xdr_reader
+        .get_num_atoms()
+        .expect("couldn't get number of atoms from xdrfile");
+    let mut xdr_frame = xdrfile::Frame::with_len(num_atoms);
+
+    for (idx, molly_frame) in frames.iter().enumerate() {
+        xdr_reader
+            .read(&mut xdr_frame)
+            .expect("couldn't read xdrfile frame");
+
+        assert_eq!(molly_frame.step as usize, xdr_frame.step, "frame {idx}");
+        assert_eq!(molly_frame.time, xdr_frame.time, "frame {idx}");
+        assert_eq!(
+            molly_frame.box_rows(),
+            xdr_frame.box_vector,
+            "box vectors for frame {idx} do not match"
+        );
+
+        let molly_positions = molly_frame
+            .coords()
+            .map(|c| c.to_array())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            molly_positions,
+            xdr_frame.coords.as_slice(),
+            "positions for frame {idx} do not match"
+        );
+    }
+
+    assert!(
+        xdr_reader.read(&mut xdr_frame).is_err(),
+        "xdrfile reader should be done by now"
+    );
+
+    std::fs::remove_file(&out)?;
+
+    Ok(())
+}
+
+#[test]
+fn roundtrip_adk() -> std::io::Result<()> {
+    roundtrip(trajectories::ADK)
+}
+
+#[test]
+fn roundtrip_aux() -> std::io::Result<()> {
+    roundtrip(trajectories::AUX)
+}
+
+#[test]
+fn roundtrip_cob() -> std::io::Result<()> {
+    roundtrip(trajectories::COB)
+}
+
+#[test]
+fn roundtrip_smol() -> std::io::Result<()> {
+    roundtrip(trajectories::SMOL)
+}
+
+#[test]
+fn roundtrip_ten() -> std::io::Result<()> {
+    roundtrip(trajectories::TEN)
+}
+
+#[test]
+fn roundtrip_xyz() -> std::io::Result<()> {
+    roundtrip(trajectories::XYZ)
+}
+
+/// A solvated system, which exercises the runs of small differences and the water swap.
+#[test]
+fn roundtrip_delinyah() -> std::io::Result<()> {
+    roundtrip(trajectories::DELINYAH)
+}
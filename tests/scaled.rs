@@ -0,0 +1,61 @@
+use molly::selection::AtomSelection;
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+fn assert_scaled(path: &str, scale: f32, atom_selection: &AtomSelection) -> std::io::Result<()> {
+    let mut reader = XTCReader::open(path)?;
+    let mut scaled_reader = XTCReader::open(path)?;
+    let mut frame = Frame::default();
+    let mut scaled = Frame::default();
+    while reader
+        .read_frame_with_selection(&mut frame, atom_selection)
+        .is_ok()
+    {
+        scaled_reader.read_frame_scaled(&mut scaled, scale, atom_selection)?;
+        assert_eq!(scaled.step, frame.step);
+        assert_eq!(scaled.time, frame.time);
+        assert_eq!(scaled.precision, frame.precision);
+        assert_eq!(scaled.boxvec, frame.boxvec * scale);
+        assert_eq!(scaled.positions.len(), frame.positions.len());
+        for (&s, &v) in scaled.positions.iter().zip(&frame.positions) {
+            let expected = v * scale;
+            assert!(
+                (s - expected).abs() <= expected.abs() * 1e-6,
+                "{s} != {expected}"
+            );
+        }
+    }
+    assert_eq!(scaled_reader.step, reader.step);
+
+    Ok(())
+}
+
+#[test]
+fn nanometer_to_angstrom() -> std::io::Result<()> {
+    assert_scaled(trajectories::ADK, 10.0, &AtomSelection::All)
+}
+
+#[test]
+fn selection() -> std::io::Result<()> {
+    assert_scaled(trajectories::COB, 10.0, &AtomSelection::Until(100))
+}
+
+#[test]
+fn uncompressed() -> std::io::Result<()> {
+    assert_scaled(trajectories::TEN, 0.5, &AtomSelection::All)
+}
+
+#[test]
+fn unit_scale() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    reader.home()?;
+    let mut scaled = Frame::default();
+    reader.read_frame_scaled(&mut scaled, 1.0, &AtomSelection::All)?;
+    assert_eq!(scaled, frame);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::io::Cursor;
+
+mod common;
+use common::trajectories;
+
+fn scan(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+
+    let metas = reader.scan(None)?;
+    assert_eq!(metas.len(), frames.len());
+    for (meta, frame) in metas.iter().zip(frames.iter()) {
+        assert_eq!(meta.step, frame.step);
+        assert_eq!(meta.time, frame.time);
+        assert_eq!(meta.boxvec, frame.boxvec);
+        assert_eq!(meta.natoms, frame.natoms());
+    }
+
+    // The frames are contiguous, and the offsets agree with those determined otherwise.
+    let offsets = reader.determine_offsets(None)?;
+    for (meta, &offset) in metas.iter().zip(offsets.iter()) {
+        assert_eq!(meta.offset, offset);
+    }
+    for pair in metas.windows(2) {
+        assert_eq!(pair[0].offset + pair[0].size, pair[1].offset);
+    }
+    let len = std::fs::metadata(&path)?.len();
+    let last = metas.last().unwrap();
+    assert_eq!(last.offset + last.size, len);
+
+    // Scanning a limited number of frames.
+    let until = frames.len() / 2;
+    assert_eq!(reader.scan(Some(until))?, metas[..until]);
+
+    Ok(())
+}
+
+#[test]
+fn scan_adk() -> std::io::Result<()> {
+    scan(trajectories::ADK)
+}
+
+#[test]
+fn scan_cob() -> std::io::Result<()> {
+    scan(trajectories::COB)
+}
+
+#[test]
+fn scan_smol() -> std::io::Result<()> {
+    scan(trajectories::SMOL)
+}
+
+#[test]
+fn scan_ten() -> std::io::Result<()> {
+    scan(trajectories::TEN)
+}
+
+/// The offsets of frames with the 2023 magic number account for the 64-bit byte counts.
+#[test]
+fn scan_magic_2023() -> std::io::Result<()> {
+    let frames = molly::XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+    let mut bytes = Vec::new();
+    for frame in frames.iter() {
+        molly::writer::write_frame(&mut bytes, frame, molly::Magic::Xtc2023)?;
+    }
+
+    let mut reader = molly::XTCReader::new(Cursor::new(&bytes));
+    let metas = reader.scan(None)?;
+    assert_eq!(metas.len(), frames.len());
+    for pair in metas.windows(2) {
+        assert_eq!(pair[0].offset + pair[0].size, pair[1].offset);
+    }
+    let last = metas.last().unwrap();
+    assert_eq!(last.offset + last.size, bytes.len() as u64);
+    assert_eq!(reader.read_all_frames()?, frames);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::num::NonZeroU64;
+use std::num::{NonZeroU32, NonZeroU64};
 
 use molly::selection::{AtomSelection as AS, FrameSelection as FS, Range};
 
@@ -164,6 +164,60 @@ mod frame_selection {
     }
 }
 
+mod selected_frames {
+    use super::*;
+
+    /// Read the selected frames and return their original indices.
+    fn selected_indices(frame_selection: FS) -> std::io::Result<Vec<usize>> {
+        let mut reader = molly::XTCReader::open(PATH)?;
+        let frames = reader.read_all_frames()?;
+        reader.home()?;
+
+        let mut indices = Vec::new();
+        for item in reader.selected_frames(&frame_selection, &AS::All) {
+            let (idx, frame) = item?;
+            assert_eq!(frame, frames[idx], "frame {idx} does not match");
+            indices.push(idx);
+        }
+        Ok(indices)
+    }
+
+    #[test]
+    fn all_frames() -> std::io::Result<()> {
+        assert_eq!(selected_indices(FS::All)?, Vec::from_iter(0..1001));
+        Ok(())
+    }
+
+    #[test]
+    fn range_step() -> std::io::Result<()> {
+        let step = NonZeroU64::new(7);
+        let indices = selected_indices(FS::Range(Range::new(Some(10), Some(50), step)))?;
+        assert_eq!(indices, Vec::from_iter((10..50).step_by(7)));
+        Ok(())
+    }
+
+    #[test]
+    fn indices() -> std::io::Result<()> {
+        let indices = selected_indices(FS::framelist_from_iter([3, 1, 4, 159, 2000]))?;
+        assert_eq!(indices, [1, 3, 4, 159]);
+        Ok(())
+    }
+
+    #[test]
+    fn atom_selection() -> std::io::Result<()> {
+        let mut reader = molly::XTCReader::open(PATH)?;
+        let frame_selection = FS::framelist_from_iter([5, 6]);
+        let frames = reader
+            .selected_frames(&frame_selection, &AS::Until(10))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        assert_eq!(frames.len(), 2);
+        for (_, frame) in frames {
+            assert_eq!(frame.natoms(), 10);
+        }
+        Ok(())
+    }
+}
+
 mod atom_selection {
     use super::*;
 
@@ -304,6 +358,42 @@ mod atom_selection {
         assert_atoms!(AS::Mask(mask) => 3)
     }
 
+    /// Read every tenth atom.
+    #[test]
+    fn stride() -> std::io::Result<()> {
+        let step = NonZeroU32::new(10).unwrap();
+        assert_atoms!(AS::Stride { start: 0, step } => NATOMS.div_ceil(10))
+    }
+    /// Read every atom from some index onwards.
+    #[test]
+    fn stride_from() -> std::io::Result<()> {
+        let step = NonZeroU32::new(1).unwrap();
+        assert_atoms!(AS::Stride { start: 100, step } => NATOMS - 100)
+    }
+    /// Read a stride that starts beyond the last atom.
+    #[test]
+    fn stride_after_last_atom() -> std::io::Result<()> {
+        let step = NonZeroU32::new(10).unwrap();
+        assert_atoms!(AS::Stride { start: NATOMS as u32, step } => 0)
+    }
+    /// A stride reads the same positions as the equivalent list of indices.
+    #[test]
+    fn stride_equals_indices() -> std::io::Result<()> {
+        let stride = AS::Stride {
+            start: 7,
+            step: NonZeroU32::new(13).unwrap(),
+        };
+        let indices: Vec<u32> = (7..NATOMS as u32).step_by(13).collect();
+        let mut reader = molly::XTCReader::open(PATH)?;
+        let mut expected = molly::Frame::default();
+        reader.read_frame_with_selection(&mut expected, &AS::from_index_list(&indices))?;
+        reader.home()?;
+        let mut frame = molly::Frame::default();
+        reader.read_frame_with_selection(&mut frame, &stride)?;
+        assert_eq!(frame, expected);
+        Ok(())
+    }
+
     #[test]
     fn list() -> std::io::Result<()> {
         let ag: Box<[u32]> = include_str!("ag.txt")
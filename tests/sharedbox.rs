@@ -0,0 +1,99 @@
+use std::io::Cursor;
+
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::writer::write_frame;
+use molly::{BoxVec, Frame, Magic, Precision, XTCReader};
+
+mod common;
+use common::trajectories;
+
+/// Construct a trajectory of compressed frames with the provided boxes.
+fn trajectory(boxes: &[BoxVec]) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for (idx, &boxvec) in boxes.iter().enumerate() {
+        let frame = Frame {
+            step: idx as u64 * 100,
+            time: idx as f32,
+            boxvec,
+            precision: Precision::Compressed(1000.0),
+            positions: (0..12 * 3).map(|i| (i + idx) as f32 * 0.125).collect(),
+            ..Frame::default()
+        };
+        write_frame(&mut bytes, &frame, Magic::Xtc1995)?;
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn shared_box() -> std::io::Result<()> {
+    let boxvec = BoxVec::from_diagonal(glam::Vec3::new(2.0, 3.0, 4.0));
+    let bytes = trajectory(&[boxvec; 5])?;
+    let expected = XTCReader::new(Cursor::new(bytes.clone())).read_all_frames()?;
+
+    let mut reader = XTCReader::new(Cursor::new(bytes));
+    let shared = reader.read_frames_shared_box(&FrameSelection::All, &AtomSelection::All)?;
+    assert_eq!(shared.boxvec, boxvec);
+    assert_eq!(shared.positions.len(), expected.len());
+    for (i, frame) in expected.iter().enumerate() {
+        assert_eq!(shared.steps[i], frame.step);
+        assert_eq!(shared.times[i], frame.time);
+        assert_eq!(shared.positions[i], frame.positions);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn varying_box() -> std::io::Result<()> {
+    let a = BoxVec::from_diagonal(glam::Vec3::new(2.0, 3.0, 4.0));
+    let b = BoxVec::from_diagonal(glam::Vec3::new(2.0, 3.0, 4.5));
+    let bytes = trajectory(&[a, a, b, a])?;
+
+    let mut reader = XTCReader::new(Cursor::new(bytes));
+    let err = reader
+        .read_frames_shared_box(&FrameSelection::All, &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Frames outside the selection are not considered.
+    reader.home()?;
+    let selection = FrameSelection::FrameList([0, 1, 3].into());
+    let shared = reader.read_frames_shared_box(&selection, &AtomSelection::Until(4))?;
+    assert_eq!(shared.boxvec, a);
+    assert_eq!(shared.steps, [0, 100, 300]);
+    assert!(shared
+        .positions
+        .iter()
+        .all(|positions| positions.len() == 4 * 3));
+
+    Ok(())
+}
+
+#[test]
+fn no_frames() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(Cursor::new(Vec::new()));
+    let shared = reader.read_frames_shared_box(&FrameSelection::All, &AtomSelection::All)?;
+    assert_eq!(shared.boxvec, BoxVec::ZERO);
+    assert!(shared.positions.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn shared_box_ten() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    let frames = reader.read_all_frames()?;
+    reader.home()?;
+
+    let result = reader.read_frames_shared_box(&FrameSelection::All, &AtomSelection::All);
+    if frames.iter().all(|frame| frame.boxvec == frames[0].boxvec) {
+        let shared = result?;
+        assert_eq!(shared.boxvec, frames[0].boxvec);
+        let positions: Vec<_> = frames.iter().map(|frame| frame.positions.clone()).collect();
+        assert_eq!(shared.positions, positions);
+    } else {
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn coords_soa() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::COB)?.read_all_frames()?;
+    for frame in frames.iter() {
+        let (x, y, z) = frame.coords_soa();
+        assert_eq!(x.len(), frame.natoms());
+        for (i, coord) in frame.coords().enumerate() {
+            assert_eq!([x[i], y[i], z[i]], coord.to_array());
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn coords_soa_into() -> std::io::Result<()> {
+    let frame = XTCReader::open(trajectories::TEN)?.read_all_frames()?[3].clone();
+    let natoms = frame.natoms();
+    let mut buf = vec![f32::NAN; 3 * natoms];
+    let (x, rest) = buf.split_at_mut(natoms);
+    let (y, z) = rest.split_at_mut(natoms);
+    frame.coords_soa_into(x, y, z);
+    assert_eq!(buf, {
+        let (x, y, z) = frame.coords_soa();
+        [x, y, z].concat()
+    });
+
+    Ok(())
+}
+
+#[test]
+fn coords_soa_empty() {
+    let (x, y, z) = Frame::default().coords_soa();
+    assert!(x.is_empty() && y.is_empty() && z.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn coords_soa_into_wrong_length() {
+    let frame = Frame {
+        positions: vec![0.0; 3 * 4],
+        ..Default::default()
+    };
+    let (mut x, mut y, mut z) = (vec![0.0; 4], vec![0.0; 4], vec![0.0; 3]);
+    frame.coords_soa_into(&mut x, &mut y, &mut z);
+}
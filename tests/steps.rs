@@ -0,0 +1,38 @@
+use std::io::Cursor;
+
+use molly::writer::write_frame;
+use molly::{Frame, Magic, Precision, XTCReader};
+
+fn frame(step: u64) -> Frame {
+    let mut frame = Frame {
+        step,
+        ..Frame::default()
+    };
+    frame.positions = (0..12 * 3).map(|i| i as f32 * 0.125).collect();
+    frame.precision = Precision::Compressed(1000.0);
+    frame
+}
+
+#[test]
+fn large_step() -> std::io::Result<()> {
+    // GROMACS writes the step as a wrapping 32-bit integer. Beyond i32::MAX, that must not be
+    // read back as a negative or truncated value.
+    let step = 0xffff_fff0;
+    let mut bytes = Vec::new();
+    write_frame(&mut bytes, &frame(step), Magic::Xtc1995)?;
+
+    let mut reader = XTCReader::new(Cursor::new(bytes));
+    let mut read = Frame::default();
+    reader.read_frame(&mut read)?;
+    assert_eq!(read.step, 4294967280);
+
+    Ok(())
+}
+
+#[test]
+fn step_exceeds_header() {
+    let mut bytes = Vec::new();
+    let err = write_frame(&mut bytes, &frame(u32::MAX as u64 + 1), Magic::Xtc1995).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(bytes.is_empty());
+}
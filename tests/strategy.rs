@@ -0,0 +1,46 @@
+use molly::selection::{AtomSelection, SelectionStrategy};
+use molly::{Frame, XTCReader};
+
+mod common;
+use common::trajectories;
+
+/// Both strategies must read the same positions for any selection.
+fn same(path: &str, selection: &AtomSelection) -> std::io::Result<()> {
+    let mut during = XTCReader::open(path)?;
+    let mut after = XTCReader::open(path)?;
+    let mut a = Frame::default();
+    let mut b = Frame::default();
+    let nframes = during.frame_count()?;
+    for _ in 0..nframes {
+        during.read_frame_with_strategy(&mut a, selection, SelectionStrategy::DuringDecode)?;
+        after.read_frame_with_strategy(&mut b, selection, SelectionStrategy::AfterDecode)?;
+        assert_eq!(a, b, "{path}: {selection:?}");
+    }
+    assert_eq!(during.step, after.step);
+
+    Ok(())
+}
+
+#[test]
+fn strategies_agree() -> std::io::Result<()> {
+    for path in [trajectories::ADK, trajectories::TEN, trajectories::SMOL] {
+        let natoms = XTCReader::open(path)?.read_all_frames()?[0].natoms() as u32;
+        let selections = [
+            AtomSelection::All,
+            AtomSelection::Until(3),
+            AtomSelection::Until(natoms + 10),
+            AtomSelection::from_index_list(&[1, 4, natoms - 1]),
+            AtomSelection::from_index_list(&[0, natoms / 2]),
+            AtomSelection::Mask((0..natoms).map(|i| i % 3 == 1).collect()),
+            AtomSelection::Stride {
+                start: 2,
+                step: 5.try_into().unwrap(),
+            },
+        ];
+        for selection in &selections {
+            same(path, selection)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use molly::selection::AtomSelection;
+use molly::Frame;
+
+mod common;
+use common::trajectories;
+
+/// Taking a subset of a full frame must be equal to reading the frame with that selection.
+fn subset(path: impl AsRef<std::path::Path>, selection: AtomSelection) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let mut full = Frame::default();
+    reader.read_frame(&mut full)?;
+    reader.home()?;
+    let mut selected = Frame::default();
+    reader.read_frame_with_selection(&mut selected, &selection)?;
+
+    let subset = full.subset(&selection);
+    assert_eq!(subset, selected);
+    assert_eq!(subset.step, full.step);
+    assert_eq!(subset.time, full.time);
+    assert_eq!(subset.boxvec, full.boxvec);
+    assert_eq!(subset.precision, full.precision);
+
+    Ok(())
+}
+
+#[test]
+fn subset_all() -> std::io::Result<()> {
+    subset(trajectories::ADK, AtomSelection::All)
+}
+
+#[test]
+fn subset_until() -> std::io::Result<()> {
+    subset(trajectories::ADK, AtomSelection::Until(100))
+}
+
+#[test]
+fn subset_until_beyond() -> std::io::Result<()> {
+    subset(trajectories::ADK, AtomSelection::Until(u32::MAX))
+}
+
+#[test]
+fn subset_indices() -> std::io::Result<()> {
+    subset(
+        trajectories::ADK,
+        AtomSelection::from_index_list(&[0, 3, 7, 1000, 1001, 3000]),
+    )
+}
+
+#[test]
+fn subset_empty() -> std::io::Result<()> {
+    subset(trajectories::ADK, AtomSelection::from_index_list(&[]))
+}
+
+#[test]
+fn subset_small() -> std::io::Result<()> {
+    subset(
+        trajectories::TEN,
+        AtomSelection::from_index_list(&[1, 2, 5]),
+    )
+}
@@ -0,0 +1,126 @@
+use molly::{Frame, Rounding, XTCReader};
+
+mod common;
+use common::trajectories;
+
+fn times(path: &str) -> std::io::Result<Vec<f32>> {
+    let mut reader = XTCReader::open(path)?;
+    Ok(reader.scan(None)?.iter().map(|meta| meta.time).collect())
+}
+
+#[test]
+fn exact() -> std::io::Result<()> {
+    let times = times(trajectories::ADK)?;
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let mut frame = Frame::default();
+    for (idx, &time) in times.iter().enumerate() {
+        for rounding in [Rounding::Nearest, Rounding::Floor, Rounding::Ceil] {
+            assert_eq!(reader.seek_to_time(time, rounding)?, idx);
+            assert_eq!(reader.step, idx);
+            reader.read_frame(&mut frame)?;
+            assert_eq!(frame.time, time);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn between() -> std::io::Result<()> {
+    let times = times(trajectories::ADK)?;
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let (a, b) = (times[3], times[4]);
+    let quarter = a + (b - a) / 4.0;
+    let midpoint = a + (b - a) / 2.0;
+    let three_quarters = a + (b - a) * 3.0 / 4.0;
+
+    assert_eq!(reader.seek_to_time(quarter, Rounding::Nearest)?, 3);
+    assert_eq!(reader.seek_to_time(three_quarters, Rounding::Nearest)?, 4);
+    assert_eq!(reader.seek_to_time(three_quarters, Rounding::Floor)?, 3);
+    assert_eq!(reader.seek_to_time(quarter, Rounding::Ceil)?, 4);
+
+    // Ties go toward the earlier frame.
+    assert_eq!(reader.seek_to_time(midpoint, Rounding::default())?, 3);
+    assert_eq!(reader.seek_to_time(midpoint, Rounding::Floor)?, 3);
+    assert_eq!(reader.seek_to_time(midpoint, Rounding::Ceil)?, 4);
+
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame.time, b);
+    Ok(())
+}
+
+#[test]
+fn out_of_range() -> std::io::Result<()> {
+    let times = times(trajectories::ADK)?;
+    let mut reader = XTCReader::open(trajectories::ADK)?;
+    let first = times[0];
+    let last = *times.last().unwrap();
+    for time in [first - 1.0, last + 1.0, f32::NAN] {
+        for rounding in [Rounding::Nearest, Rounding::Floor, Rounding::Ceil] {
+            let err = reader.seek_to_time(time, rounding).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn not_increasing() -> std::io::Result<()> {
+    // Concatenate a trajectory with itself, such that time jumps back halfway.
+    let bytes = std::fs::read(trajectories::TEN)?;
+    let concatenated = [bytes.as_slice(), bytes.as_slice()].concat();
+
+    let mut reader = XTCReader::new(std::io::Cursor::new(concatenated));
+    let err = reader.seek_to_time(0.0, Rounding::Nearest).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    Ok(())
+}
+
+#[test]
+fn empty() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(std::io::Cursor::new(Vec::new()));
+    let err = reader.seek_to_time(0.0, Rounding::Nearest).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    Ok(())
+}
+
+#[test]
+fn time_bounds() -> std::io::Result<()> {
+    for path in [trajectories::ADK, trajectories::TEN, trajectories::SMOL] {
+        let times = times(path)?;
+        let mut reader = XTCReader::open(path)?;
+        let expected = (times[0], times[times.len() - 1]);
+        assert_eq!(reader.time_bounds()?, expected, "{path}");
+
+        // The position of the reader is left unchanged, also when not at the start.
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        assert_eq!(reader.time_bounds()?, (times[1], expected.1), "{path}");
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.time, times[1], "{path}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn time_bounds_remapped() -> std::io::Result<()> {
+    let times = times(trajectories::TEN)?;
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    reader.set_step_remap(Some(molly::StepRemap::new(|idx, step, _| {
+        (step, idx as f32 * 10.0)
+    })));
+    assert_eq!(
+        reader.time_bounds()?,
+        (0.0, (times.len() - 1) as f32 * 10.0)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn time_bounds_empty() {
+    let mut reader = XTCReader::new(std::io::Cursor::new(Vec::new()));
+    let err = reader.time_bounds().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
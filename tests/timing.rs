@@ -0,0 +1,113 @@
+#![cfg(feature = "profile")]
+
+use molly::selection::{AtomSelection, FrameSelection};
+use molly::{Frame, FrameTiming, XTCReader};
+
+mod common;
+use common::trajectories;
+
+/// Frames read with their timing are identical to those read otherwise.
+fn timing(path: &str) -> std::io::Result<()> {
+    let selection = AtomSelection::Until(100);
+    let mut expected = Vec::new();
+    XTCReader::open(path)?.read_frames::<false>(&mut expected, &FrameSelection::All, &selection)?;
+
+    let mut frames = Vec::new();
+    let mut reader = XTCReader::open(path)?;
+    let timings = reader.read_frames_with_timing(&mut frames, &FrameSelection::All, &selection)?;
+    assert_eq!(frames, expected);
+    assert_eq!(timings.len(), frames.len());
+
+    // The same, sequentially.
+    reader.home()?;
+    let mut frame = Frame::default();
+    let mut total = FrameTiming::default();
+    for expected in &expected {
+        total += reader.read_frame_with_timing(&mut frame, &selection)?;
+        assert_eq!(&frame, expected);
+    }
+    assert!(reader
+        .read_frame_with_timing(&mut frame, &selection)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn timing_adk() -> std::io::Result<()> {
+    timing(trajectories::ADK)
+}
+
+#[test]
+fn timing_cob() -> std::io::Result<()> {
+    timing(trajectories::COB)
+}
+
+#[test]
+fn timing_ten() -> std::io::Result<()> {
+    timing(trajectories::TEN)
+}
+
+#[test]
+fn timing_xyz() -> std::io::Result<()> {
+    timing(trajectories::XYZ)
+}
+
+/// Uncompressed frames take no time to decode.
+#[test]
+fn timing_uncompressed() -> std::io::Result<()> {
+    let frame = Frame {
+        positions: vec![0.5; 3 * 3],
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    molly::writer::write_frame(&mut bytes, &frame, molly::Magic::Xtc1995)?;
+
+    let mut read = Frame::default();
+    let timing = XTCReader::new(std::io::Cursor::new(bytes))
+        .read_frame_with_timing(&mut read, &AtomSelection::All)?;
+    assert_eq!(timing.decode, std::time::Duration::ZERO);
+    assert_eq!(read.positions, frame.positions);
+
+    Ok(())
+}
+
+/// Frames that are read through their offsets are remapped with their index.
+#[test]
+fn timing_remap() -> std::io::Result<()> {
+    let remap = molly::StepRemap::new(|idx, step, time| (step + idx as u64 * 1000, time));
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    reader.set_step_remap(Some(remap));
+    let metas = reader.scan(None)?;
+
+    let list = [1, 4, 7, 9];
+    let selection = FrameSelection::FrameList(list.into_iter().collect());
+    let mut frames = Vec::new();
+    reader.read_frames_with_timing(&mut frames, &selection, &AtomSelection::All)?;
+    let steps: Vec<u64> = frames.iter().map(|frame| frame.step).collect();
+    let expected: Vec<u64> = list.iter().map(|&idx| metas[idx].step).collect();
+    assert_eq!(steps, expected);
+
+    Ok(())
+}
+
+/// A corrupt byte count is not trusted for allocation.
+#[test]
+fn timing_corrupt_nbytes() -> std::io::Result<()> {
+    let bytes = std::fs::read(trajectories::ADK)?;
+    let mut header = XTCReader::new(bytes.as_slice()).read_header()?;
+    header.magic = molly::Magic::Xtc2023;
+
+    let mut corrupt = header.to_be_bytes()?.to_vec();
+    corrupt.extend(&bytes[molly::Header::SIZE..molly::Header::SIZE + 4 * 8]); // Up to smallidx.
+    molly::writer::write_u64(&mut corrupt, 1 << 60)?;
+    corrupt.extend([0; 64]);
+
+    let mut frame = Frame::default();
+    let err = XTCReader::new(std::io::Cursor::new(corrupt))
+        .read_frame_with_timing(&mut frame, &AtomSelection::All)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    Ok(())
+}
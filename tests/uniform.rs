@@ -0,0 +1,118 @@
+use std::io::Cursor;
+
+use molly::writer::write_frame;
+use molly::{Frame, Magic, Precision, XTCReader};
+
+mod common;
+use common::trajectories;
+
+#[test]
+fn uniform() -> std::io::Result<()> {
+    for path in [trajectories::ADK, trajectories::SMOL, trajectories::TEN] {
+        let mut reader = XTCReader::open(path)?;
+        assert!(reader.has_uniform_natoms()?, "{path}");
+
+        // Checking leaves the reader where it was, and agrees with the decoded frames.
+        let frames = reader.read_all_frames()?;
+        assert!(!frames.is_empty(), "{path}");
+        assert!(frames
+            .iter()
+            .all(|frame| frame.natoms() == frames[0].natoms()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ragged() -> std::io::Result<()> {
+    let mut bytes = Vec::new();
+    for natoms in [12, 12, 15] {
+        let frame = Frame {
+            positions: (0..natoms * 3).map(|i| i as f32 * 0.125).collect(),
+            precision: Precision::Compressed(1000.0),
+            ..Frame::default()
+        };
+        write_frame(&mut bytes, &frame, Magic::Xtc1995)?;
+    }
+
+    let mut reader = XTCReader::new(Cursor::new(bytes));
+    assert!(!reader.has_uniform_natoms()?);
+
+    // From the second frame on, the atom counts still differ.
+    reader.read_frame(&mut Frame::default())?;
+    assert!(!reader.has_uniform_natoms()?);
+
+    // But the last frame is uniform on its own.
+    reader.read_frame(&mut Frame::default())?;
+    assert!(reader.has_uniform_natoms()?);
+
+    Ok(())
+}
+
+#[test]
+fn uniform_empty() -> std::io::Result<()> {
+    let mut reader = XTCReader::new(Cursor::new(Vec::new()));
+    assert!(reader.has_uniform_natoms()?);
+
+    Ok(())
+}
+
+#[test]
+fn offsets_uniform() -> std::io::Result<()> {
+    // Frames of 10 atoms that happen to all compress to the same size.
+    let mut reader = XTCReader::open(trajectories::TEN)?;
+    let (base, stride) = reader
+        .offsets_uniform()?
+        .expect("the frames have the same size");
+    assert_eq!(base, 0);
+    let offsets = reader.determine_offsets(None)?;
+    assert_eq!(offsets.len(), 10);
+    for (i, &offset) in offsets.iter().enumerate() {
+        assert_eq!(offset, base + i as u64 * stride);
+    }
+
+    // From a later frame, the base moves along and the position is left unchanged.
+    let frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    reader.read_frame(&mut Frame::default())?;
+    assert_eq!(reader.offsets_uniform()?, Some((stride, stride)));
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame)?;
+    assert_eq!(frame, frames[1]);
+
+    Ok(())
+}
+
+#[test]
+fn offsets_varying() -> std::io::Result<()> {
+    for path in [trajectories::ADK, trajectories::XYZ, trajectories::SMOL] {
+        let mut reader = XTCReader::open(path)?;
+        assert_eq!(reader.offsets_uniform()?, None, "{path}");
+    }
+
+    let mut reader = XTCReader::new(Cursor::new(Vec::new()));
+    assert_eq!(reader.offsets_uniform()?, None);
+
+    Ok(())
+}
+
+#[test]
+fn offsets_uniform_later_change() -> std::io::Result<()> {
+    let frame = |natoms: usize| Frame {
+        positions: (0..natoms * 3).map(|i| i as f32 * 0.125).collect(),
+        ..Frame::default()
+    };
+    let mut bytes = Vec::new();
+    for _ in 0..6 {
+        write_frame(&mut bytes, &frame(3), Magic::Xtc1995)?;
+    }
+    let mut reader = XTCReader::new(Cursor::new(bytes.clone()));
+    assert_eq!(reader.offsets_uniform()?, Some((0, 56 + 3 * 12)));
+
+    // A different frame after the inspected ones leaves a file that is not a whole number of
+    // strides long.
+    write_frame(&mut bytes, &frame(4), Magic::Xtc1995)?;
+    let mut reader = XTCReader::new(Cursor::new(bytes));
+    assert_eq!(reader.offsets_uniform()?, None);
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use molly::selection::AtomSelection;
+use molly::Frame;
+
+mod common;
+use common::trajectories;
+
+fn vec3(path: impl AsRef<std::path::Path>, atom_selection: AtomSelection) -> std::io::Result<()> {
+    let mut reader = molly::XTCReader::open(&path)?;
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while reader
+        .read_frame_with_selection(&mut frame, &atom_selection)
+        .is_ok()
+    {
+        frames.push(frame.clone());
+    }
+    reader.home()?;
+
+    let mut coords = Vec::new();
+    for frame in &frames {
+        let header = reader.read_frame_into_vec3(&mut coords, &atom_selection)?;
+        assert_eq!(header.step, frame.step);
+        assert_eq!(header.time, frame.time);
+        assert_eq!(header.boxvec, frame.boxvec);
+        assert_eq!(coords, frame.coords().collect::<Vec<_>>());
+    }
+    assert_eq!(reader.step, frames.len());
+    assert!(reader
+        .read_frame_into_vec3(&mut coords, &atom_selection)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn vec3_adk() -> std::io::Result<()> {
+    vec3(trajectories::ADK, AtomSelection::All)
+}
+
+#[test]
+fn vec3_adk_until() -> std::io::Result<()> {
+    vec3(trajectories::ADK, AtomSelection::Until(100))
+}
+
+#[test]
+fn vec3_adk_mask() -> std::io::Result<()> {
+    vec3(
+        trajectories::ADK,
+        AtomSelection::from_index_list(&[1, 5, 8, 13, 400]),
+    )
+}
+
+#[test]
+fn vec3_ten() -> std::io::Result<()> {
+    vec3(trajectories::TEN, AtomSelection::All)
+}
+
+#[test]
+fn vec3_xyz() -> std::io::Result<()> {
+    vec3(trajectories::XYZ, AtomSelection::Until(3))
+}
@@ -0,0 +1,158 @@
+use std::io::Cursor;
+
+use glam::Vec3;
+use molly::writer::{validate_box, XTCWriter};
+use molly::{BoxVec, Frame, Magic, XTCReader};
+
+mod common;
+use common::{trajectories, TempPath};
+
+fn write(frames: &[Frame], boxvec: Option<BoxVec>, magic: Magic) -> std::io::Result<Vec<u8>> {
+    let mut writer = XTCWriter::new(Vec::new());
+    writer.set_magic(magic);
+    writer.set_box(boxvec)?;
+    for frame in frames {
+        writer.write_frame(frame)?;
+    }
+    Ok(writer.file)
+}
+
+#[test]
+fn roundtrip() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    for magic in [Magic::Xtc1995, Magic::Xtc2023] {
+        let bytes = write(&frames, None, magic)?;
+        let read = XTCReader::new(Cursor::new(bytes)).read_all_frames()?;
+        assert_eq!(read, frames);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn create() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    let path = TempPath::new("writer_create");
+    let mut writer = XTCWriter::create(&path)?;
+    for frame in frames.iter() {
+        writer.write_frame(frame)?;
+    }
+    writer.flush()?;
+    assert_eq!(XTCReader::open(&path)?.read_all_frames()?, frames);
+
+    Ok(())
+}
+
+#[test]
+fn override_box() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+    let boxvec = BoxVec::from_cols(
+        Vec3::new(8.0, 0.0, 0.0),
+        Vec3::new(2.0, 7.0, 0.0),
+        Vec3::new(-3.0, 3.0, 6.0),
+    );
+    let bytes = write(&frames, Some(boxvec), Magic::Xtc1995)?;
+    let read = XTCReader::new(Cursor::new(bytes)).read_all_frames()?;
+    assert_eq!(read.len(), frames.len());
+    for (read, frame) in read.iter().zip(frames.iter()) {
+        assert_eq!(read.boxvec, boxvec);
+        assert_eq!(read.positions, frame.positions);
+        assert_eq!(read.step, frame.step);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invalid_box() {
+    let mut writer = XTCWriter::new(Vec::new());
+    let skewed = BoxVec::from_cols(
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(3.0, 4.0, 0.0),
+        Vec3::new(0.0, 0.0, 4.0),
+    );
+    let rotated = BoxVec::from_cols(
+        Vec3::new(4.0, 1.0, 0.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        Vec3::new(0.0, 0.0, 4.0),
+    );
+    let negative = BoxVec::from_diagonal(Vec3::new(4.0, -4.0, 4.0));
+    for boxvec in [skewed, rotated, negative] {
+        let err = writer.set_box(Some(boxvec)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    assert!(validate_box(&BoxVec::ZERO).is_ok());
+    assert!(validate_box(&BoxVec::from_diagonal(Vec3::splat(5.0))).is_ok());
+}
+
+#[test]
+fn append() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    let path = TempPath::new("writer_append");
+    std::fs::write(&path, write(&frames[..4], None, Magic::Xtc2023)?)?;
+
+    let mut writer = XTCWriter::append(&path)?;
+    for frame in &frames[4..] {
+        writer.write_frame(frame)?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    assert_eq!(XTCReader::open(&path)?.read_all_frames()?, frames);
+    // The appended frames continue with the magic number of the existing ones.
+    let headers = XTCReader::open(&path)?
+        .headers()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    assert!(headers.iter().all(|header| header.magic == Magic::Xtc2023));
+
+    Ok(())
+}
+
+#[test]
+fn append_natoms_mismatch() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    let path = TempPath::new("writer_append_natoms");
+    std::fs::write(&path, write(&frames, None, Magic::Xtc1995)?)?;
+
+    let mut writer = XTCWriter::append(&path)?;
+    let frame = frames[0].subset(&molly::selection::AtomSelection::Until(4));
+    let err = writer.write_frame(&frame).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    drop(writer);
+    // Nothing was written.
+    assert_eq!(XTCReader::open(&path)?.read_all_frames()?, frames);
+
+    Ok(())
+}
+
+#[test]
+fn append_incomplete() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::ADK)?.read_all_frames()?;
+    let mut bytes = write(&frames[..2], None, Magic::Xtc1995)?;
+    bytes.truncate(bytes.len() - 100);
+    let path = TempPath::new("writer_append_incomplete");
+    std::fs::write(&path, &bytes)?;
+
+    let err = XTCWriter::append(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    // The file is left as it was.
+    assert_eq!(std::fs::read(&path)?, bytes);
+
+    Ok(())
+}
+
+#[test]
+fn append_new() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::TEN)?.read_all_frames()?;
+    let path = TempPath::new("writer_append_new");
+
+    let mut writer = XTCWriter::append(&path)?;
+    for frame in frames.iter() {
+        writer.write_frame(frame)?;
+    }
+    writer.flush()?;
+    assert_eq!(XTCReader::open(&path)?.read_all_frames()?, frames);
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::io::Cursor;
+
+use molly::reader::calc_sizeint;
+use molly::selection::AtomSelection;
+use molly::writer::write_frame;
+use molly::{Frame, Header, Magic, Precision, XTCReader};
+
+mod common;
+use common::trajectories;
+
+/// Returns the position of atom `idx`, as integers on the grid of a precision of 1000.
+fn grid(frame: &Frame, idx: usize) -> [i32; 3] {
+    let pos = &frame.positions[idx * 3..idx * 3 + 3];
+    // Scale in double precision, since a float may not resolve the grid for large coordinates.
+    [0, 1, 2].map(|d| (pos[d] as f64 * 1000.0).round() as i32)
+}
+
+#[test]
+fn xyz_known_positions() -> std::io::Result<()> {
+    let frames = XTCReader::open(trajectories::XYZ)?.read_all_frames()?;
+    assert_eq!(frames.len(), 100);
+
+    let first = &frames[0];
+    assert_eq!((first.step, first.time), (0, 0.0));
+    assert_eq!(first.precision, Precision::Compressed(1000.0));
+    assert_eq!(first.natoms(), 100);
+    assert_eq!(grid(first, 0), [-11, -269, -48]);
+    assert_eq!(grid(first, 1), [-274, 8, 55]);
+    assert_eq!(grid(first, 2), [78, -15, -112]);
+    assert_eq!(grid(first, 50), [4, -60, 177]);
+    assert_eq!(grid(first, 99), [1, 258, 14]);
+
+    let second = &frames[1];
+    assert_eq!(grid(second, 0), [10, -271, 120]);
+    assert_eq!(grid(second, 99), [12, 296, 286]);
+
+    // After a hundred steps, the walkers have wandered off a fair bit further.
+    let last = &frames[99];
+    assert_eq!((last.step, last.time), (99, 99.0));
+    assert_eq!(grid(last, 0), [-2709, -1749, -254]);
+    assert_eq!(grid(last, 1), [-1884, 1906, 140]);
+
+    Ok(())
+}
+
+#[test]
+fn xyz_known_positions_selection() -> std::io::Result<()> {
+    let mut reader = XTCReader::open(trajectories::XYZ)?;
+    let mut frame = Frame::default();
+    reader.read_frame_with_selection(&mut frame, &AtomSelection::from_index_list(&[1, 50]))?;
+    assert_eq!(grid(&frame, 0), [-274, 8, 55]);
+    assert_eq!(grid(&frame, 1), [4, -60, 177]);
+
+    Ok(())
+}
+
+/// Positions that span more than `0xffffff` grid points along x, such that the sizes are too
+/// large to be multiplied and each coordinate is stored with its own number of bits.
+fn large_positions() -> Vec<[f32; 3]> {
+    (0..24)
+        .map(|i| {
+            let i = i as f32;
+            if (8.0..16.0).contains(&i) {
+                // A cluster of close atoms, which are stored as a run of small differences.
+                [2500.0 + i * 0.25, 1.0 + i * 0.125, -3.0]
+            } else {
+                [-10000.0 + i * 850.5, i * 0.5, i * -0.25]
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn large_coordinates() -> std::io::Result<()> {
+    let positions = large_positions();
+    let frame = Frame {
+        positions: positions.iter().flatten().copied().collect(),
+        precision: Precision::Compressed(1000.0),
+        ..Frame::default()
+    };
+
+    for magic in [Magic::Xtc1995, Magic::Xtc2023] {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, &frame, magic)?;
+
+        // Make sure that the large sizes are in fact used.
+        let prelude = &bytes[Header::SIZE + 4..];
+        let int = |i: usize| i32::from_be_bytes(prelude[i * 4..i * 4 + 4].try_into().unwrap());
+        let (minint, maxint) = ([int(0), int(1), int(2)], [int(3), int(4), int(5)]);
+        assert_eq!(minint[0], -10_000_000);
+        let (mut sizeint, mut bitsizeint) = ([0; 3], [0; 3]);
+        assert_eq!(
+            calc_sizeint(minint, maxint, &mut sizeint, &mut bitsizeint),
+            0
+        );
+        assert!(sizeint[0] > 0xffffff);
+
+        let mut read = Frame::default();
+        XTCReader::new(Cursor::new(bytes)).read_frame(&mut read)?;
+        assert_eq!(read.natoms(), positions.len());
+        for (idx, position) in positions.iter().enumerate() {
+            let expected = position.map(|v| (v as f64 * 1000.0).round() as i32);
+            assert_eq!(grid(&read, idx), expected, "atom {idx}");
+        }
+        // Spot checks of the known values, at both ends of the range.
+        assert_eq!(grid(&read, 0), [-10_000_000, 0, 0]);
+        assert_eq!(grid(&read, 23), [9_561_500, 11_500, -5_750]);
+        assert_eq!(grid(&read, 8), [2_502_000, 2_000, -3_000]);
+    }
+
+    Ok(())
+}